@@ -1,16 +1,28 @@
 use std::collections::HashMap;
+use std::fmt::Display;
 use std::fs::OpenOptions;
 use std::io::Read;
 use std::path::PathBuf;
+use std::rc::Rc;
 
-use eframe::egui::{self, Button, TextEdit};
+use eframe::egui::{self, Button, Color32, RichText, TextEdit};
 use egui_node_graph::{GraphEditorState, NodeResponse};
-use log::{error, info, warn};
+use log::info;
 use strum::EnumCount;
 use walkdir::WalkDir;
 
-use crate::ui::{ComboBoxEnum, NewWindowPrompt};
-use crate::window::{Window, WindowType};
+use crate::dependencies::Dependencies;
+use crate::diagnostics::Diagnostics;
+use crate::errors::AppError;
+use crate::json_view::JsonView;
+use crate::pack::{data_root, export_zip, PackSettings};
+use crate::preview::Preview;
+use crate::references::References;
+use crate::sidecar::json_hash;
+use crate::ui::{ComboBoxEnum, NewWindowPrompt, RenamePrompt};
+use crate::usages::{find_usages, rename, Usage};
+use crate::version::McVersion;
+use crate::window::{FileLocation, Window, WindowType};
 use crate::{
     nodes::{
         data_types::{decrease_node_list_length, increase_node_list_length, DataType, ValueType},
@@ -23,7 +35,7 @@ use crate::{
 pub type EditorStateType =
     GraphEditorState<NodeData, DataType, ValueType, NodeTemplate, GraphState>;
 
-#[derive(PartialEq, Eq, Hash, Clone)]
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
 pub struct Identifier {
     window_type: WindowType,
     namespace: String,
@@ -37,21 +49,31 @@ impl Identifier {
             window_type,
         }
     }
+    /// Parses `namespace:path`, the namespace defaults to `minecraft` like in game.
     pub fn from_string(s: String, window_type: WindowType) -> Option<Self> {
-        if let Some((namespace, path)) = s.split_once(":") {
-            Some(Self {
-                namespace: String::from(namespace),
-                path: String::from(path),
-                window_type,
-            })
-        } else {
-            None
+        let (namespace, path) = s.split_once(':').unwrap_or(("minecraft", &s));
+        if path.is_empty() {
+            return None;
         }
+        Some(Self {
+            namespace: String::from(namespace),
+            path: String::from(path),
+            window_type,
+        })
+    }
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+    pub fn window_type(&self) -> WindowType {
+        self.window_type
     }
 }
-impl ToString for Identifier {
-    fn to_string(&self) -> String {
-        format!("{}:{}", self.namespace, self.path)
+impl Display for Identifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.namespace, self.path)
     }
 }
 
@@ -69,99 +91,438 @@ impl PartialOrd for Identifier {
     }
 }
 
-pub struct App<'a> {
-    active_window: Option<&'a mut Window>,
+pub struct App {
+    active_window: Option<Identifier>,
     new_window_prompt: NewWindowPrompt,
     file_structure: [HashMap<Identifier, Window>; WindowType::COUNT],
     project_path: Option<PathBuf>,
     open_project_prompt: OpenProjectPrompt,
+    preview: Preview,
+    json_view: JsonView,
+    /// Everything in `file_structure`, shared with the graphs to check their references
+    references: Rc<References>,
+    /// File whose usages are listed and where they are
+    usages: Option<(Identifier, Vec<Usage>)>,
+    rename_prompt: Option<RenamePrompt>,
+    /// Shown while `Some`, only recomputed when refreshed
+    dependencies: Option<Dependencies>,
+    diagnostics: Diagnostics,
+    /// `pack.mcmeta` of the project
+    pack: PackSettings,
+    show_pack_settings: bool,
 }
-impl App<'_> {
-    pub fn new<'a>(_cc: &eframe::CreationContext, project_path_option: Option<PathBuf>) -> Self {
-        let mut map: [HashMap<Identifier, Window>; WindowType::COUNT] =
-            [HashMap::new(), HashMap::new(), HashMap::new()];
+impl App {
+    pub fn new(_cc: &eframe::CreationContext, project_path_option: Option<PathBuf>) -> Self {
+        let mut map: [HashMap<Identifier, Window>; WindowType::COUNT] = [
+            HashMap::new(),
+            HashMap::new(),
+            HashMap::new(),
+            HashMap::new(),
+        ];
+        let mut diagnostics = Diagnostics::default();
+        let mut pack = PackSettings::default();
         if let Some(project_path) = &project_path_option {
-            Self::load_from_fs(project_path, &mut map);
+            match PackSettings::load(project_path) {
+                Ok(settings) => pack = settings,
+                Err(e) => diagnostics.push(e),
+            }
+            diagnostics.extend(Self::load_from_fs(project_path, &pack, &mut map));
         }
         let has_path = project_path_option.is_some();
-        Self {
+        let mut app = Self {
             file_structure: map,
             active_window: None,
             new_window_prompt: NewWindowPrompt::new(false),
             project_path: project_path_option,
             open_project_prompt: OpenProjectPrompt::new(!has_path),
+            preview: Preview::new(),
+            json_view: JsonView::new(),
+            references: Rc::default(),
+            usages: None,
+            rename_prompt: None,
+            dependencies: None,
+            diagnostics,
+            pack,
+            show_pack_settings: false,
+        };
+        app.update_references();
+        app
+    }
+    /// Has to be called whenever a file is added to or removed from `file_structure`.
+    fn update_references(&mut self) {
+        self.references = Rc::new(References::new(
+            self.file_structure.iter().flat_map(|map| map.keys()),
+        ));
+    }
+    /// Moves the file of `from` to `to` and points every reference to it at the new identifier,
+    /// see `usages::rename`. Returns how many references were changed.
+    fn rename(&mut self, from: &Identifier, to: Identifier) -> Result<usize, AppError> {
+        let project_path = self
+            .project_path
+            .as_ref()
+            .ok_or_else(|| AppError::FileRead("no project is open".to_string()))?;
+        let (count, errors) = rename(&mut self.file_structure, project_path, from, &to)?;
+        self.diagnostics.extend(errors);
+        if self.active_window.as_ref() == Some(from) {
+            self.active_window = Some(to.clone());
+        }
+        if self.usages.as_ref().is_some_and(|(id, _)| id == from) {
+            self.usages = Some((to.clone(), find_usages(&self.file_structure, &to)));
+        }
+        self.update_references();
+        Ok(count)
+    }
+    fn usages_ui(&mut self, ctx: &egui::Context) {
+        let (target, usages) = match &self.usages {
+            Some(usages) => usages,
+            None => return,
+        };
+        let mut open = true;
+        let mut go_to = None;
+        egui::Window::new(format!("Usages of {}", target))
+            .open(&mut open)
+            .show(ctx, |ui| {
+                if usages.is_empty() {
+                    ui.label("no usages");
+                }
+                for usage in usages {
+                    if ui
+                        .button(format!("{} → {}", usage.window, usage.description))
+                        .clicked()
+                    {
+                        go_to = Some((usage.window.clone(), usage.node_id));
+                    }
+                }
+            });
+        if !open {
+            self.usages = None;
+        }
+        if let Some((id, node_id)) = go_to {
+            if let Some(window) = self.file_structure[id.window_type as usize].get_mut(&id) {
+                window.state.selected_nodes = vec![node_id];
+                self.active_window = Some(id);
+            }
+        }
+    }
+    fn dependencies_ui(&mut self, ctx: &egui::Context) {
+        let dependencies = match &self.dependencies {
+            Some(dependencies) => dependencies,
+            None => return,
+        };
+        let mut open = true;
+        let mut refresh = false;
+        let mut go_to = None;
+        egui::Window::new("Dependencies")
+            .open(&mut open)
+            .default_size([800., 500.])
+            .show(ctx, |ui| {
+                refresh = ui.button("refresh").clicked();
+                go_to = dependencies.ui(ui);
+            });
+        if let Some(id) = go_to {
+            self.active_window = Some(id);
+        }
+        if !open {
+            self.dependencies = None;
+        } else if refresh {
+            self.dependencies = Some(Dependencies::new(&self.file_structure, &self.references));
         }
     }
-    fn load_from_fs(project_path: &PathBuf, map: &mut [HashMap<Identifier, Window>]) {
-        for entry in WalkDir::new(project_path)
+    fn pack_settings_ui(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_pack_settings;
+        let mut export = false;
+        egui::Window::new("Pack settings")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                let version = self.pack.version();
+                self.pack.ui(ui);
+                if self.pack.version() != version {
+                    let errors =
+                        Self::change_version(&mut self.file_structure, self.pack.version());
+                    self.diagnostics.extend(errors);
+                }
+                ui.separator();
+                export = ui.button("export zip").clicked();
+            });
+        self.show_pack_settings = open;
+        let project_path = match (&self.project_path, export) {
+            (Some(project_path), true) => project_path,
+            _ => return,
+        };
+        let default_name = project_path
+            .file_name()
+            .map(|name| format!("{}.zip", name.to_string_lossy()))
+            .unwrap_or_default();
+        if let Some(zip_path) = tinyfiledialogs::save_file_dialog_with_filter(
+            "Export datapack",
+            &default_name,
+            &["*.zip"],
+            "zip archive",
+        ) {
+            match export_zip(
+                project_path,
+                &self.pack,
+                &self.file_structure,
+                &PathBuf::from(&zip_path),
+            ) {
+                Ok(count) => info!("Exported {} files to {}", count, zip_path),
+                Err(errors) => self.diagnostics.extend(errors),
+            }
+        }
+    }
+    fn rename_prompt_ui(&mut self, ctx: &egui::Context) {
+        let prompt = match &mut self.rename_prompt {
+            Some(prompt) => prompt,
+            None => return,
+        };
+        let mut open = true;
+        let mut rename = None;
+        egui::Window::new(format!("Rename {}", prompt.from))
+            .collapsible(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.add(TextEdit::singleline(&mut prompt.to).hint_text("namespace:path"));
+                let to = Identifier::from_string(prompt.to.clone(), prompt.from.window_type);
+                if ui
+                    .add_enabled(to.is_some(), Button::new("Rename"))
+                    .clicked()
+                {
+                    rename = to;
+                }
+                if let Some(e) = &prompt.error {
+                    ui.colored_label(egui::Color32::RED, e);
+                }
+            });
+        if !open {
+            self.rename_prompt = None;
+        }
+        if let Some(to) = rename {
+            let from = self.rename_prompt.as_ref().unwrap().from.clone();
+            match self.rename(&from, to) {
+                Ok(count) => {
+                    info!("Renamed {}, {} references changed", from, count);
+                    self.rename_prompt = None;
+                }
+                Err(e) => self.rename_prompt.as_mut().unwrap().error = Some(e.to_string()),
+            }
+        }
+    }
+    /// Builds every window again for `version`, the fields it doesn't read are kept as unknown.
+    /// Each window can undo the change in one step. Returns what couldn't be built.
+    pub fn change_version(
+        map: &mut [HashMap<Identifier, Window>],
+        version: McVersion,
+    ) -> Vec<AppError> {
+        let mut errors = Vec::new();
+        for window in map.iter_mut().flat_map(|map| map.values_mut()) {
+            if window.user_state.version == version {
+                continue;
+            }
+            let id = window.identifier();
+            let json = match window.serialize() {
+                Ok(json) => json,
+                Err(e) => {
+                    // The graph can't be read back, it is kept as it is until it's finished
+                    window.user_state.version = version;
+                    errors.push(e.with_window(id));
+                    continue;
+                }
+            };
+            let rebuilt = window.record_edit(|window| {
+                window.user_state.version = version;
+                window.read_json(&json)
+            });
+            if window.serialize().ok().as_ref() != Some(&json) {
+                window.dirty = true;
+            }
+            errors.extend(rebuilt.into_iter().map(|e| e.with_window(id.clone())));
+        }
+        errors
+    }
+    /// Opens every file of the project for the version of `pack`, returns what couldn't be read.
+    pub fn load_from_fs(
+        project_path: &PathBuf,
+        pack: &PackSettings,
+        map: &mut [HashMap<Identifier, Window>],
+    ) -> Vec<AppError> {
+        let mut errors = Vec::new();
+        let data_root = data_root(project_path);
+        let known_folders = pack.known_folders();
+        for entry in WalkDir::new(&data_root)
             .follow_links(true)
             .into_iter()
+            // Hidden files, like the sidecars of the editor, aren't part of the pack
+            .filter_entry(|e| e.depth() == 0 || !e.file_name().to_string_lossy().starts_with('.'))
             .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
         {
-            let f_name = entry.file_name().to_string_lossy();
-            if f_name.ends_with(".json") {
-                if let Ok(file) = OpenOptions::new().read(true).write(true).open(entry.path()) {
-                    let new_window =
-                        Window::from_file(file, entry.path().to_path_buf(), &project_path);
-                    match new_window {
-                        Ok(mut window) => {
-                            let mut buf = String::new();
-                            if let Err(e) = window.file.as_ref().unwrap().read_to_string(&mut buf) {
-                                warn!("{}", e);
-                            };
-                            if let Ok(json) = json::parse(&buf) {
-                                window.deserialize(&json);
-                            }
-                            map[window.window_type as usize].insert(
-                                Identifier::new(
-                                    window.namespace.clone(),
-                                    window.name.clone(),
-                                    window.window_type,
-                                ),
-                                window,
-                            );
-                        }
-                        Err(e) => error!("{}", e.to_string()),
+            let path = entry.path().to_path_buf();
+            let relative_path = path.strip_prefix(&data_root).unwrap_or(&path);
+            // Without `data/`, files next to the namespaces aren't part of the pack
+            if data_root == *project_path && relative_path.components().count() < 2 {
+                continue;
+            }
+            match Window::locate(relative_path, &known_folders) {
+                FileLocation::Window(..) => {}
+                FileLocation::Other => continue,
+                FileLocation::Stray => {
+                    errors.push(AppError::FileStructure(path.into()));
+                    continue;
+                }
+            }
+            let file = match OpenOptions::new().read(true).write(true).open(&path) {
+                Ok(file) => file,
+                Err(e) => {
+                    errors.push(AppError::FileRead(e.to_string()).with_file(path));
+                    continue;
+                }
+            };
+            let mut buf = String::new();
+            let read_error = (&file).read_to_string(&mut buf).err();
+            let mut window =
+                match Window::from_file(file, path.clone(), project_path, pack.version()) {
+                    Ok(window) => window,
+                    Err(e) => {
+                        errors.push(e.with_file(path));
+                        continue;
+                    }
+                };
+            if let Some(e) = read_error {
+                window.load_failed = true;
+                errors.push(
+                    AppError::FileRead(e.to_string())
+                        .with_window(window.identifier())
+                        .with_file(path.clone()),
+                );
+            };
+            window.json_hash = Some(json_hash(&buf));
+            if !window.load_failed && !window.load_sidecar(project_path, &buf) {
+                match json::parse(&buf) {
+                    Ok(json) => {
+                        let file_errors = window.deserialize(&json);
+                        window.load_failed = !file_errors.is_empty();
+                        errors.extend(file_errors.into_iter().map(|e| e.with_file(path.clone())))
+                    }
+                    Err(e) => {
+                        window.load_failed = true;
+                        errors.push(
+                            AppError::JsonError(e)
+                                .with_window(window.identifier())
+                                .with_file(path),
+                        )
                     }
-                } else {
-                    error!("fs error: {}", entry.path().display());
                 }
             }
+            map[window.window_type as usize].insert(window.identifier(), window);
         }
+        errors
     }
-    fn serialize_all(&mut self) -> Result<(), ()> {
+    /// Saves every file and its editor state, the problems of every file are checked again.
+    /// What was found while loading a file is dropped once it is saved without problems.
+    fn serialize_all(&mut self) -> Result<(), Vec<AppError>> {
+        let mut errors = Vec::new();
         for filetype_map in self.file_structure.iter_mut() {
             for window in filetype_map.values_mut() {
+                // What was read of a broken file would overwrite all of it
+                if window.load_failed && !window.history.can_undo() {
+                    continue;
+                }
                 info!("Serializing window {}", window);
-                if let Some(json) = window.serialize() {
-                    if let Err(e) = window.save_to_file(json.pretty(4)) {
-                        error!(
-                            "failed to save to file: {} ({})",
-                            e,
-                            window.filepath.display()
-                        )
-                    }
-                } else {
-                    error!("Window failed to serialize {}", window)
+                let window_errors = window.save(self.project_path.as_deref());
+                if window_errors.is_empty() {
+                    self.diagnostics.clear_window(&window.identifier());
+                }
+                errors.extend(window_errors);
+            }
+        }
+        if let Some(project_path) = &self.project_path {
+            if self.pack.dirty {
+                if let Err(e) = self.pack.save(project_path) {
+                    errors.push(e);
                 }
             }
         }
-        Ok(())
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
     }
 }
 
-impl eframe::App for App<'_> {
+impl eframe::App for App {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         egui::TopBottomPanel::top("top").show(ctx, |ui| {
             egui::menu::bar(ui, |ui| {
                 egui::widgets::global_dark_light_mode_switch(ui);
                 if ui.button("save all").clicked() {
-                    if let Err(_) = self.serialize_all() {
-                        warn!("Something went wrong when saving.");
+                    let errors = match self.serialize_all() {
+                        Ok(()) => Vec::new(),
+                        Err(errors) => errors,
+                    };
+                    self.diagnostics.set_save_errors(errors);
+                }
+                let problems = format!("problems ({})", self.diagnostics.len());
+                ui.toggle_value(&mut self.diagnostics.show, problems);
+                if let Some(window) = self
+                    .active_window
+                    .as_ref()
+                    .and_then(|id| self.file_structure[id.window_type as usize].get_mut(id))
+                {
+                    if ui
+                        .add_enabled(window.history.can_undo(), Button::new("undo"))
+                        .clicked()
+                    {
+                        window.undo();
+                    }
+                    if ui
+                        .add_enabled(window.history.can_redo(), Button::new("redo"))
+                        .clicked()
+                    {
+                        window.redo();
                     }
+                    if ui.button("arrange").clicked() {
+                        window.arrange_all();
+                    }
+                    if ui
+                        .add_enabled(
+                            !window.state.selected_nodes.is_empty(),
+                            Button::new("arrange selection"),
+                        )
+                        .clicked()
+                    {
+                        window.arrange_selection();
+                    }
+                }
+                ui.add_enabled_ui(self.project_path.is_some(), |ui| {
+                    ui.toggle_value(&mut self.show_pack_settings, "pack");
+                });
+                ui.toggle_value(&mut self.preview.show, "preview");
+                ui.toggle_value(&mut self.json_view.show, "json");
+                let mut show_dependencies = self.dependencies.is_some();
+                if ui
+                    .toggle_value(&mut show_dependencies, "dependencies")
+                    .changed()
+                {
+                    self.dependencies = show_dependencies
+                        .then(|| Dependencies::new(&self.file_structure, &self.references));
                 }
             });
         });
+        if self.diagnostics.show {
+            let clicked = egui::TopBottomPanel::bottom("problems")
+                .resizable(true)
+                .show(ctx, |ui| self.diagnostics.ui(ui))
+                .inner;
+            if let Some((id, node_id)) = clicked {
+                if let Some(window) = self.file_structure[id.window_type as usize].get_mut(&id) {
+                    if let Some(node_id) = node_id {
+                        window.focus_node(node_id);
+                    }
+                    self.active_window = Some(id);
+                }
+            }
+        }
         egui::SidePanel::left("outline").show(ctx, |ui| {
             ui.add_enabled_ui(self.project_path.is_some(), |ui| {
                 ui.with_layout(egui::Layout::top_down_justified(egui::Align::Min), |ui| {
@@ -198,9 +559,10 @@ impl eframe::App for App<'_> {
                             //});
                             let enable = self.new_window_prompt.are_strings_correct();
                             if ui.add_enabled(enable, Button::new("Add file")).clicked() {
-                                let win = self
-                                    .new_window_prompt
-                                    .make_window(&self.project_path.as_ref().unwrap());
+                                let win = self.new_window_prompt.make_window(
+                                    self.project_path.as_ref().unwrap(),
+                                    self.pack.version(),
+                                );
                                 self.file_structure[win.window_type as usize].insert(
                                     Identifier::new(
                                         win.namespace.clone(),
@@ -209,6 +571,7 @@ impl eframe::App for App<'_> {
                                     ),
                                     win,
                                 );
+                                self.update_references();
                                 self.new_window_prompt.reset();
                                 true
                             } else {
@@ -220,6 +583,7 @@ impl eframe::App for App<'_> {
                     }
                     self.new_window_prompt.show = should_open;
 
+                    let mut find_usages_of = None;
                     for (i, map) in &mut self.file_structure.iter_mut().enumerate() {
                         if !map.is_empty() {
                             ui.group(|ui| {
@@ -229,41 +593,124 @@ impl eframe::App for App<'_> {
                                 v.sort_unstable_by(|(id, _), (id2, _)| {
                                     id.partial_cmp(id2).unwrap()
                                 });
-                                for (_id, win) in v {
-                                    if ui
-                                        .button(format!("{}:{}", &*win.namespace, &*win.name))
-                                        .clicked()
-                                    {
-                                        self.active_window = Some(win);
+                                for (id, win) in v {
+                                    let mut text = RichText::new(format!(
+                                        "{}:{}",
+                                        &*win.namespace, &*win.name
+                                    ));
+                                    if win.load_failed {
+                                        text = text.color(Color32::LIGHT_RED);
                                     }
+                                    let mut response = ui.button(text);
+                                    if win.load_failed {
+                                        response = response.on_hover_text(
+                                            "couldn't be read completely, see problems",
+                                        );
+                                    }
+                                    if response.clicked() {
+                                        self.active_window = Some(id.clone());
+                                    }
+                                    response.context_menu(|ui| {
+                                        if ui.button("find usages").clicked() {
+                                            find_usages_of = Some(id.clone());
+                                            ui.close_menu();
+                                        }
+                                        if ui.button("rename").clicked() {
+                                            self.rename_prompt =
+                                                Some(RenamePrompt::new(id.clone()));
+                                            ui.close_menu();
+                                        }
+                                    });
                                 }
                             });
                         }
                     }
+                    if let Some(id) = find_usages_of {
+                        let usages = find_usages(&self.file_structure, &id);
+                        self.usages = Some((id, usages));
+                    }
                 });
             });
         });
-        if let Some(window) = &mut self.active_window {
-            let graph_response = egui::CentralPanel::default()
+        if let Some(window) = self
+            .active_window
+            .as_ref()
+            .and_then(|id| self.file_structure[id.window_type as usize].get(id))
+            .filter(|window| {
+                matches!(
+                    window.window_type,
+                    WindowType::DensityFunction | WindowType::NoiseSettings
+                )
+            })
+        {
+            if self.preview.show {
+                let file_structure = &self.file_structure;
+                let resolve = |id: &Identifier| file_structure[id.window_type as usize].get(id);
+                egui::SidePanel::right("preview")
+                    .default_width(300.)
+                    .show(ctx, |ui| self.preview.ui(ui, window, &resolve));
+            }
+        }
+        if self.json_view.show {
+            if let Some(window) = self
+                .active_window
+                .as_ref()
+                .and_then(|id| self.file_structure[id.window_type as usize].get_mut(id))
+            {
+                let read = egui::SidePanel::right("json")
+                    .default_width(400.)
+                    .show(ctx, |ui| self.json_view.ui(ui, window))
+                    .inner;
+                if read {
+                    self.diagnostics.clear_window(&window.identifier());
+                }
+            }
+        }
+        let mut go_to = None;
+        if let Some(window) = self
+            .active_window
+            .as_ref()
+            .and_then(|id| self.file_structure[id.window_type as usize].get_mut(id))
+        {
+            window.user_state.references = self.references.clone();
+            let (graph_response, editor_rect) = egui::CentralPanel::default()
                 .show(ctx, |ui| {
-                    window
-                        .state
-                        .draw_graph_editor(ui, AllNodeTemplates, &mut window.user_state)
+                    let editor_rect = ui.max_rect();
+                    let response = window.state.draw_graph_editor(
+                        ui,
+                        AllNodeTemplates(self.pack.version()),
+                        &mut window.user_state,
+                    );
+                    (response, editor_rect)
                 })
                 .inner;
+            if window.arrange_when_drawn {
+                window.arrange_when_drawn = false;
+                window.arrange_all();
+            }
 
-            for node_response in graph_response.node_responses {
-                if let NodeResponse::User(user_event) = node_response {
+            for node_response in &graph_response.node_responses {
+                if let NodeResponse::User(user_event) = node_response.clone() {
                     match user_event {
                         Response::SetActiveNode(node_id) => {
                             window.user_state.active_node = Some(node_id)
                         }
                         Response::ClearActiveNode => window.user_state.active_node = None,
-                        Response::IncreaseInputs(node_id) => {
-                            increase_node_list_length(&mut window.state.graph, node_id);
+                        Response::IncreaseInputs(node_id, list) => {
+                            if increase_node_list_length(&mut window.state.graph, node_id, &list)
+                                .is_none()
+                            {
+                                self.diagnostics.push(
+                                    AppError::WrongData(
+                                        format!("couldn't add an entry to {}", list).into(),
+                                    )
+                                    .with_window(window.identifier())
+                                    .with_node(node_id),
+                                );
+                            }
                         }
-                        Response::DecreaseInputs(node_id) => {
-                            decrease_node_list_length(&mut window.state.graph, node_id);
+                        Response::DecreaseInputs(node_id, list) => {
+                            decrease_node_list_length(&mut window.state.graph, node_id, &list);
                         }
                         Response::ChangeNodeType(node_id, new_template) => rebuild_node(
                             node_id,
@@ -271,19 +718,37 @@ impl eframe::App for App<'_> {
                             &mut window.user_state,
                             new_template,
                         ),
-                        Response::ChangeInputLabel(node_id, from, to) => {
+                        Response::GoToDefinition(id) => go_to = Some(id),
+                        Response::RemoveUnknownField(node_id, i) => {
                             if let Some(node) = window.state.graph.nodes.get_mut(node_id) {
-                                if let Some(mut m) =
-                                    node.inputs.iter_mut().find(|(s, _)| s == &*from)
-                                {
-                                    m.0 = to.to_string();
+                                if i < node.user_data.unknown_fields.len() {
+                                    node.user_data.unknown_fields.remove(i);
                                 }
                             }
                         }
                     }
                 }
             }
+            // Pasted nodes go where the pointer is, or in the middle of the editor
+            let pointer = ctx
+                .input()
+                .pointer
+                .hover_pos()
+                .filter(|pos| editor_rect.contains(*pos))
+                .unwrap_or_else(|| editor_rect.center());
+            let paste_pos = pointer - window.state.pan_zoom.pan - editor_rect.min.to_vec2();
+            let clipboard_errors = window.handle_clipboard_keys(ctx, paste_pos);
+            self.diagnostics.extend(clipboard_errors);
+            window.record_history(ctx, &graph_response.node_responses);
+            window.handle_history_keys(ctx);
         }
+        if let Some(id) = go_to.filter(|id| self.references.in_project(id)) {
+            self.active_window = Some(id);
+        }
+        self.usages_ui(ctx);
+        self.dependencies_ui(ctx);
+        self.rename_prompt_ui(ctx);
+        self.pack_settings_ui(ctx);
         {
             let mut should_open = self.open_project_prompt.show;
             let inner_response = egui::Window::new("Open Project Folder")
@@ -291,10 +756,17 @@ impl eframe::App for App<'_> {
                 .show(ctx, |ui| {
                     if self.open_project_prompt.ui_entered(ui) {
                         self.project_path = Some(PathBuf::from(&self.open_project_prompt.path));
-                        Self::load_from_fs(
-                            &self.project_path.as_ref().unwrap(),
+                        match PackSettings::load(self.project_path.as_ref().unwrap()) {
+                            Ok(settings) => self.pack = settings,
+                            Err(e) => self.diagnostics.push(e),
+                        }
+                        let errors = Self::load_from_fs(
+                            self.project_path.as_ref().unwrap(),
+                            &self.pack,
                             &mut self.file_structure,
                         );
+                        self.diagnostics.extend(errors);
+                        self.update_references();
                         true
                     } else {
                         false
@@ -0,0 +1,244 @@
+//! Commands that work on a datapack without opening the editor, for scripts and pre-commit hooks.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use json::JsonValue;
+use strum::EnumCount;
+
+use crate::app::{App, Identifier};
+use crate::errors::AppError;
+use crate::pack::{data_root, PackSettings};
+use crate::references::References;
+use crate::sidecar::json_hash;
+use crate::window::{Window, WindowType};
+
+pub const USAGE: &str = "\
+usage:
+    datapack_creato_rs [<pack>]                 open the editor
+    datapack_creato_rs validate <pack>          list problems of every file
+    datapack_creato_rs fmt [--check] <pack>     write every file again the way the editor saves it
+    datapack_creato_rs check-roundtrip <pack>   list what saving every file would change";
+
+type FileStructure = [HashMap<Identifier, Window>; WindowType::COUNT];
+
+/// Runs the command in `args` (without the program name). Returns `None` if they don't start
+/// with a command, the editor is opened then.
+pub fn run(args: &[String]) -> Option<ExitCode> {
+    let (command, args) = args.split_first()?;
+    let (flags, paths): (Vec<&String>, Vec<&String>) =
+        args.iter().partition(|arg| arg.starts_with("--"));
+    let allowed_flags: &[&str] = match command.as_str() {
+        "validate" | "check-roundtrip" => &[],
+        "fmt" => &["--check"],
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            return Some(ExitCode::SUCCESS);
+        }
+        _ => return None,
+    };
+    let usage_error = |message: String| {
+        eprintln!("{}\n\n{}", message, USAGE);
+        Some(ExitCode::from(2))
+    };
+    if let Some(flag) = flags
+        .iter()
+        .find(|flag| !allowed_flags.contains(&flag.as_str()))
+    {
+        return usage_error(format!("unknown option {} for {}", flag, command));
+    }
+    let pack = match paths.as_slice() {
+        [pack] => PathBuf::from(pack),
+        _ => return usage_error(format!("{} needs the path of one pack", command)),
+    };
+    let success = match command.as_str() {
+        "validate" => validate(&pack),
+        "fmt" => fmt(&pack, !flags.is_empty()),
+        _ => check_roundtrip(&pack),
+    };
+    Some(if success {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+/// Opens every file of the pack, like the editor does.
+fn load(pack: &PathBuf) -> (FileStructure, Vec<AppError>) {
+    let mut file_structure = FileStructure::default();
+    let (settings, mut errors) = match PackSettings::load(pack) {
+        Ok(settings) => (settings, Vec::new()),
+        Err(e) => (PackSettings::default(), vec![e]),
+    };
+    errors.extend(App::load_from_fs(pack, &settings, &mut file_structure));
+    (file_structure, errors)
+}
+
+/// Windows sorted by their identifier, so output doesn't change between runs.
+fn sorted(file_structure: &mut FileStructure) -> Vec<&mut Window> {
+    let mut windows: Vec<&mut Window> = file_structure
+        .iter_mut()
+        .flat_map(|map| map.values_mut())
+        .collect();
+    windows.sort_unstable_by_key(|window| window.identifier().to_string());
+    windows
+}
+
+fn report(errors: &[AppError]) {
+    for error in errors {
+        eprintln!("{}", error);
+    }
+}
+
+/// Problems of reading and writing every file, and references that don't resolve.
+fn validate(pack: &PathBuf) -> bool {
+    let (mut file_structure, mut errors) = load(pack);
+    let references = References::new(file_structure.iter().flat_map(|map| map.keys()));
+    let windows = sorted(&mut file_structure);
+    for window in &windows {
+        if window.load_failed {
+            continue;
+        }
+        if let Err(e) = window.serialize() {
+            errors.push(e);
+        }
+        for (window_type, name) in window.outgoing_references() {
+            match window_type {
+                Some(window_type) if !references.resolves(window_type, &name) => errors.push(
+                    AppError::WrongData(format!("unresolved reference {}", name).into())
+                        .with_window(window.identifier()),
+                ),
+                _ => {}
+            }
+        }
+    }
+    report(&errors);
+    println!("{} files, {} problems", windows.len(), errors.len());
+    errors.is_empty()
+}
+
+/// Saves every file again, with `check` only lists the files that would change.
+fn fmt(pack: &PathBuf, check: bool) -> bool {
+    let (mut file_structure, mut errors) = load(pack);
+    let mut changed = 0;
+    for window in sorted(&mut file_structure) {
+        // What couldn't be read would be lost
+        if window.load_failed {
+            continue;
+        }
+        let path = data_root(pack).join(window.relative_path());
+        let s = match window.serialize_to_string() {
+            Ok(s) => s,
+            Err(e) => {
+                errors.push(e);
+                continue;
+            }
+        };
+        if fs::read_to_string(&path).ok().as_deref() == Some(s.as_str()) {
+            continue;
+        }
+        changed += 1;
+        if check {
+            println!("would format {}", path.display());
+            continue;
+        }
+        let id = window.identifier();
+        let write_error = |e: std::io::Error, file: PathBuf| {
+            AppError::FileWrite(e.to_string())
+                .with_window(id.clone())
+                .with_file(file)
+        };
+        if let Err(e) = window.save_to_file(s.clone()) {
+            errors.push(write_error(e, path));
+            continue;
+        }
+        println!("formatted {}", path.display());
+        // Keeps the layout made in the editor
+        let sidecar_path = window.sidecar_path(pack);
+        if sidecar_path.exists() {
+            window.json_hash = Some(json_hash(&s));
+            if let Err(e) = window.save_sidecar(pack) {
+                errors.push(write_error(e, sidecar_path));
+            }
+        }
+    }
+    report(&errors);
+    errors.is_empty() && !(check && changed > 0)
+}
+
+/// Lists the differences between every file and what the editor would save in its place.
+fn check_roundtrip(pack: &PathBuf) -> bool {
+    let (mut file_structure, mut errors) = load(pack);
+    let mut changed = 0;
+    for window in sorted(&mut file_structure) {
+        let path = data_root(pack).join(window.relative_path());
+        let expected = match fs::read_to_string(&path).map(|s| json::parse(&s)) {
+            Ok(Ok(expected)) => expected,
+            // Already reported while loading
+            _ => continue,
+        };
+        let actual = match window.serialize() {
+            Ok(actual) => actual,
+            Err(e) => {
+                errors.push(e);
+                continue;
+            }
+        };
+        let diffs = json_diff(&expected, &actual);
+        if !diffs.is_empty() {
+            changed += 1;
+            println!("{}:\n    {}", path.display(), diffs.join("\n    "));
+        }
+    }
+    report(&errors);
+    errors.is_empty() && changed == 0
+}
+
+/// Paths of the values that differ between `expected` and `actual`. Key order and the way
+/// numbers are written don't matter.
+pub fn json_diff(expected: &JsonValue, actual: &JsonValue) -> Vec<String> {
+    let mut diffs = Vec::new();
+    diff(expected, actual, "$", &mut diffs);
+    diffs
+}
+
+fn diff(expected: &JsonValue, actual: &JsonValue, path: &str, diffs: &mut Vec<String>) {
+    match (expected, actual) {
+        (JsonValue::Object(_), JsonValue::Object(_)) => {
+            for (key, value) in expected.entries() {
+                let path = format!("{}.{}", path, key);
+                if actual.has_key(key) {
+                    diff(value, &actual[key], &path, diffs);
+                } else {
+                    diffs.push(format!("{}: missing", path));
+                }
+            }
+            for (key, _) in actual.entries().filter(|(key, _)| !expected.has_key(key)) {
+                diffs.push(format!("{}.{}: not in the original", path, key));
+            }
+        }
+        (JsonValue::Array(expected), JsonValue::Array(actual)) => {
+            if expected.len() != actual.len() {
+                diffs.push(format!(
+                    "{}: {} entries instead of {}",
+                    path,
+                    actual.len(),
+                    expected.len()
+                ));
+            }
+            for (i, (expected, actual)) in expected.iter().zip(actual).enumerate() {
+                diff(expected, actual, &format!("{}[{}]", path, i), diffs);
+            }
+        }
+        _ if expected.is_number() && actual.is_number() => {
+            if expected.as_f64() != actual.as_f64() {
+                diffs.push(format!("{}: {} instead of {}", path, actual, expected));
+            }
+        }
+        _ if expected.is_string() && expected.as_str() == actual.as_str() => {}
+        _ if !expected.is_string() && expected == actual => {}
+        _ => diffs.push(format!("{}: {} instead of {}", path, actual, expected)),
+    }
+}
@@ -0,0 +1,299 @@
+//! Copying nodes as the JSON they stand for, and adding nodes for JSON pasted from anywhere.
+
+use std::collections::HashSet;
+
+use eframe::egui::{self, Event, Key};
+use eframe::epaint::{Pos2, Vec2};
+use egui_node_graph::NodeId;
+use json::JsonValue;
+
+use crate::errors::AppError;
+use crate::nodes::data_types::{ComplexDataType, DataType, ValueType};
+use crate::nodes::node_types::NodeTemplate;
+use crate::nodes::{add_node, GraphType};
+use crate::serializer::canonical;
+use crate::window::Window;
+
+/// Types pasted JSON is tried as when it isn't known what it is, the first that reads it
+/// without problems is taken.
+const PASTE_TYPES: [ComplexDataType; 13] = [
+    ComplexDataType::DensityFunction,
+    ComplexDataType::SurfaceRule,
+    ComplexDataType::SurfaceRuleCondition,
+    ComplexDataType::Noise,
+    ComplexDataType::Spline,
+    ComplexDataType::SplinePoint,
+    ComplexDataType::ParameterPoint,
+    ComplexDataType::Spawner,
+    ComplexDataType::SpawnCost,
+    ComplexDataType::MoodSound,
+    ComplexDataType::AdditionsSound,
+    ComplexDataType::Music,
+    ComplexDataType::Particle,
+];
+
+/// Nodes connected to the inputs of `node_id`.
+fn input_nodes(graph: &GraphType, node_id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+    graph[node_id]
+        .input_ids()
+        .filter_map(|input_id| graph.connection(input_id))
+        .map(|output_id| graph.get_output(output_id).node)
+}
+
+impl Window {
+    /// Selected nodes that aren't part of what another selected node is made of, top to
+    /// bottom. The output node is never copied.
+    fn copied_nodes(&self) -> Vec<NodeId> {
+        let graph = &self.state.graph;
+        let selected: Vec<NodeId> =
+            self.state
+                .selected_nodes
+                .iter()
+                .copied()
+                .filter(|id| {
+                    graph.nodes.get(*id).is_some_and(|node| {
+                        !matches!(node.user_data.template, NodeTemplate::Output(_))
+                    })
+                })
+                .collect();
+        let mut below = HashSet::new();
+        for id in &selected {
+            let mut stack: Vec<NodeId> = input_nodes(graph, *id).collect();
+            while let Some(next) = stack.pop() {
+                if below.insert(next) {
+                    stack.extend(input_nodes(graph, next));
+                }
+            }
+        }
+        let mut copied: Vec<NodeId> = selected
+            .into_iter()
+            .filter(|id| !below.contains(id))
+            .collect();
+        let y = |id: &NodeId| self.state.node_positions.get(*id).map_or(0., |pos| pos.y);
+        copied.sort_by(|a, b| y(a).total_cmp(&y(b)));
+        copied
+    }
+
+    /// JSON of the selected nodes with everything connected to them, an array if several are
+    /// selected. `None` if no node is selected.
+    pub fn copy_selection(&self) -> Result<Option<String>, AppError> {
+        let mut values = self
+            .copied_nodes()
+            .into_iter()
+            .map(|id| self.serialize_node(id))
+            .collect::<Result<Vec<_>, _>>()?;
+        let json = match values.len() {
+            0 => return Ok(None),
+            1 => values.remove(0),
+            _ => JsonValue::Array(values),
+        };
+        Ok(Some(canonical(json).pretty(4)))
+    }
+
+    /// Adds nodes for the JSON in `text` at `pos` and selects them. An array is pasted as one
+    /// node for every entry.
+    pub fn paste(&mut self, text: &str, pos: Pos2) -> Vec<AppError> {
+        let json = match json::parse(text) {
+            Ok(json) => json,
+            Err(e) => return vec![AppError::JsonError(e).with_window(self.identifier())],
+        };
+        let values: Vec<JsonValue> = match json {
+            JsonValue::Array(values) => values,
+            json => vec![json],
+        };
+        let start = self.state.node_order.len();
+        let mut errors = Vec::new();
+        for (i, value) in values.iter().enumerate() {
+            let path = match values.len() {
+                1 => "$".to_string(),
+                _ => format!("$[{}]", i),
+            };
+            let data_type = if value.is_object() {
+                self.guess_data_type(value)
+            } else {
+                None
+            };
+            match data_type {
+                Some(data_type) => {
+                    self.add_pasted_node(value, &data_type, pos, &path, &mut errors);
+                }
+                None => errors.push(
+                    AppError::WrongData(format!("{} isn't a node", value).into())
+                        .with_json_path(&path),
+                ),
+            }
+        }
+        self.finish_paste(start);
+        let id = self.identifier();
+        errors
+            .into_iter()
+            .map(|e| e.with_window(id.clone()))
+            .collect()
+    }
+
+    /// Adds a copy of the selected nodes and everything connected to them, next to them.
+    pub fn duplicate_selection(&mut self) -> Vec<AppError> {
+        let copied = self.copied_nodes();
+        let pos = match copied
+            .first()
+            .and_then(|id| self.state.node_positions.get(*id))
+        {
+            Some(pos) => *pos + Vec2::new(40., 40.),
+            None => return Vec::new(),
+        };
+        let start = self.state.node_order.len();
+        let mut errors = Vec::new();
+        for id in copied {
+            let json = match self.serialize_node(id) {
+                Ok(json) => json,
+                Err(e) => {
+                    errors.push(e);
+                    continue;
+                }
+            };
+            let graph = &self.state.graph;
+            let data_type = graph[id]
+                .output_ids()
+                .next()
+                .map(|output_id| graph.get_output(output_id).typ);
+            let pasted = match data_type {
+                Some(data_type) if json.is_object() => {
+                    self.add_pasted_node(&json, &data_type, pos, "$", &mut errors)
+                }
+                _ => None,
+            };
+            // References, blocks and the like are written as plain values
+            if pasted.is_none() {
+                self.clone_node(id, pos);
+            }
+        }
+        self.finish_paste(start);
+        let id = self.identifier();
+        errors
+            .into_iter()
+            .map(|e| e.with_window(id.clone()))
+            .collect()
+    }
+
+    /// Ctrl+C, Ctrl+V and Ctrl+D to duplicate, left to text fields while one has focus.
+    /// Pasted nodes go to `pos`.
+    pub fn handle_clipboard_keys(&mut self, ctx: &egui::Context, pos: Pos2) -> Vec<AppError> {
+        if ctx.memory().focus().is_some() {
+            return Vec::new();
+        }
+        let (events, duplicate) = {
+            let input = ctx.input();
+            let duplicate = input.modifiers.command && input.key_pressed(Key::D);
+            (input.events.clone(), duplicate)
+        };
+        let mut errors = Vec::new();
+        for event in events {
+            match event {
+                Event::Copy => match self.copy_selection() {
+                    Ok(Some(text)) => ctx.output().copied_text = text,
+                    Ok(None) => {}
+                    Err(e) => errors.push(e),
+                },
+                Event::Paste(text) => errors.extend(self.paste(&text, pos)),
+                _ => {}
+            }
+        }
+        if duplicate {
+            errors.extend(self.duplicate_selection());
+        }
+        errors
+    }
+
+    /// The type `value` reads best as, `None` if it isn't any node at all.
+    fn guess_data_type(&mut self, value: &JsonValue) -> Option<DataType> {
+        let start = self.state.node_order.len();
+        let fields = value.entries().count();
+        let mut best: Option<(usize, DataType)> = None;
+        for cdt in PASTE_TYPES {
+            let data_type = DataType::Single(cdt);
+            let mut errors = Vec::new();
+            let node_id = self.add_pasted_node(value, &data_type, Pos2::ZERO, "$", &mut errors);
+            let graph = &self.state.graph;
+            let new_nodes = &self.state.node_order[start..];
+            let unknown: usize = new_nodes
+                .iter()
+                .map(|id| graph[*id].user_data.unknown_fields.len())
+                .sum();
+            let raw = new_nodes
+                .iter()
+                .filter(|id| matches!(graph[**id].user_data.template, NodeTemplate::RawJson(_)))
+                .count();
+            // A node that understood none of the fields isn't what was meant
+            let understood = node_id.is_some_and(|id| {
+                let node = &graph[id];
+                !matches!(node.user_data.template, NodeTemplate::RawJson(_))
+                    && (fields == 0 || node.user_data.unknown_fields.len() < fields)
+            });
+            self.remove_nodes_from(start);
+            let score = errors.len() + unknown + raw;
+            if understood && best.as_ref().is_none_or(|(best, _)| score < *best) {
+                best = Some((score, data_type));
+                if score == 0 {
+                    break;
+                }
+            }
+        }
+        best.map(|(_, data_type)| data_type)
+    }
+
+    /// Adds the default node of `data_type` at `pos` and fills it from `value`.
+    fn add_pasted_node(
+        &mut self,
+        value: &JsonValue,
+        data_type: &DataType,
+        pos: Pos2,
+        path: &str,
+        errors: &mut Vec<AppError>,
+    ) -> Option<NodeId> {
+        // Biomes and the like are only pasted by name
+        let template = match data_type.defualt_NodeTemplate()? {
+            NodeTemplate::Reference(_) => return None,
+            template => template,
+        };
+        let node_id = add_node(&mut self.state, &mut self.user_state, template, pos);
+        self.deserialize_inner(value, &node_id, path, errors);
+        Some(node_id)
+    }
+
+    /// Adds a node of the same type as `node_id` with the same input values.
+    fn clone_node(&mut self, node_id: NodeId, pos: Pos2) {
+        let node = self.state.graph[node_id].clone();
+        let new_id = add_node(
+            &mut self.state,
+            &mut self.user_state,
+            node.user_data.template,
+            pos,
+        );
+        let graph = &mut self.state.graph;
+        let values: Vec<ValueType> = node
+            .input_ids()
+            .map(|input_id| graph[input_id].value.clone())
+            .collect();
+        let input_ids: Vec<_> = graph[new_id].input_ids().collect();
+        for (input_id, value) in input_ids.into_iter().zip(values) {
+            graph[input_id].value = value;
+        }
+        graph[new_id].user_data.unknown_fields = node.user_data.unknown_fields;
+        graph[new_id].user_data.single_id_lists = node.user_data.single_id_lists;
+    }
+
+    /// Removes the nodes added after the first `start` nodes.
+    fn remove_nodes_from(&mut self, start: usize) {
+        for node_id in self.state.node_order.split_off(start) {
+            self.state.graph.remove_node(node_id);
+            self.state.node_positions.remove(node_id);
+        }
+    }
+
+    /// Arranges and selects the nodes added after the first `start` nodes.
+    fn finish_paste(&mut self, start: usize) {
+        let new_nodes = self.state.node_order[start..].to_vec();
+        self.arrange(&new_nodes);
+        self.state.selected_nodes = new_nodes;
+    }
+}
@@ -0,0 +1,310 @@
+//! Project-wide view of which files refer to which.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use eframe::egui::{self, Align2, Color32, FontId, Rect, Sense, Stroke, Ui, Vec2};
+use egui_node_graph::NodeId;
+
+use crate::app::Identifier;
+use crate::nodes::data_types::ValueType;
+use crate::references::References;
+use crate::window::{Window, WindowType};
+
+const NODE_SIZE: Vec2 = Vec2::new(220., 24.);
+const SPACING: Vec2 = Vec2::new(80., 12.);
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum Status {
+    Project,
+    Vanilla,
+    /// not found in the project or vanilla
+    Unresolved,
+    /// a dull reference to a kind of file the editor doesn't know about
+    External,
+}
+
+pub struct FileNode {
+    pub label: String,
+    /// `None` for external files
+    pub id: Option<Identifier>,
+    pub status: Status,
+    pub in_cycle: bool,
+    /// a project file nothing refers to
+    pub orphan: bool,
+    layer: usize,
+    row: usize,
+}
+
+pub struct Edge {
+    pub from: usize,
+    pub to: usize,
+    pub in_cycle: bool,
+}
+
+#[derive(PartialEq, Eq, Hash, Clone)]
+enum Target {
+    File(Identifier),
+    External(String),
+}
+
+impl Window {
+    /// References made by nodes connected to the output, dull references have no type.
+    pub fn outgoing_references(&self) -> Vec<(Option<WindowType>, String)> {
+        let graph = &self.state.graph;
+        let mut references = Vec::new();
+        let mut visited = HashSet::new();
+        let mut stack: Vec<NodeId> = vec![self.root_node];
+        while let Some(node_id) = stack.pop() {
+            if !visited.insert(node_id) {
+                continue;
+            }
+            for input_id in graph[node_id].input_ids() {
+                if let Some(output_id) = graph.connection(input_id) {
+                    stack.push(graph.get_output(output_id).node);
+                    continue;
+                }
+                match graph.get_input(input_id).value() {
+                    ValueType::Reference(window_type, name) if !name.is_empty() => {
+                        references.push((Some(*window_type), name.clone()))
+                    }
+                    ValueType::DullReference(name) if !name.is_empty() => {
+                        references.push((None, name.clone()))
+                    }
+                    _ => {}
+                }
+            }
+        }
+        references
+    }
+}
+
+/// Files of the project and everything they refer to.
+pub struct Dependencies {
+    pub nodes: Vec<FileNode>,
+    pub edges: Vec<Edge>,
+}
+
+impl Dependencies {
+    pub fn new(file_structure: &[HashMap<Identifier, Window>], references: &References) -> Self {
+        let mut windows: Vec<(&Identifier, &Window)> =
+            file_structure.iter().flat_map(|map| map.iter()).collect();
+        windows.sort_unstable_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap());
+
+        let mut nodes = Vec::new();
+        let mut indices = HashMap::new();
+        let mut add_node = |nodes: &mut Vec<FileNode>, target: Target| {
+            *indices.entry(target.clone()).or_insert_with(|| {
+                let (label, id, status) = match target {
+                    Target::File(id) => {
+                        let status = if references.in_project(&id) {
+                            Status::Project
+                        } else if id.is_vanilla() {
+                            Status::Vanilla
+                        } else {
+                            Status::Unresolved
+                        };
+                        (
+                            format!("{} ({})", id, id.window_type().as_ref()),
+                            Some(id),
+                            status,
+                        )
+                    }
+                    Target::External(name) => (name, None, Status::External),
+                };
+                nodes.push(FileNode {
+                    label,
+                    id,
+                    status,
+                    in_cycle: false,
+                    orphan: false,
+                    layer: 0,
+                    row: 0,
+                });
+                nodes.len() - 1
+            })
+        };
+        let mut edges = Vec::new();
+        for (id, _) in &windows {
+            add_node(&mut nodes, Target::File((*id).clone()));
+        }
+        for (id, window) in &windows {
+            let from = add_node(&mut nodes, Target::File((*id).clone()));
+            for (window_type, name) in window.outgoing_references() {
+                let target = match window_type {
+                    Some(window_type) => match Identifier::from_string(name.clone(), window_type) {
+                        Some(id) => Target::File(id),
+                        None => continue,
+                    },
+                    None => Target::External(name),
+                };
+                let to = add_node(&mut nodes, target);
+                if !edges.iter().any(|e: &Edge| e.from == from && e.to == to) {
+                    edges.push(Edge {
+                        from,
+                        to,
+                        in_cycle: false,
+                    });
+                }
+            }
+        }
+
+        let mut dependencies = Self { nodes, edges };
+        dependencies.find_cycles();
+        dependencies.find_orphans();
+        dependencies.layout();
+        dependencies
+    }
+
+    fn successors(&self, node: usize) -> impl Iterator<Item = usize> + '_ {
+        self.edges
+            .iter()
+            .filter(move |e| e.from == node)
+            .map(|e| e.to)
+    }
+
+    /// Every node reachable from `node` in at least one step.
+    fn reachable(&self, node: usize) -> HashSet<usize> {
+        let mut reached = HashSet::new();
+        let mut stack: Vec<usize> = self.successors(node).collect();
+        while let Some(n) = stack.pop() {
+            if reached.insert(n) {
+                stack.extend(self.successors(n));
+            }
+        }
+        reached
+    }
+
+    fn find_cycles(&mut self) {
+        let reachable: Vec<HashSet<usize>> =
+            (0..self.nodes.len()).map(|n| self.reachable(n)).collect();
+        for (n, node) in self.nodes.iter_mut().enumerate() {
+            node.in_cycle = reachable[n].contains(&n);
+        }
+        for edge in &mut self.edges {
+            edge.in_cycle = reachable[edge.to].contains(&edge.from);
+        }
+    }
+
+    fn find_orphans(&mut self) {
+        for (n, node) in self.nodes.iter_mut().enumerate() {
+            // Only dimensions refer to noise settings and biomes, and they aren't edited here
+            let is_root_type = matches!(
+                node.id.as_ref().map(|id| id.window_type()),
+                Some(WindowType::NoiseSettings | WindowType::Biome)
+            );
+            node.orphan = node.status == Status::Project
+                && !is_root_type
+                && !self.edges.iter().any(|e| e.to == n && e.from != n);
+        }
+    }
+
+    /// Puts every node one column right of the first file found referring to it.
+    fn layout(&mut self) {
+        let mut layers = vec![None; self.nodes.len()];
+        let mut queue = VecDeque::new();
+        let roots = (0..self.nodes.len()).filter(|n| !self.edges.iter().any(|e| e.to == *n));
+        // Nodes that are only part of cycles are started from afterwards
+        for start in roots.chain(0..self.nodes.len()).collect::<Vec<_>>() {
+            if layers[start].is_some() {
+                continue;
+            }
+            layers[start] = Some(0);
+            queue.push_back(start);
+            while let Some(n) = queue.pop_front() {
+                let layer = layers[n].unwrap();
+                for next in self.successors(n).collect::<Vec<_>>() {
+                    if layers[next].is_none() {
+                        layers[next] = Some(layer + 1);
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+        let mut rows = HashMap::new();
+        for (node, layer) in self.nodes.iter_mut().zip(layers) {
+            node.layer = layer.unwrap_or(0);
+            let row = rows.entry(node.layer).or_insert(0);
+            node.row = *row;
+            *row += 1;
+        }
+    }
+
+    fn node_rect(&self, origin: egui::Pos2, node: &FileNode) -> Rect {
+        let pos = origin
+            + Vec2::new(
+                node.layer as f32 * (NODE_SIZE.x + SPACING.x),
+                node.row as f32 * (NODE_SIZE.y + SPACING.y),
+            );
+        Rect::from_min_size(pos, NODE_SIZE)
+    }
+
+    /// Draws the graph, returns the project file that was clicked.
+    pub fn ui(&self, ui: &mut Ui) -> Option<Identifier> {
+        ui.horizontal(|ui| {
+            ui.colored_label(Color32::RED, "cycle");
+            ui.colored_label(Color32::YELLOW, "not referenced");
+            ui.colored_label(Color32::LIGHT_RED, "unresolved");
+            ui.colored_label(Color32::GRAY, "vanilla / external");
+        });
+        let layers = self.nodes.iter().map(|n| n.layer + 1).max().unwrap_or(0);
+        let rows = self.nodes.iter().map(|n| n.row + 1).max().unwrap_or(0);
+        let size = Vec2::new(
+            layers as f32 * (NODE_SIZE.x + SPACING.x),
+            rows as f32 * (NODE_SIZE.y + SPACING.y),
+        );
+        let mut clicked = None;
+        egui::ScrollArea::both().show(ui, |ui| {
+            let (rect, _) = ui.allocate_exact_size(size, Sense::hover());
+            let painter = ui.painter_at(rect);
+            let visuals = ui.visuals().clone();
+            for edge in &self.edges {
+                let from = self.node_rect(rect.min, &self.nodes[edge.from]);
+                let to = self.node_rect(rect.min, &self.nodes[edge.to]);
+                let color = if edge.in_cycle {
+                    Color32::RED
+                } else {
+                    visuals.weak_text_color()
+                };
+                let (start, end) = (from.right_center(), to.left_center());
+                painter.line_segment([start, end], Stroke::new(1.5, color));
+                let direction = (end - start).normalized() * 6.;
+                let normal = direction.rot90() * 0.5;
+                painter.line_segment([end, end - direction + normal], Stroke::new(1.5, color));
+                painter.line_segment([end, end - direction - normal], Stroke::new(1.5, color));
+            }
+            for node in &self.nodes {
+                let node_rect = self.node_rect(rect.min, node);
+                let response = ui.interact(node_rect, ui.id().with(&node.label), Sense::click());
+                let text_color = match node.status {
+                    Status::Project => visuals.strong_text_color(),
+                    Status::Unresolved => Color32::LIGHT_RED,
+                    Status::Vanilla | Status::External => Color32::GRAY,
+                };
+                let stroke_color = if node.in_cycle {
+                    Color32::RED
+                } else if node.orphan {
+                    Color32::YELLOW
+                } else {
+                    visuals.widgets.noninteractive.bg_stroke.color
+                };
+                let fill = if response.hovered() && node.status == Status::Project {
+                    visuals.widgets.hovered.bg_fill
+                } else {
+                    visuals.widgets.noninteractive.bg_fill
+                };
+                painter.rect(node_rect, 3., fill, Stroke::new(1.5, stroke_color));
+                painter.text(
+                    node_rect.left_center() + Vec2::new(6., 0.),
+                    Align2::LEFT_CENTER,
+                    &node.label,
+                    FontId::proportional(13.),
+                    text_color,
+                );
+                if response.clicked() && node.status == Status::Project {
+                    clicked = node.id.clone();
+                }
+            }
+        });
+        clicked
+    }
+}
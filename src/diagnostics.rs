@@ -0,0 +1,82 @@
+//! Problems found while loading and saving, listed in a panel at the bottom of the app.
+
+use eframe::egui::{self, Color32, Ui};
+use egui_node_graph::NodeId;
+
+use crate::app::Identifier;
+use crate::errors::AppError;
+
+#[derive(Default)]
+pub struct Diagnostics {
+    errors: Vec<AppError>,
+    /// problems of the last save, replaced by the next one
+    save_errors: Vec<AppError>,
+    pub show: bool,
+}
+
+impl Diagnostics {
+    pub fn len(&self) -> usize {
+        self.errors.len() + self.save_errors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty() && self.save_errors.is_empty()
+    }
+
+    /// Adds errors to the queue, the panel is opened if there are new ones.
+    pub fn extend(&mut self, errors: impl IntoIterator<Item = AppError>) {
+        let len = self.errors.len();
+        self.errors.extend(errors);
+        self.show |= self.errors.len() > len;
+    }
+
+    pub fn push(&mut self, error: AppError) {
+        self.extend([error]);
+    }
+
+    /// Drops the problems found while loading the window, once it was read again or saved
+    /// without any.
+    pub fn clear_window(&mut self, id: &Identifier) {
+        self.errors.retain(|error| {
+            error.context().and_then(|context| context.window.as_ref()) != Some(id)
+        });
+    }
+
+    /// Replaces the problems of the last save, the ones found while loading are kept.
+    pub fn set_save_errors(&mut self, errors: Vec<AppError>) {
+        self.save_errors = errors;
+        self.show |= !self.save_errors.is_empty();
+    }
+
+    /// Lists the problems, returns the window and node of the one that was clicked.
+    pub fn ui(&mut self, ui: &mut Ui) -> Option<(Identifier, Option<NodeId>)> {
+        let mut clicked = None;
+        ui.horizontal(|ui| {
+            ui.strong(format!("Problems ({})", self.len()));
+            if ui.small_button("clear").clicked() {
+                self.errors.clear();
+                self.save_errors.clear();
+            }
+        });
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, true])
+            .show(ui, |ui| {
+                for error in self.errors.iter().chain(&self.save_errors) {
+                    let context = error.context();
+                    let window = context.and_then(|context| context.window.clone());
+                    let text = egui::RichText::new(error.to_string()).color(Color32::LIGHT_RED);
+                    let response = ui.add_enabled(
+                        window.is_some(),
+                        egui::Label::new(text).sense(egui::Sense::click()),
+                    );
+                    if let Some(file) = context.and_then(|context| context.file.as_ref()) {
+                        response.clone().on_hover_text(file.display().to_string());
+                    }
+                    if response.clicked() {
+                        clicked = window.map(|id| (id, context.and_then(|c| c.node_id)));
+                    }
+                }
+            });
+        clicked
+    }
+}
@@ -1,12 +1,86 @@
-use std::{error, fmt::Display, path::Path};
+use std::{
+    error,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use egui_node_graph::NodeId;
+
+use crate::app::Identifier;
 
 #[derive(Debug)]
-// TODO: Move all error types here as this will be used when displaying errors in ui
 pub enum AppError {
     WrongData(Box<str>),
     JsonError(json::Error),
     FileRead(String),
+    FileWrite(String),
     FileStructure(Box<Path>),
+    Evaluation(Box<str>),
+    /// The graph can't be written as JSON
+    Serialization(Box<str>),
+    /// Another error together with where it happened
+    InContext(Box<AppError>, ErrorContext),
+}
+
+/// Where an error happened, shown next to it in the problems panel.
+#[derive(Debug, Default, Clone)]
+pub struct ErrorContext {
+    pub window: Option<Identifier>,
+    pub file: Option<PathBuf>,
+    /// `.field[index].field` of the JSON value being read
+    pub json_path: Option<String>,
+    pub node_id: Option<NodeId>,
+}
+
+impl AppError {
+    /// Error without its context.
+    pub fn inner(&self) -> &AppError {
+        match self {
+            AppError::InContext(error, _) => error.inner(),
+            error => error,
+        }
+    }
+
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            AppError::InContext(_, context) => Some(context),
+            _ => None,
+        }
+    }
+
+    /// Fills the context, fields that are already known are kept.
+    fn with(self, f: impl FnOnce(&mut ErrorContext)) -> Self {
+        let (error, mut context) = match self {
+            AppError::InContext(error, context) => (error, context),
+            error => (Box::new(error), ErrorContext::default()),
+        };
+        f(&mut context);
+        AppError::InContext(error, context)
+    }
+
+    pub fn with_window(self, id: Identifier) -> Self {
+        self.with(|c| {
+            c.window.get_or_insert(id);
+        })
+    }
+
+    pub fn with_file(self, path: PathBuf) -> Self {
+        self.with(|c| {
+            c.file.get_or_insert(path);
+        })
+    }
+
+    pub fn with_json_path(self, path: &str) -> Self {
+        self.with(|c| {
+            c.json_path.get_or_insert_with(|| path.to_string());
+        })
+    }
+
+    pub fn with_node(self, node_id: NodeId) -> Self {
+        self.with(|c| {
+            c.node_id.get_or_insert(node_id);
+        })
+    }
 }
 
 impl Display for AppError {
@@ -15,7 +89,22 @@ impl Display for AppError {
             AppError::WrongData(x) => write!(f, "Wrong data: {}", x),
             AppError::JsonError(x) => x.fmt(f),
             AppError::FileRead(x) => write!(f, "File Read: {}", x),
+            AppError::FileWrite(x) => write!(f, "File Write: {}", x),
             AppError::FileStructure(x) => write!(f, "File structure: {}", x.display()),
+            AppError::Evaluation(x) => write!(f, "Evaluation: {}", x),
+            AppError::Serialization(x) => write!(f, "Serialization: {}", x),
+            AppError::InContext(x, context) => {
+                if let Some(window) = &context.window {
+                    write!(f, "{}: ", window)?;
+                } else if let Some(file) = &context.file {
+                    write!(f, "{}: ", file.display())?;
+                }
+                x.fmt(f)?;
+                if let Some(json_path) = &context.json_path {
+                    write!(f, " (at {})", json_path)?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -0,0 +1,678 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use egui_node_graph::NodeId;
+
+use crate::app::Identifier;
+use crate::errors::AppError;
+use crate::nodes::{
+    data_types::{ComplexDataType, DataType, ValueType},
+    inner_data_types::density_function::{DensityFunctionType, WeirdScaledSampleRarityValueMapper},
+    node_types::NodeTemplate,
+};
+use crate::window::{Window, WindowType};
+
+use super::clamped_map;
+use super::noise::NormalNoise;
+use super::random::{PositionalRandomFactory, XoroshiroRandom};
+
+/// Density function compiled from a node graph, ready to be computed at any block position.
+#[derive(Clone, Debug)]
+pub enum DensityFunction {
+    Constant(f64),
+    Mapped(Mapping, Box<DensityFunction>),
+    TwoArgument(Operation, Box<DensityFunction>, Box<DensityFunction>),
+    Clamp {
+        input: Box<DensityFunction>,
+        min: f64,
+        max: f64,
+    },
+    RangeChoice {
+        input: Box<DensityFunction>,
+        min_inclusive: f64,
+        max_exclusive: f64,
+        when_in_range: Box<DensityFunction>,
+        when_out_of_range: Box<DensityFunction>,
+    },
+    YClampedGradient {
+        from_y: i32,
+        to_y: i32,
+        from_value: f64,
+        to_value: f64,
+    },
+    Spline(Box<Spline>),
+    Noise {
+        noise: Arc<NormalNoise>,
+        xz_scale: f64,
+        y_scale: f64,
+    },
+    ShiftedNoise {
+        noise: Arc<NormalNoise>,
+        xz_scale: f64,
+        y_scale: f64,
+        shift_x: Box<DensityFunction>,
+        shift_y: Box<DensityFunction>,
+        shift_z: Box<DensityFunction>,
+    },
+    Shift(ShiftType, Arc<NormalNoise>),
+    WeirdScaledSampler {
+        input: Box<DensityFunction>,
+        noise: Arc<NormalNoise>,
+        rarity_value_mapper: WeirdScaledSampleRarityValueMapper,
+    },
+    FindTopSurface {
+        density: Box<DensityFunction>,
+        upper_bound: Box<DensityFunction>,
+        lower_bound: i32,
+        cell_height: i32,
+    },
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum ShiftType {
+    /// samples at `(x, 0, z)`
+    A,
+    /// samples at `(z, x, 0)`
+    B,
+    /// samples at `(x, y, z)`
+    All,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Mapping {
+    Abs,
+    Square,
+    Cube,
+    HalfNegative,
+    QuarterNegative,
+    Squeeze,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Operation {
+    Add,
+    Mul,
+    Min,
+    Max,
+}
+
+#[derive(Clone, Debug)]
+pub enum Spline {
+    Constant(f32),
+    Multipoint {
+        coordinate: DensityFunction,
+        points: Vec<SplinePoint>,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct SplinePoint {
+    pub location: f32,
+    pub derivative: f32,
+    pub value: Spline,
+}
+
+impl DensityFunction {
+    pub fn compute(&self, x: i32, y: i32, z: i32) -> f64 {
+        match self {
+            DensityFunction::Constant(v) => *v,
+            DensityFunction::Mapped(mapping, argument) => {
+                let v = argument.compute(x, y, z);
+                match mapping {
+                    Mapping::Abs => v.abs(),
+                    Mapping::Square => v * v,
+                    Mapping::Cube => v * v * v,
+                    Mapping::HalfNegative => {
+                        if v > 0. {
+                            v
+                        } else {
+                            v * 0.5
+                        }
+                    }
+                    Mapping::QuarterNegative => {
+                        if v > 0. {
+                            v
+                        } else {
+                            v * 0.25
+                        }
+                    }
+                    Mapping::Squeeze => {
+                        let c = v.clamp(-1., 1.);
+                        c / 2. - c * c * c / 24.
+                    }
+                }
+            }
+            DensityFunction::TwoArgument(operation, argument1, argument2) => {
+                let a = argument1.compute(x, y, z);
+                match operation {
+                    Operation::Add => a + argument2.compute(x, y, z),
+                    // Same shortcuts as vanilla, the second argument may be expensive
+                    Operation::Mul => {
+                        if a == 0. {
+                            0.
+                        } else {
+                            a * argument2.compute(x, y, z)
+                        }
+                    }
+                    Operation::Min => a.min(argument2.compute(x, y, z)),
+                    Operation::Max => a.max(argument2.compute(x, y, z)),
+                }
+            }
+            DensityFunction::Clamp { input, min, max } => input.compute(x, y, z).clamp(*min, *max),
+            DensityFunction::RangeChoice {
+                input,
+                min_inclusive,
+                max_exclusive,
+                when_in_range,
+                when_out_of_range,
+            } => {
+                let v = input.compute(x, y, z);
+                if v >= *min_inclusive && v < *max_exclusive {
+                    when_in_range.compute(x, y, z)
+                } else {
+                    when_out_of_range.compute(x, y, z)
+                }
+            }
+            DensityFunction::YClampedGradient {
+                from_y,
+                to_y,
+                from_value,
+                to_value,
+            } => clamped_map(
+                y as f64,
+                *from_y as f64,
+                *to_y as f64,
+                *from_value,
+                *to_value,
+            ),
+            DensityFunction::Spline(spline) => spline.compute(x, y, z) as f64,
+            DensityFunction::Noise {
+                noise,
+                xz_scale,
+                y_scale,
+            } => noise.get_value(x as f64 * xz_scale, y as f64 * y_scale, z as f64 * xz_scale),
+            DensityFunction::ShiftedNoise {
+                noise,
+                xz_scale,
+                y_scale,
+                shift_x,
+                shift_y,
+                shift_z,
+            } => noise.get_value(
+                x as f64 * xz_scale + shift_x.compute(x, y, z),
+                y as f64 * y_scale + shift_y.compute(x, y, z),
+                z as f64 * xz_scale + shift_z.compute(x, y, z),
+            ),
+            DensityFunction::Shift(typ, noise) => {
+                let (a, b, c) = match typ {
+                    ShiftType::A => (x, 0, z),
+                    ShiftType::B => (z, x, 0),
+                    ShiftType::All => (x, y, z),
+                };
+                noise.get_value(a as f64 * 0.25, b as f64 * 0.25, c as f64 * 0.25) * 4.
+            }
+            DensityFunction::WeirdScaledSampler {
+                input,
+                noise,
+                rarity_value_mapper,
+            } => {
+                let rarity = rarity_value_mapper.rarity(input.compute(x, y, z));
+                rarity
+                    * noise
+                        .get_value(x as f64 / rarity, y as f64 / rarity, z as f64 / rarity)
+                        .abs()
+            }
+            DensityFunction::FindTopSurface {
+                density,
+                upper_bound,
+                lower_bound,
+                cell_height,
+            } => {
+                // Starts at the top of the cell `upper_bound` is in, going down a cell at a time
+                let top = (upper_bound.compute(x, y, z) / *cell_height as f64).floor() as i32
+                    * cell_height;
+                if top <= *lower_bound {
+                    return *lower_bound as f64;
+                }
+                (*lower_bound..=top)
+                    .rev()
+                    .step_by(*cell_height as usize)
+                    .find(|y| density.compute(x, *y, z) > 0.)
+                    .unwrap_or(*lower_bound) as f64
+            }
+        }
+    }
+}
+
+impl WeirdScaledSampleRarityValueMapper {
+    /// Same as `QuantizedSpaghettiRarity` in Minecraft.
+    pub fn rarity(&self, v: f64) -> f64 {
+        match self {
+            WeirdScaledSampleRarityValueMapper::Type1 => {
+                if v < -0.5 {
+                    0.75
+                } else if v < 0. {
+                    1.
+                } else if v < 0.5 {
+                    1.5
+                } else {
+                    2.
+                }
+            }
+            WeirdScaledSampleRarityValueMapper::Type2 => {
+                if v < -0.75 {
+                    0.5
+                } else if v < -0.5 {
+                    0.75
+                } else if v < 0.5 {
+                    1.
+                } else if v < 0.75 {
+                    2.
+                } else {
+                    3.
+                }
+            }
+        }
+    }
+}
+
+impl Spline {
+    /// Port of `CubicSpline.Multipoint.apply`, calculations are done on `f32` just like in the game.
+    pub fn compute(&self, x: i32, y: i32, z: i32) -> f32 {
+        let (coordinate, points) = match self {
+            Spline::Constant(v) => return *v,
+            Spline::Multipoint { coordinate, points } => (coordinate, points),
+        };
+        let f = coordinate.compute(x, y, z) as f32;
+        let last = points.len() - 1;
+        let linear_extend = |point: &SplinePoint| {
+            let value = point.value.compute(x, y, z);
+            if point.derivative == 0. {
+                value
+            } else {
+                value + point.derivative * (f - point.location)
+            }
+        };
+        // Index of the last point whose location is not greater than `f`
+        match points.iter().position(|p| f < p.location) {
+            Some(0) => linear_extend(&points[0]),
+            None => linear_extend(&points[last]),
+            Some(i) => {
+                let (p1, p2) = (&points[i - 1], &points[i]);
+                let width = p2.location - p1.location;
+                let t = (f - p1.location) / width;
+                let v1 = p1.value.compute(x, y, z);
+                let v2 = p2.value.compute(x, y, z);
+                let a = p1.derivative * width - (v2 - v1);
+                let b = -p2.derivative * width + (v2 - v1);
+                let lerp = |start: f32, end: f32| start + t * (end - start);
+                lerp(v1, v2) + t * (1. - t) * lerp(a, b)
+            }
+        }
+    }
+}
+
+/// Turns node graphs into `DensityFunction`s, following references with `resolve`.
+pub struct Compiler<'a> {
+    resolve: &'a dyn Fn(&Identifier) -> Option<&'a Window>,
+    /// references currently being compiled, used to detect cycles
+    stack: Vec<Identifier>,
+    random: PositionalRandomFactory,
+    noises: HashMap<Identifier, Arc<NormalNoise>>,
+}
+
+impl<'a> Compiler<'a> {
+    pub fn new(seed: i64, resolve: &'a dyn Fn(&Identifier) -> Option<&'a Window>) -> Self {
+        Self {
+            resolve,
+            stack: Vec::new(),
+            random: XoroshiroRandom::new(seed).fork_positional(),
+            noises: HashMap::new(),
+        }
+    }
+
+    /// Compiles the function starting at `node_id`, or at the root node if it is `None`.
+    pub fn compile(
+        &mut self,
+        window: &Window,
+        node_id: Option<NodeId>,
+    ) -> Result<DensityFunction, AppError> {
+        if window.window_type != WindowType::DensityFunction {
+            return Err(AppError::Evaluation(
+                format!("{} is not a density function", window).into(),
+            ));
+        }
+        self.compile_node(window, node_id.unwrap_or(window.root_node))
+    }
+
+    fn compile_node(
+        &mut self,
+        window: &Window,
+        node_id: NodeId,
+    ) -> Result<DensityFunction, AppError> {
+        let node = window
+            .state
+            .graph
+            .nodes
+            .get(node_id)
+            .ok_or_else(|| AppError::Evaluation("node does not exist".into()))?;
+        match node.user_data.template {
+            NodeTemplate::Output(WindowType::DensityFunction) => {
+                self.compile_input(window, node_id, "output")
+            }
+            NodeTemplate::Reference(WindowType::DensityFunction) => {
+                let (_, in_id) = node.inputs[0];
+                if let ValueType::Reference(_, name) = window.state.graph.get_input(in_id).value() {
+                    self.compile_reference(name)
+                } else {
+                    Err(AppError::WrongData(node.label.clone().into()))
+                }
+            }
+            NodeTemplate::DensityFunction(typ) => {
+                self.compile_density_function(window, node_id, typ)
+            }
+            _ => Err(AppError::Evaluation(
+                format!("{} is not a density function", node.label).into(),
+            )),
+        }
+    }
+
+    fn compile_reference(&mut self, name: &str) -> Result<DensityFunction, AppError> {
+        let id = Identifier::from_string(name.to_string(), WindowType::DensityFunction)
+            .ok_or_else(|| AppError::WrongData(name.into()))?;
+        if self.stack.contains(&id) {
+            return Err(AppError::Evaluation(
+                format!("circular reference to {}", id).into(),
+            ));
+        }
+        let window = (self.resolve)(&id)
+            .ok_or_else(|| AppError::Evaluation(format!("unresolved reference {}", id).into()))?;
+        self.stack.push(id);
+        let result = self.compile_node(window, window.root_node);
+        self.stack.pop();
+        result
+    }
+
+    fn compile_density_function(
+        &mut self,
+        window: &Window,
+        node_id: NodeId,
+        typ: DensityFunctionType,
+    ) -> Result<DensityFunction, AppError> {
+        use DensityFunctionType::*;
+        let mapped = |compiler: &mut Self, mapping| {
+            Ok(DensityFunction::Mapped(
+                mapping,
+                Box::new(compiler.compile_input(window, node_id, "argument")?),
+            ))
+        };
+        let two_argument = |compiler: &mut Self, operation| {
+            Ok(DensityFunction::TwoArgument(
+                operation,
+                Box::new(compiler.compile_input(window, node_id, "argument1")?),
+                Box::new(compiler.compile_input(window, node_id, "argument2")?),
+            ))
+        };
+        match typ {
+            // Caching and interpolation only change performance in game
+            Interpolated | FlatCache | Cache2d | CacheOnce | CacheAllInCell | BlendDensity => {
+                self.compile_input(window, node_id, "argument")
+            }
+            Abs => mapped(self, Mapping::Abs),
+            Square => mapped(self, Mapping::Square),
+            Cube => mapped(self, Mapping::Cube),
+            HalfNegative => mapped(self, Mapping::HalfNegative),
+            QuarterNegative => mapped(self, Mapping::QuarterNegative),
+            Squeeze => mapped(self, Mapping::Squeeze),
+            Add => two_argument(self, Operation::Add),
+            Mul => two_argument(self, Operation::Mul),
+            Min => two_argument(self, Operation::Min),
+            Max => two_argument(self, Operation::Max),
+            // Without blending these are constant
+            BlendAlpha => Ok(DensityFunction::Constant(1.)),
+            BlendOffset | Beardifier => Ok(DensityFunction::Constant(0.)),
+            Constant => Ok(DensityFunction::Constant(value_input(
+                window, node_id, "argument",
+            )?)),
+            Clamp => {
+                let min = value_input(window, node_id, "min")?;
+                let max = value_input(window, node_id, "max")?;
+                // The game doesn't load such a clamp either
+                if min > max {
+                    return Err(AppError::Evaluation(
+                        format!("clamp min {} is greater than max {}", min, max).into(),
+                    ));
+                }
+                Ok(DensityFunction::Clamp {
+                    input: Box::new(self.compile_input(window, node_id, "input")?),
+                    min,
+                    max,
+                })
+            }
+            RangeChoice => Ok(DensityFunction::RangeChoice {
+                input: Box::new(self.compile_input(window, node_id, "input")?),
+                min_inclusive: value_input(window, node_id, "min_inclusive")?,
+                max_exclusive: value_input(window, node_id, "max_exclusive")?,
+                when_in_range: Box::new(self.compile_input(window, node_id, "when_in_range")?),
+                when_out_of_range: Box::new(self.compile_input(
+                    window,
+                    node_id,
+                    "when_out_of_range",
+                )?),
+            }),
+            YClampedGradient => Ok(DensityFunction::YClampedGradient {
+                from_y: value_input(window, node_id, "from_y")? as i32,
+                to_y: value_input(window, node_id, "to_y")? as i32,
+                from_value: value_input(window, node_id, "from_value")?,
+                to_value: value_input(window, node_id, "to_value")?,
+            }),
+            Spline => Ok(DensityFunction::Spline(Box::new(
+                self.compile_spline_input(window, node_id, "spline")?,
+            ))),
+            Noise => Ok(DensityFunction::Noise {
+                noise: self.noise_input(window, node_id, "noise")?,
+                xz_scale: value_input(window, node_id, "xz_scale")?,
+                y_scale: value_input(window, node_id, "y_scale")?,
+            }),
+            ShiftedNoise => Ok(DensityFunction::ShiftedNoise {
+                noise: self.noise_input(window, node_id, "noise")?,
+                xz_scale: value_input(window, node_id, "xz_scale")?,
+                y_scale: value_input(window, node_id, "y_scale")?,
+                shift_x: Box::new(self.compile_input(window, node_id, "shift_x")?),
+                shift_y: Box::new(self.compile_input(window, node_id, "shift_y")?),
+                shift_z: Box::new(self.compile_input(window, node_id, "shift_z")?),
+            }),
+            ShiftA => Ok(DensityFunction::Shift(
+                ShiftType::A,
+                self.noise_input(window, node_id, "argument")?,
+            )),
+            ShiftB => Ok(DensityFunction::Shift(
+                ShiftType::B,
+                self.noise_input(window, node_id, "argument")?,
+            )),
+            Shift => Ok(DensityFunction::Shift(
+                ShiftType::All,
+                self.noise_input(window, node_id, "argument")?,
+            )),
+            WeirdScaledSampler => {
+                let in_id = window.state.graph[node_id]
+                    .get_input("rarity_value_mapper")
+                    .map_err(|_| AppError::WrongData("missing input rarity_value_mapper".into()))?;
+                let rarity_value_mapper = match window.state.graph.get_input(in_id).value() {
+                    ValueType::WeirdScaledSampleRarityValueMapper(x) => *x,
+                    _ => return Err(AppError::WrongData("rarity_value_mapper".into())),
+                };
+                Ok(DensityFunction::WeirdScaledSampler {
+                    input: Box::new(self.compile_input(window, node_id, "input")?),
+                    noise: self.noise_input(window, node_id, "noise")?,
+                    rarity_value_mapper,
+                })
+            }
+            FindTopSurface => {
+                let cell_height = value_input(window, node_id, "cell_height")? as i32;
+                // The game doesn't load such a function either
+                if cell_height <= 0 {
+                    return Err(AppError::Evaluation(
+                        format!("cell_height {} is not positive", cell_height).into(),
+                    ));
+                }
+                Ok(DensityFunction::FindTopSurface {
+                    density: Box::new(self.compile_input(window, node_id, "density")?),
+                    upper_bound: Box::new(self.compile_input(window, node_id, "upper_bound")?),
+                    lower_bound: value_input(window, node_id, "lower_bound")? as i32,
+                    cell_height,
+                })
+            }
+            OldBlendedNoise | EndIslands => Err(AppError::Evaluation(
+                format!("{} cannot be evaluated yet", typ.as_ref()).into(),
+            )),
+        }
+    }
+
+    /// Instantiates the noise referenced in input `name` like the game does for this seed.
+    fn noise_input(
+        &mut self,
+        window: &Window,
+        node_id: NodeId,
+        name: &str,
+    ) -> Result<Arc<NormalNoise>, AppError> {
+        let graph = &window.state.graph;
+        let in_id = graph[node_id]
+            .get_input(name)
+            .map_err(|_| AppError::WrongData(format!("missing input {}", name).into()))?;
+        match graph.get_input(in_id).value() {
+            ValueType::Reference(WindowType::Noise, reference) => self.noise(reference),
+            _ => Err(AppError::WrongData(name.into())),
+        }
+    }
+
+    /// Instantiates the noise `reference` like the game does for this seed.
+    pub fn noise(&mut self, reference: &str) -> Result<Arc<NormalNoise>, AppError> {
+        let id = Identifier::from_string(reference.to_string(), WindowType::Noise)
+            .ok_or_else(|| AppError::WrongData(reference.into()))?;
+        if let Some(noise) = self.noises.get(&id) {
+            return Ok(noise.clone());
+        }
+        let noise_window = (self.resolve)(&id)
+            .ok_or_else(|| AppError::Evaluation(format!("unresolved reference {}", id).into()))?;
+        let noise = Arc::new(NormalNoise::new(
+            &mut self.random.from_hash_of(&id.to_string()),
+            &noise_window.noise_parameters()?,
+        ));
+        self.noises.insert(id, noise.clone());
+        Ok(noise)
+    }
+
+    /// Random factory of the seed, `RandomState.random` in the game.
+    pub fn random(&self) -> PositionalRandomFactory {
+        self.random
+    }
+
+    /// Compiles whatever is connected to the density function input `name`.
+    /// Unconnected inputs are the number they hold, `0.0` for ones saved before they had one.
+    fn compile_input(
+        &mut self,
+        window: &Window,
+        node_id: NodeId,
+        name: &str,
+    ) -> Result<DensityFunction, AppError> {
+        match connected_node(window, node_id, name)? {
+            Some(next) => self.compile_node(window, next),
+            None => Ok(DensityFunction::Constant(
+                value_input(window, node_id, name).unwrap_or(0.),
+            )),
+        }
+    }
+
+    fn compile_spline_input(
+        &mut self,
+        window: &Window,
+        node_id: NodeId,
+        name: &str,
+    ) -> Result<Spline, AppError> {
+        match connected_node(window, node_id, name)? {
+            Some(next) => self.compile_spline(window, next),
+            None => Ok(Spline::Constant(value_input(window, node_id, name)? as f32)),
+        }
+    }
+
+    fn compile_spline(&mut self, window: &Window, node_id: NodeId) -> Result<Spline, AppError> {
+        let graph = &window.state.graph;
+        let node = &graph[node_id];
+        if !matches!(node.user_data.template, NodeTemplate::Spline) {
+            return Err(AppError::Evaluation(
+                format!("{} is not a spline", node.label).into(),
+            ));
+        }
+        let coordinate = self.compile_input(window, node_id, "coordinate")?;
+        let mut points = Vec::new();
+        for (_, in_id) in node.inputs.iter() {
+            if graph.get_input(*in_id).typ != DataType::Single(ComplexDataType::SplinePoint) {
+                continue;
+            }
+            if let Some(out_id) = graph.connection(*in_id) {
+                let point_id = graph.get_output(out_id).node;
+                points.push(SplinePoint {
+                    location: value_input(window, point_id, "location")? as f32,
+                    derivative: value_input(window, point_id, "derivative")? as f32,
+                    value: self.compile_spline_input(window, point_id, "value")?,
+                });
+            }
+        }
+        if points.is_empty() {
+            return Err(AppError::Evaluation("spline has no points".into()));
+        }
+        if points.windows(2).any(|w| w[0].location >= w[1].location) {
+            return Err(AppError::Evaluation(
+                "spline point locations must be in ascending order".into(),
+            ));
+        }
+        Ok(Spline::Multipoint { coordinate, points })
+    }
+}
+
+impl Window {
+    /// Compiles the density function at `node_id` (or the whole file) with references resolved by `resolve`.
+    pub fn compile_density_function<'a>(
+        &self,
+        node_id: Option<NodeId>,
+        seed: i64,
+        resolve: &'a dyn Fn(&Identifier) -> Option<&'a Window>,
+    ) -> Result<DensityFunction, AppError> {
+        Compiler::new(seed, resolve).compile(self, node_id)
+    }
+}
+
+pub(super) fn connected_node(
+    window: &Window,
+    node_id: NodeId,
+    name: &str,
+) -> Result<Option<NodeId>, AppError> {
+    let graph = &window.state.graph;
+    let in_id = graph[node_id]
+        .get_input(name)
+        .map_err(|_| AppError::WrongData(format!("missing input {}", name).into()))?;
+    Ok(graph
+        .connection(in_id)
+        .map(|out_id| graph.get_output(out_id).node))
+}
+
+/// Reads a number from an input, following connections to value nodes.
+pub(super) fn value_input(window: &Window, node_id: NodeId, name: &str) -> Result<f64, AppError> {
+    let graph = &window.state.graph;
+    if let Some(next) = connected_node(window, node_id, name)? {
+        return match graph[next].user_data.template {
+            NodeTemplate::ConstantValue => value_input(window, next, "value"),
+            NodeTemplate::AddValue => {
+                Ok(value_input(window, next, "arg1")? + value_input(window, next, "arg2")?)
+            }
+            _ => Err(AppError::WrongData(graph[next].label.clone().into())),
+        };
+    }
+    let in_id = graph[node_id].get_input(name).unwrap();
+    match graph.get_input(in_id).value() {
+        ValueType::Value(x) => Ok(*x),
+        ValueType::Integer(x) => Ok(*x as f64),
+        _ => Err(AppError::WrongData(name.into())),
+    }
+}
@@ -0,0 +1,21 @@
+pub mod density_function;
+pub mod noise;
+pub mod random;
+pub mod surface_rule;
+
+/// Same as `Mth.lerp` in Minecraft.
+pub fn lerp(delta: f64, start: f64, end: f64) -> f64 {
+    start + delta * (end - start)
+}
+
+/// Same as `Mth.clampedMap` in Minecraft.
+pub fn clamped_map(value: f64, from_min: f64, from_max: f64, to_min: f64, to_max: f64) -> f64 {
+    let delta = (value - from_min) / (from_max - from_min);
+    if delta < 0. {
+        to_min
+    } else if delta > 1. {
+        to_max
+    } else {
+        lerp(delta, to_min, to_max)
+    }
+}
@@ -0,0 +1,286 @@
+//! Ports of Minecraft's `ImprovedNoise`, `PerlinNoise` and `NormalNoise`.
+
+use crate::errors::AppError;
+use crate::nodes::data_types::ValueType;
+use crate::window::{Window, WindowType};
+
+use super::lerp;
+use super::random::XoroshiroRandom;
+
+const GRADIENT: [[f64; 3]; 16] = [
+    [1., 1., 0.],
+    [-1., 1., 0.],
+    [1., -1., 0.],
+    [-1., -1., 0.],
+    [1., 0., 1.],
+    [-1., 0., 1.],
+    [1., 0., -1.],
+    [-1., 0., -1.],
+    [0., 1., 1.],
+    [0., -1., 1.],
+    [0., 1., -1.],
+    [0., -1., -1.],
+    [1., 1., 0.],
+    [0., -1., 1.],
+    [-1., 1., 0.],
+    [0., -1., -1.],
+];
+
+fn grad_dot(hash: u8, x: f64, y: f64, z: f64) -> f64 {
+    let g = GRADIENT[(hash & 15) as usize];
+    g[0] * x + g[1] * y + g[2] * z
+}
+
+fn smoothstep(x: f64) -> f64 {
+    x * x * x * (x * (x * 6. - 15.) + 10.)
+}
+
+/// Contents of a `worldgen/noise` file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NoiseParameters {
+    pub first_octave: i32,
+    pub amplitudes: Vec<f64>,
+}
+
+/// Single octave of noise.
+#[derive(Clone, Debug)]
+pub struct ImprovedNoise {
+    xo: f64,
+    yo: f64,
+    zo: f64,
+    p: [u8; 256],
+}
+
+impl ImprovedNoise {
+    pub fn new(random: &mut XoroshiroRandom) -> Self {
+        let xo = random.next_double() * 256.;
+        let yo = random.next_double() * 256.;
+        let zo = random.next_double() * 256.;
+        let mut p = [0u8; 256];
+        for (i, x) in p.iter_mut().enumerate() {
+            *x = i as u8;
+        }
+        for i in 0..256 {
+            let j = random.next_int_bounded(256 - i as i32) as usize;
+            p.swap(i, i + j);
+        }
+        Self { xo, yo, zo, p }
+    }
+
+    fn p(&self, i: i32) -> i32 {
+        self.p[(i & 0xFF) as usize] as i32
+    }
+
+    pub fn noise(&self, x: f64, y: f64, z: f64, y_scale: f64, y_max: f64) -> f64 {
+        let (x, y, z) = (x + self.xo, y + self.yo, z + self.zo);
+        let (section_x, section_y, section_z) = (x.floor(), y.floor(), z.floor());
+        let (local_x, local_y, local_z) = (x - section_x, y - section_y, z - section_z);
+        let y_offset = if y_scale != 0. {
+            let m = if y_max >= 0. && y_max < local_y {
+                y_max
+            } else {
+                local_y
+            };
+            (m / y_scale + 1.0E-7_f32 as f64).floor() * y_scale
+        } else {
+            0.
+        };
+        self.sample_and_lerp(
+            section_x as i32,
+            section_y as i32,
+            section_z as i32,
+            local_x,
+            local_y - y_offset,
+            local_z,
+            local_y,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn sample_and_lerp(
+        &self,
+        section_x: i32,
+        section_y: i32,
+        section_z: i32,
+        x: f64,
+        y: f64,
+        z: f64,
+        fade_y: f64,
+    ) -> f64 {
+        let i = self.p(section_x);
+        let j = self.p(section_x.wrapping_add(1));
+        let k = self.p(i.wrapping_add(section_y));
+        let l = self.p(i.wrapping_add(section_y).wrapping_add(1));
+        let m = self.p(j.wrapping_add(section_y));
+        let n = self.p(j.wrapping_add(section_y).wrapping_add(1));
+        let hash =
+            |a: i32, offset: i32| self.p(a.wrapping_add(section_z).wrapping_add(offset)) as u8;
+
+        let d000 = grad_dot(hash(k, 0), x, y, z);
+        let d100 = grad_dot(hash(m, 0), x - 1., y, z);
+        let d010 = grad_dot(hash(l, 0), x, y - 1., z);
+        let d110 = grad_dot(hash(n, 0), x - 1., y - 1., z);
+        let d001 = grad_dot(hash(k, 1), x, y, z - 1.);
+        let d101 = grad_dot(hash(m, 1), x - 1., y, z - 1.);
+        let d011 = grad_dot(hash(l, 1), x, y - 1., z - 1.);
+        let d111 = grad_dot(hash(n, 1), x - 1., y - 1., z - 1.);
+
+        let (tx, ty, tz) = (smoothstep(x), smoothstep(fade_y), smoothstep(z));
+        lerp(
+            tz,
+            lerp(ty, lerp(tx, d000, d100), lerp(tx, d010, d110)),
+            lerp(ty, lerp(tx, d001, d101), lerp(tx, d011, d111)),
+        )
+    }
+}
+
+/// Octaves of `ImprovedNoise` added together.
+#[derive(Clone, Debug)]
+pub struct PerlinNoise {
+    noise_levels: Vec<Option<ImprovedNoise>>,
+    amplitudes: Vec<f64>,
+    lowest_freq_input_factor: f64,
+    lowest_freq_value_factor: f64,
+}
+
+impl PerlinNoise {
+    pub fn new(random: &mut XoroshiroRandom, parameters: &NoiseParameters) -> Self {
+        let positional = random.fork_positional();
+        let noise_levels = parameters
+            .amplitudes
+            .iter()
+            .enumerate()
+            .map(|(i, amplitude)| {
+                (*amplitude != 0.).then(|| {
+                    let octave = parameters.first_octave + i as i32;
+                    ImprovedNoise::new(&mut positional.from_hash_of(&format!("octave_{}", octave)))
+                })
+            })
+            .collect();
+        let octaves = parameters.amplitudes.len() as i32;
+        Self {
+            noise_levels,
+            amplitudes: parameters.amplitudes.clone(),
+            lowest_freq_input_factor: 2f64.powi(parameters.first_octave),
+            lowest_freq_value_factor: 2f64.powi(octaves - 1) / (2f64.powi(octaves) - 1.),
+        }
+    }
+
+    fn wrap(x: f64) -> f64 {
+        x - (x / 3.3554432E7 + 0.5).floor() * 3.3554432E7
+    }
+
+    pub fn get_value(&self, x: f64, y: f64, z: f64) -> f64 {
+        let mut value = 0.;
+        let mut input_factor = self.lowest_freq_input_factor;
+        let mut value_factor = self.lowest_freq_value_factor;
+        for (level, amplitude) in self.noise_levels.iter().zip(self.amplitudes.iter()) {
+            if let Some(noise) = level {
+                let v = noise.noise(
+                    Self::wrap(x * input_factor),
+                    Self::wrap(y * input_factor),
+                    Self::wrap(z * input_factor),
+                    0.,
+                    0.,
+                );
+                value += amplitude * v * value_factor;
+            }
+            input_factor *= 2.;
+            value_factor /= 2.;
+        }
+        value
+    }
+
+    pub fn max_value(&self) -> f64 {
+        let mut value = 0.;
+        let mut value_factor = self.lowest_freq_value_factor;
+        for (level, amplitude) in self.noise_levels.iter().zip(self.amplitudes.iter()) {
+            if level.is_some() {
+                value += amplitude * 2. * value_factor;
+            }
+            value_factor /= 2.;
+        }
+        value
+    }
+}
+
+/// Two `PerlinNoise`s sampled at slightly different scales, what `worldgen/noise` files describe.
+#[derive(Clone, Debug)]
+pub struct NormalNoise {
+    first: PerlinNoise,
+    second: PerlinNoise,
+    value_factor: f64,
+}
+
+impl NormalNoise {
+    const INPUT_FACTOR: f64 = 1.0181268882175227;
+
+    pub fn new(random: &mut XoroshiroRandom, parameters: &NoiseParameters) -> Self {
+        let first = PerlinNoise::new(random, parameters);
+        let second = PerlinNoise::new(random, parameters);
+        let non_zero = || {
+            parameters
+                .amplitudes
+                .iter()
+                .enumerate()
+                .filter(|(_, a)| **a != 0.)
+        };
+        let min = non_zero().map(|(i, _)| i as i32).min().unwrap_or(i32::MAX);
+        let max = non_zero().map(|(i, _)| i as i32).max().unwrap_or(i32::MIN);
+        let expected_deviation = 0.1 * (1. + 1. / (max.wrapping_sub(min) + 1) as f64);
+        Self {
+            first,
+            second,
+            value_factor: 0.16666666666666666 / expected_deviation,
+        }
+    }
+
+    pub fn get_value(&self, x: f64, y: f64, z: f64) -> f64 {
+        (self.first.get_value(x, y, z)
+            + self.second.get_value(
+                x * Self::INPUT_FACTOR,
+                y * Self::INPUT_FACTOR,
+                z * Self::INPUT_FACTOR,
+            ))
+            * self.value_factor
+    }
+
+    pub fn max_value(&self) -> f64 {
+        (self.first.max_value() + self.second.max_value()) * self.value_factor
+    }
+}
+
+impl Window {
+    /// Reads `firstOctave` and `amplitudes` from the output node of a noise window.
+    pub fn noise_parameters(&self) -> Result<NoiseParameters, AppError> {
+        if self.window_type != WindowType::Noise {
+            return Err(AppError::Evaluation(
+                format!("{} is not a noise", self).into(),
+            ));
+        }
+        let graph = &self.state.graph;
+        let value = |name: &str| {
+            graph[self.root_node]
+                .get_input(name)
+                .map(|in_id| graph.get_input(in_id).value())
+                .map_err(|_| AppError::WrongData(format!("missing input {}", name).into()))
+        };
+        match (value("firstOctave")?, value("amplitudes")?) {
+            (ValueType::Value(first_octave), ValueType::ValuesArray(amplitudes)) => {
+                Ok(NoiseParameters {
+                    first_octave: *first_octave as i32,
+                    amplitudes: amplitudes.clone(),
+                })
+            }
+            _ => Err(AppError::WrongData(format!("{}", self).into())),
+        }
+    }
+
+    /// Creates the same noise the game would for this file and world seed.
+    pub fn normal_noise(&self, seed: i64) -> Result<NormalNoise, AppError> {
+        let mut random = XoroshiroRandom::new(seed)
+            .fork_positional()
+            .from_hash_of(&format!("{}:{}", self.namespace, self.name));
+        Ok(NormalNoise::new(&mut random, &self.noise_parameters()?))
+    }
+}
@@ -0,0 +1,127 @@
+//! Ports of Minecraft's `XoroshiroRandomSource` and `XoroshiroPositionalRandomFactory`.
+
+const GOLDEN_RATIO_64: u64 = 0x9E37_79B9_7F4A_7C15;
+const SILVER_RATIO_64: u64 = 0x6A09_E667_F3BC_C909;
+
+fn mix_stafford_13(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Same as `RandomSupport.seedFromHashOf`, the MD5 hash split into two big endian longs.
+fn seed_from_hash_of(s: &str) -> (u64, u64) {
+    let digest = md5::compute(s.as_bytes());
+    let (lo, hi) = digest.0.split_at(8);
+    (
+        u64::from_be_bytes(lo.try_into().unwrap()),
+        u64::from_be_bytes(hi.try_into().unwrap()),
+    )
+}
+
+/// Same as `Mth.getSeed`.
+pub fn get_seed(x: i32, y: i32, z: i32) -> i64 {
+    let mut l = (x.wrapping_mul(3129871) as i64) ^ (z as i64).wrapping_mul(116129781) ^ y as i64;
+    l = l
+        .wrapping_mul(l)
+        .wrapping_mul(42317861)
+        .wrapping_add(l.wrapping_mul(11));
+    l >> 16
+}
+
+#[derive(Clone, Debug)]
+pub struct XoroshiroRandom {
+    lo: u64,
+    hi: u64,
+}
+
+impl XoroshiroRandom {
+    /// Seeds the generator the way a world seed is used.
+    pub fn new(seed: i64) -> Self {
+        let lo = seed as u64 ^ SILVER_RATIO_64;
+        let hi = lo.wrapping_add(GOLDEN_RATIO_64);
+        Self::from_parts(mix_stafford_13(lo), mix_stafford_13(hi))
+    }
+
+    pub fn from_parts(lo: u64, hi: u64) -> Self {
+        if lo | hi == 0 {
+            Self {
+                lo: GOLDEN_RATIO_64,
+                hi: SILVER_RATIO_64,
+            }
+        } else {
+            Self { lo, hi }
+        }
+    }
+
+    pub fn next_long(&mut self) -> i64 {
+        let (lo, mut hi) = (self.lo, self.hi);
+        let result = lo.wrapping_add(hi).rotate_left(17).wrapping_add(lo);
+        hi ^= lo;
+        self.lo = lo.rotate_left(49) ^ hi ^ (hi << 21);
+        self.hi = hi.rotate_left(28);
+        result as i64
+    }
+
+    pub fn next_int(&mut self) -> i32 {
+        self.next_long() as i32
+    }
+
+    /// Returns a number in `0..bound` without modulo bias, `bound` must be positive.
+    pub fn next_int_bounded(&mut self, bound: i32) -> i32 {
+        let bound = bound as u64;
+        let mut m = (self.next_int() as u32 as u64) * bound;
+        if m & 0xFFFF_FFFF < bound {
+            let threshold = (bound as u32).wrapping_neg() % bound as u32;
+            while m & 0xFFFF_FFFF < threshold as u64 {
+                m = (self.next_int() as u32 as u64) * bound;
+            }
+        }
+        (m >> 32) as i32
+    }
+
+    /// Returns a number in `min..=max`.
+    pub fn next_int_between_inclusive(&mut self, min: i32, max: i32) -> i32 {
+        self.next_int_bounded(max - min + 1) + min
+    }
+
+    pub fn next_boolean(&mut self) -> bool {
+        self.next_long() & 1 != 0
+    }
+
+    fn next_bits(&mut self, bits: u32) -> u64 {
+        (self.next_long() as u64) >> (64 - bits)
+    }
+
+    pub fn next_double(&mut self) -> f64 {
+        self.next_bits(53) as f64 * 1.1102230246251565E-16
+    }
+
+    pub fn next_float(&mut self) -> f32 {
+        self.next_bits(24) as f32 * 5.9604645E-8_f32
+    }
+
+    pub fn fork_positional(&mut self) -> PositionalRandomFactory {
+        PositionalRandomFactory {
+            lo: self.next_long() as u64,
+            hi: self.next_long() as u64,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct PositionalRandomFactory {
+    lo: u64,
+    hi: u64,
+}
+
+impl PositionalRandomFactory {
+    pub fn at(&self, x: i32, y: i32, z: i32) -> XoroshiroRandom {
+        XoroshiroRandom::from_parts(get_seed(x, y, z) as u64 ^ self.lo, self.hi)
+    }
+
+    pub fn from_hash_of(&self, s: &str) -> XoroshiroRandom {
+        let (lo, hi) = seed_from_hash_of(s);
+        XoroshiroRandom::from_parts(lo ^ self.lo, hi ^ self.hi)
+    }
+}
@@ -0,0 +1,615 @@
+//! Ports of `SurfaceRules` and the parts of `SurfaceSystem` they use, applied to a single
+//! made up column instead of a chunk.
+
+use std::sync::Arc;
+
+use egui_node_graph::{Node, NodeId};
+
+use crate::app::Identifier;
+use crate::errors::AppError;
+use crate::nodes::{
+    blocks::BlockState,
+    data_types::{list_entries, ValueType},
+    inner_data_types::{
+        surface_rule::SurfaceRuleType,
+        surface_rule_condition::{SurfaceRuleConditionType, SurfaceType, VerticalAnchor},
+    },
+    node_types::NodeTemplate,
+    NodeData,
+};
+use crate::window::{Window, WindowType};
+
+use super::density_function::{self, connected_node, value_input};
+use super::noise::NormalNoise;
+use super::random::{PositionalRandomFactory, XoroshiroRandom};
+
+/// Surface rule compiled from a node graph. Rules that place a block keep the node they come
+/// from, to show which one won.
+#[derive(Clone, Debug)]
+pub enum SurfaceRule {
+    Block(BlockState, NodeId),
+    Bandlands(Arc<Bands>, NodeId),
+    Sequence(Vec<SurfaceRule>),
+    Condition(Condition, Box<SurfaceRule>),
+}
+
+#[derive(Clone, Debug)]
+pub enum Condition {
+    Biome(Vec<String>),
+    NoiseThreshold {
+        noise: Arc<NormalNoise>,
+        min_threshold: f64,
+        max_threshold: f64,
+    },
+    VerticalGradient {
+        random: PositionalRandomFactory,
+        true_at_and_below: Anchor,
+        false_at_and_above: Anchor,
+    },
+    YAbove {
+        anchor: Anchor,
+        surface_depth_multiplier: i32,
+        add_stone_depth: bool,
+    },
+    Water {
+        offset: i32,
+        surface_depth_multiplier: i32,
+        add_stone_depth: bool,
+    },
+    Temperature,
+    Steep,
+    Not(Box<Condition>),
+    Hole,
+    AbovePreliminarySurface,
+    StoneDepth {
+        offset: i32,
+        add_surface_depth: bool,
+        secondary_depth_range: i32,
+        surface_type: SurfaceType,
+    },
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Anchor(VerticalAnchor, i32);
+
+impl Anchor {
+    fn resolve(self, column: &Column) -> i32 {
+        match self.0 {
+            VerticalAnchor::Absolute => self.1,
+            VerticalAnchor::AboveBottom => column.min_y + self.1,
+            VerticalAnchor::BelowTop => column.min_y + column.height - 1 - self.1,
+        }
+    }
+}
+
+/// Terracotta bands of badlands, same as `SurfaceSystem.clayBands`.
+#[derive(Debug)]
+pub struct Bands {
+    states: Vec<BlockState>,
+    offset: Arc<NormalNoise>,
+}
+
+impl Bands {
+    fn new(random: &mut XoroshiroRandom, offset: Arc<NormalNoise>) -> Self {
+        let terracotta = |color: &str| BlockState::new(&format!("minecraft:{}terracotta", color));
+        let mut states = vec![terracotta(""); 192];
+        let len = states.len() as i32;
+        let mut i = 0;
+        while i < len {
+            i += random.next_int_bounded(5) + 1;
+            if i < len {
+                states[i as usize] = terracotta("orange_");
+            }
+            i += 1;
+        }
+        let mut make_bands = |random: &mut XoroshiroRandom, base_width: i32, color: &str| {
+            for _ in 0..random.next_int_between_inclusive(6, 15) {
+                let width = base_width + random.next_int_bounded(3);
+                let start = random.next_int_bounded(len);
+                for j in (start..len).take(width as usize) {
+                    states[j as usize] = terracotta(color);
+                }
+            }
+        };
+        make_bands(random, 1, "yellow_");
+        make_bands(random, 2, "brown_");
+        make_bands(random, 1, "red_");
+        let white_bands = random.next_int_between_inclusive(9, 15);
+        let mut i = 0;
+        for _ in 0..white_bands {
+            if i >= len {
+                break;
+            }
+            states[i as usize] = terracotta("white_");
+            if i - 1 > 0 && random.next_boolean() {
+                states[i as usize - 1] = terracotta("light_gray_");
+            }
+            if i + 1 < len && random.next_boolean() {
+                states[i as usize + 1] = terracotta("light_gray_");
+            }
+            i += random.next_int_bounded(16) + 4;
+        }
+        Self { states, offset }
+    }
+
+    fn get(&self, x: i32, y: i32, z: i32) -> &BlockState {
+        // `Math.round`, halves are rounded up
+        let offset = (self.offset.get_value(x as f64, 0., z as f64) * 4. + 0.5).floor() as i32;
+        let len = self.states.len() as i32;
+        &self.states[(y + offset).rem_euclid(len) as usize]
+    }
+}
+
+/// A made up column of a world: ground up to `surface_y`, water or air above it.
+#[derive(Clone, Debug)]
+pub struct Column {
+    pub x: i32,
+    pub z: i32,
+    pub min_y: i32,
+    pub height: i32,
+    /// Highest block of the ground
+    pub surface_y: i32,
+    /// Water fills the column from the surface up to below this height, like the sea level
+    pub water_level: i32,
+    pub biome: String,
+    /// Thickness of the surface, in game the `surface` noise makes it 2 to 6 blocks
+    pub surface_depth: i32,
+    /// Value of the `surface_secondary` noise, in `-1.0..=1.0`
+    pub surface_secondary: f64,
+    pub steep: bool,
+    /// Whether it snows at the surface of the biome, what `temperature` checks
+    pub cold: bool,
+    pub default_block: BlockState,
+    pub default_fluid: BlockState,
+}
+
+/// Block of a column and the node of the rule that placed it, `None` where no rule did.
+#[derive(Clone, Debug)]
+pub struct ColumnBlock {
+    pub state: BlockState,
+    pub rule: Option<NodeId>,
+}
+
+/// What conditions know about the block being placed, same as `SurfaceRules.Context`.
+struct Context<'a> {
+    column: &'a Column,
+    y: i32,
+    stone_depth_above: i32,
+    stone_depth_below: i32,
+    /// Top of the water above, `None` if there is air
+    water_height: Option<i32>,
+}
+
+impl Column {
+    /// Blocks from `min_y` up. Like in game the rule only replaces the default block, and the
+    /// first block that matches wins.
+    pub fn blocks(&self, rule: &SurfaceRule) -> Vec<ColumnBlock> {
+        let block = |state: &BlockState, rule| ColumnBlock {
+            state: state.clone(),
+            rule,
+        };
+        self.contexts()
+            .map(|(y, context)| match context {
+                Some(context) => match rule.apply(&context) {
+                    Some((state, node)) => block(state, Some(node)),
+                    None => block(&self.default_block, None),
+                },
+                None if self.is_water(y) => block(&self.default_fluid, None),
+                None => block(&BlockState::new("minecraft:air"), None),
+            })
+            .collect()
+    }
+
+    /// Whether the condition holds at each y from `min_y` up, `None` above the ground.
+    pub fn test(&self, condition: &Condition) -> Vec<Option<bool>> {
+        self.contexts()
+            .map(|(_, context)| context.map(|context| condition.test(&context)))
+            .collect()
+    }
+
+    fn water_height(&self) -> Option<i32> {
+        Some(self.water_level).filter(|level| *level > self.surface_y + 1)
+    }
+
+    fn is_water(&self, y: i32) -> bool {
+        self.water_height().is_some_and(|height| y < height)
+    }
+
+    /// Every y from `min_y` up, with the context of the blocks of the ground.
+    fn contexts(&self) -> impl Iterator<Item = (i32, Option<Context<'_>>)> {
+        (self.min_y..self.min_y + self.height).map(move |y| {
+            let context = (y <= self.surface_y).then(|| Context {
+                column: self,
+                y,
+                stone_depth_above: self.surface_y - y + 1,
+                stone_depth_below: y - self.min_y + 1,
+                water_height: self.water_height(),
+            });
+            (y, context)
+        })
+    }
+
+    /// Lowest y `above_preliminary_surface` holds at. The game estimates the surface from the
+    /// noise router before placing blocks, here it is the actual one.
+    fn min_surface_level(&self) -> i32 {
+        self.surface_y + self.surface_depth - 8
+    }
+}
+
+impl SurfaceRule {
+    fn apply(&self, context: &Context) -> Option<(&BlockState, NodeId)> {
+        match self {
+            SurfaceRule::Block(state, node) => Some((state, *node)),
+            SurfaceRule::Bandlands(bands, node) => {
+                let column = context.column;
+                Some((bands.get(column.x, context.y, column.z), *node))
+            }
+            SurfaceRule::Sequence(rules) => rules.iter().find_map(|rule| rule.apply(context)),
+            SurfaceRule::Condition(condition, rule) => {
+                if condition.test(context) {
+                    rule.apply(context)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+impl Condition {
+    fn test(&self, context: &Context) -> bool {
+        let column = context.column;
+        let stone_depth = |add_stone_depth: bool| {
+            if add_stone_depth {
+                context.stone_depth_above
+            } else {
+                0
+            }
+        };
+        match self {
+            Condition::Biome(biomes) => biomes.contains(&column.biome),
+            Condition::NoiseThreshold {
+                noise,
+                min_threshold,
+                max_threshold,
+            } => {
+                let value = noise.get_value(column.x as f64, 0., column.z as f64);
+                value >= *min_threshold && value <= *max_threshold
+            }
+            Condition::VerticalGradient {
+                random,
+                true_at_and_below,
+                false_at_and_above,
+            } => {
+                let true_at = true_at_and_below.resolve(column);
+                let false_at = false_at_and_above.resolve(column);
+                if context.y <= true_at {
+                    return true;
+                }
+                if context.y >= false_at {
+                    return false;
+                }
+                let chance =
+                    super::clamped_map(context.y as f64, true_at as f64, false_at as f64, 1., 0.);
+                (random.at(column.x, context.y, column.z).next_float() as f64) < chance
+            }
+            Condition::YAbove {
+                anchor,
+                surface_depth_multiplier,
+                add_stone_depth,
+            } => {
+                context.y + stone_depth(*add_stone_depth)
+                    >= anchor.resolve(column) + column.surface_depth * surface_depth_multiplier
+            }
+            Condition::Water {
+                offset,
+                surface_depth_multiplier,
+                add_stone_depth,
+            } => match context.water_height {
+                None => true,
+                Some(water_height) => {
+                    context.y + stone_depth(*add_stone_depth)
+                        >= water_height + offset + column.surface_depth * surface_depth_multiplier
+                }
+            },
+            Condition::Temperature => column.cold,
+            Condition::Steep => column.steep,
+            Condition::Not(condition) => !condition.test(context),
+            Condition::Hole => column.surface_depth <= 0,
+            Condition::AbovePreliminarySurface => context.y >= column.min_surface_level(),
+            Condition::StoneDepth {
+                offset,
+                add_surface_depth,
+                secondary_depth_range,
+                surface_type,
+            } => {
+                let depth = match surface_type {
+                    SurfaceType::Floor => context.stone_depth_above,
+                    SurfaceType::Ceiling => context.stone_depth_below,
+                };
+                let surface_depth = if *add_surface_depth {
+                    column.surface_depth
+                } else {
+                    0
+                };
+                let secondary_depth = if *secondary_depth_range == 0 {
+                    0
+                } else {
+                    super::lerp(
+                        (column.surface_secondary + 1.) / 2.,
+                        0.,
+                        *secondary_depth_range as f64,
+                    ) as i32
+                };
+                depth <= 1 + offset + surface_depth + secondary_depth
+            }
+        }
+    }
+}
+
+/// Turns node graphs into `SurfaceRule`s and `Condition`s.
+pub struct Compiler<'a> {
+    functions: density_function::Compiler<'a>,
+}
+
+impl<'a> Compiler<'a> {
+    pub fn new(seed: i64, resolve: &'a dyn Fn(&Identifier) -> Option<&'a Window>) -> Self {
+        Self {
+            functions: density_function::Compiler::new(seed, resolve),
+        }
+    }
+
+    /// Compiles the rule starting at `node_id`, which may also be the output of noise settings.
+    pub fn compile_rule(
+        &mut self,
+        window: &Window,
+        node_id: NodeId,
+    ) -> Result<SurfaceRule, AppError> {
+        let node = node(window, node_id)?;
+        let typ = match node.user_data.template {
+            NodeTemplate::Output(WindowType::NoiseSettings) => {
+                return self.compile_rule_input(window, node_id, "surface_rule")
+            }
+            NodeTemplate::SurfaceRule(typ) => typ,
+            _ => {
+                return Err(AppError::Evaluation(
+                    format!("{} is not a surface rule", node.label).into(),
+                ))
+            }
+        };
+        match typ {
+            SurfaceRuleType::Block => Ok(SurfaceRule::Block(
+                block_input(window, node_id, "result_state")?,
+                node_id,
+            )),
+            SurfaceRuleType::Bandlands => {
+                let mut random = self.functions.random().from_hash_of("minecraft:clay_bands");
+                let offset = self.functions.noise("minecraft:clay_bands_offset")?;
+                Ok(SurfaceRule::Bandlands(
+                    Arc::new(Bands::new(&mut random, offset)),
+                    node_id,
+                ))
+            }
+            SurfaceRuleType::Sequence => Ok(SurfaceRule::Sequence(
+                list_nodes(window, node_id, "sequence")?
+                    .into_iter()
+                    .map(|next| self.compile_rule(window, next))
+                    .collect::<Result<_, _>>()?,
+            )),
+            SurfaceRuleType::Condition => Ok(SurfaceRule::Condition(
+                self.compile_condition_input(window, node_id, "if_true")?,
+                Box::new(self.compile_rule_input(window, node_id, "then_run")?),
+            )),
+        }
+    }
+
+    pub fn compile_condition(
+        &mut self,
+        window: &Window,
+        node_id: NodeId,
+    ) -> Result<Condition, AppError> {
+        let node = node(window, node_id)?;
+        let typ = match node.user_data.template {
+            NodeTemplate::SurfaceRuleCondition(typ) => typ,
+            _ => {
+                return Err(AppError::Evaluation(
+                    format!("{} is not a surface rule condition", node.label).into(),
+                ))
+            }
+        };
+        let int_input = |name| value_input(window, node_id, name).map(|v| v as i32);
+        use SurfaceRuleConditionType::*;
+        Ok(match typ {
+            Biome => Condition::Biome(
+                list_nodes(window, node_id, "biome_is")?
+                    .into_iter()
+                    .map(|next| reference_name(window, next, WindowType::Biome))
+                    .collect::<Result<_, _>>()?,
+            ),
+            NoiseThreshold => {
+                let noise = match connected_node(window, node_id, "noise")? {
+                    Some(next) => reference_name(window, next, WindowType::Noise)?,
+                    None => match input_value(window, node_id, "noise")? {
+                        ValueType::Reference(_, name) => name.clone(),
+                        _ => return Err(AppError::WrongData("noise".into())),
+                    },
+                };
+                Condition::NoiseThreshold {
+                    noise: self.functions.noise(&noise)?,
+                    min_threshold: value_input(window, node_id, "min_threshold")?,
+                    max_threshold: value_input(window, node_id, "max_threshold")?,
+                }
+            }
+            VerticalGradient => {
+                let random_name = match input_value(window, node_id, "random_name")? {
+                    ValueType::DullReference(name) => name.clone(),
+                    _ => return Err(AppError::WrongData("random_name".into())),
+                };
+                Condition::VerticalGradient {
+                    random: self
+                        .functions
+                        .random()
+                        .from_hash_of(&full_name(&random_name))
+                        .fork_positional(),
+                    true_at_and_below: anchor_input(window, node_id, "true_at_and_below")?,
+                    false_at_and_above: anchor_input(window, node_id, "false_at_and_above")?,
+                }
+            }
+            YAbove => Condition::YAbove {
+                anchor: anchor_input(window, node_id, "anchor")?,
+                surface_depth_multiplier: int_input("surface_depth_multiplier")?,
+                add_stone_depth: bool_input(window, node_id, "add_stone_depth")?,
+            },
+            Water => Condition::Water {
+                offset: int_input("offset")?,
+                surface_depth_multiplier: int_input("surface_depth_multiplier")?,
+                add_stone_depth: bool_input(window, node_id, "add_stone_depth")?,
+            },
+            Temperature => Condition::Temperature,
+            Steep => Condition::Steep,
+            Not => Condition::Not(Box::new(
+                self.compile_condition_input(window, node_id, "invert")?,
+            )),
+            Hole => Condition::Hole,
+            AbovePreliminarySurface => Condition::AbovePreliminarySurface,
+            StoneDepth => Condition::StoneDepth {
+                offset: int_input("offset")?,
+                add_surface_depth: bool_input(window, node_id, "add_surface_depth")?,
+                secondary_depth_range: int_input("secondary_depth_range")?,
+                surface_type: match input_value(window, node_id, "surface_type")? {
+                    ValueType::SurfaceType(x) => *x,
+                    _ => return Err(AppError::WrongData("surface_type".into())),
+                },
+            },
+        })
+    }
+
+    fn compile_rule_input(
+        &mut self,
+        window: &Window,
+        node_id: NodeId,
+        name: &str,
+    ) -> Result<SurfaceRule, AppError> {
+        match connected_node(window, node_id, name)? {
+            Some(next) => self.compile_rule(window, next),
+            None => Err(AppError::Evaluation(
+                format!("nothing connected to {}", name).into(),
+            )),
+        }
+    }
+
+    fn compile_condition_input(
+        &mut self,
+        window: &Window,
+        node_id: NodeId,
+        name: &str,
+    ) -> Result<Condition, AppError> {
+        match connected_node(window, node_id, name)? {
+            Some(next) => self.compile_condition(window, next),
+            None => Err(AppError::Evaluation(
+                format!("nothing connected to {}", name).into(),
+            )),
+        }
+    }
+}
+
+impl Window {
+    /// Compiles the surface rule at `node_id`, or the one of the whole file.
+    pub fn compile_surface_rule<'a>(
+        &self,
+        node_id: Option<NodeId>,
+        seed: i64,
+        resolve: &'a dyn Fn(&Identifier) -> Option<&'a Window>,
+    ) -> Result<SurfaceRule, AppError> {
+        Compiler::new(seed, resolve).compile_rule(self, node_id.unwrap_or(self.root_node))
+    }
+}
+
+fn node(window: &Window, node_id: NodeId) -> Result<&Node<NodeData>, AppError> {
+    window
+        .state
+        .graph
+        .nodes
+        .get(node_id)
+        .ok_or_else(|| AppError::Evaluation("node does not exist".into()))
+}
+
+fn input_value<'w>(
+    window: &'w Window,
+    node_id: NodeId,
+    name: &str,
+) -> Result<&'w ValueType, AppError> {
+    let graph = &window.state.graph;
+    let in_id = graph[node_id]
+        .get_input(name)
+        .map_err(|_| AppError::WrongData(format!("missing input {}", name).into()))?;
+    Ok(graph.get_input(in_id).value())
+}
+
+fn bool_input(window: &Window, node_id: NodeId, name: &str) -> Result<bool, AppError> {
+    match input_value(window, node_id, name)? {
+        ValueType::Bool(x) => Ok(*x),
+        _ => Err(AppError::WrongData(name.into())),
+    }
+}
+
+fn anchor_input(window: &Window, node_id: NodeId, name: &str) -> Result<Anchor, AppError> {
+    match input_value(window, node_id, name)? {
+        ValueType::VerticalAnchor(anchor, y) => Ok(Anchor(*anchor, *y)),
+        _ => Err(AppError::WrongData(name.into())),
+    }
+}
+
+/// Reads a block state from an input, following connections to block nodes.
+fn block_input(window: &Window, node_id: NodeId, name: &str) -> Result<BlockState, AppError> {
+    if let Some(next) = connected_node(window, node_id, name)? {
+        return match window.state.graph[next].user_data.template {
+            NodeTemplate::ConstantBlock => block_input(window, next, "block"),
+            _ => Err(AppError::WrongData(name.into())),
+        };
+    }
+    match input_value(window, node_id, name)? {
+        ValueType::Block(state) => Ok(state.clone()),
+        _ => Err(AppError::WrongData(name.into())),
+    }
+}
+
+/// Nodes connected to the entries of list `name`, unconnected entries are skipped.
+fn list_nodes(window: &Window, node_id: NodeId, name: &str) -> Result<Vec<NodeId>, AppError> {
+    let graph = &window.state.graph;
+    let (position, len) = list_entries(graph, node_id, name)
+        .ok_or_else(|| AppError::WrongData(format!("missing input {}", name).into()))?;
+    Ok(graph[node_id].inputs[position + 1..=position + len]
+        .iter()
+        .filter_map(|(_, in_id)| graph.connection(*in_id))
+        .map(|out_id| graph.get_output(out_id).node)
+        .collect())
+}
+
+/// Full name of what the reference node points to.
+fn reference_name(
+    window: &Window,
+    node_id: NodeId,
+    window_type: WindowType,
+) -> Result<String, AppError> {
+    let node = node(window, node_id)?;
+    match (node.user_data.template, node.inputs.first()) {
+        (NodeTemplate::Reference(typ), Some((_, in_id))) if typ == window_type => {
+            match window.state.graph.get_input(*in_id).value() {
+                ValueType::Reference(_, name) => Ok(full_name(name)),
+                _ => Err(AppError::WrongData(node.label.clone().into())),
+            }
+        }
+        _ => Err(AppError::Evaluation(
+            format!("{} is not a reference", node.label).into(),
+        )),
+    }
+}
+
+/// Adds the `minecraft` namespace to names without one.
+fn full_name(name: &str) -> String {
+    match name.split_once(':') {
+        Some(_) => name.to_string(),
+        None => format!("minecraft:{}", name),
+    }
+}
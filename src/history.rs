@@ -0,0 +1,195 @@
+//! Undo/redo of graph edits, kept separately for every `Window`.
+
+use std::collections::VecDeque;
+
+use eframe::egui::{self, Key};
+use eframe::epaint::Pos2;
+use egui_node_graph::{NodeId, NodeResponse};
+
+use crate::nodes::{GraphType, NodeData, Response};
+use crate::window::Window;
+
+/// How many edits can be undone.
+pub const HISTORY_DEPTH: usize = 100;
+
+#[derive(Clone)]
+struct Snapshot {
+    graph: GraphType,
+    node_order: Vec<NodeId>,
+    /// only used for nodes that don't exist anymore when going back to this snapshot
+    node_positions: Vec<(NodeId, Pos2)>,
+}
+
+#[derive(Clone, Default)]
+pub struct History {
+    undo: VecDeque<Snapshot>,
+    redo: Vec<Snapshot>,
+    /// Graph after the last recorded edit, `None` until the window is shown for the first time
+    current: Option<Snapshot>,
+    /// Changes whenever the graph does, what is worked out from the graph is kept until then
+    generation: u64,
+}
+
+impl History {
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+}
+
+/// Whether the response always changes the graph, value edits are found by comparing instead.
+fn is_edit(response: &NodeResponse<Response, NodeData>) -> bool {
+    match response {
+        NodeResponse::CreatedNode(_)
+        | NodeResponse::DeleteNodeFull { .. }
+        | NodeResponse::ConnectEventEnded { .. }
+        | NodeResponse::DisconnectEvent { .. } => true,
+        NodeResponse::User(response) => matches!(
+            response,
+            Response::IncreaseInputs(..)
+                | Response::DecreaseInputs(..)
+                | Response::ChangeNodeType(..)
+                | Response::RemoveUnknownField(..)
+        ),
+        _ => false,
+    }
+}
+
+impl Window {
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            graph: self.state.graph.clone(),
+            node_order: self.state.node_order.clone(),
+            node_positions: self
+                .state
+                .node_positions
+                .iter()
+                .map(|(id, pos)| (id, *pos))
+                .collect(),
+        }
+    }
+
+    /// Whether any input value differs from the last recorded graph.
+    fn values_changed(&self) -> bool {
+        let current = match &self.history.current {
+            Some(current) => &current.graph,
+            None => return false,
+        };
+        let graph = &self.state.graph;
+        graph.inputs.len() != current.inputs.len()
+            || graph.inputs.iter().any(|(id, input)| {
+                !matches!(current.inputs.get(id), Some(old) if old.value == input.value)
+            })
+    }
+
+    /// Records the edits made this frame. Dragging a value or typing into a field is recorded
+    /// once the pointer is released and no field has focus.
+    pub fn record_history(
+        &mut self,
+        ctx: &egui::Context,
+        responses: &[NodeResponse<Response, NodeData>],
+    ) {
+        if self.history.current.is_none() {
+            self.history.current = Some(self.snapshot());
+            return;
+        }
+        let settled = !ctx.input().pointer.any_down() && ctx.memory().focus().is_none();
+        if responses.iter().any(is_edit) || (settled && self.values_changed()) {
+            self.commit();
+            // What was drawn before the graph this frame shows the edit on the next one
+            ctx.request_repaint();
+        } else if !settled && self.values_changed() {
+            // A value being dragged or typed is recorded once it's done, but shown right away
+            self.history.generation += 1;
+        }
+    }
+
+    /// Makes a change from outside the graph editor, like a rename, as one step that can be
+    /// undone. Windows that weren't shown yet get the graph before the change as their start.
+    pub fn record_edit<T>(&mut self, edit: impl FnOnce(&mut Self) -> T) -> T {
+        if self.history.current.is_none() {
+            self.history.current = Some(self.snapshot());
+        } else if self.values_changed() {
+            self.commit();
+        }
+        let result = edit(self);
+        self.commit();
+        result
+    }
+
+    fn commit(&mut self) {
+        let snapshot = self.snapshot();
+        if let Some(previous) = self.history.current.replace(snapshot) {
+            if self.history.undo.len() == HISTORY_DEPTH {
+                self.history.undo.pop_front();
+            }
+            self.history.undo.push_back(previous);
+        }
+        self.history.redo.clear();
+        self.history.generation += 1;
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        for (node_id, pos) in &snapshot.node_positions {
+            if !self.state.node_positions.contains_key(*node_id) {
+                self.state.node_positions.insert(*node_id, *pos);
+            }
+        }
+        self.state.graph = snapshot.graph.clone();
+        self.state.node_order = snapshot.node_order.clone();
+        self.state.selected_nodes.clear();
+        self.state.connection_in_progress = None;
+        if let Some(active_node) = self.user_state.active_node {
+            if !self.state.graph.nodes.contains_key(active_node) {
+                self.user_state.active_node = None;
+            }
+        }
+        self.history.current = Some(snapshot);
+        self.history.generation += 1;
+    }
+
+    pub fn undo(&mut self) {
+        if self.values_changed() {
+            self.commit();
+        }
+        if let Some(snapshot) = self.history.undo.pop_back() {
+            if let Some(current) = self.history.current.take() {
+                self.history.redo.push(current);
+            }
+            self.restore(snapshot);
+        }
+    }
+
+    pub fn redo(&mut self) {
+        if let Some(snapshot) = self.history.redo.pop() {
+            if let Some(current) = self.history.current.take() {
+                self.history.undo.push_back(current);
+            }
+            self.restore(snapshot);
+        }
+    }
+
+    /// Ctrl+Z and Ctrl+Shift+Z, left to text fields while one has focus.
+    pub fn handle_history_keys(&mut self, ctx: &egui::Context) {
+        if ctx.memory().focus().is_some() {
+            return;
+        }
+        let (undo, redo) = {
+            let input = ctx.input();
+            let z = input.modifiers.command && input.key_pressed(Key::Z);
+            (z && !input.modifiers.shift, z && input.modifiers.shift)
+        };
+        if undo {
+            self.undo();
+        } else if redo {
+            self.redo();
+        }
+    }
+}
@@ -0,0 +1,457 @@
+//! The JSON of the active window next to its graph. The values of the selected nodes are
+//! highlighted, edits are read back into the graph as long as they are valid JSON.
+
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+use eframe::egui::{text::LayoutJob, Align, ScrollArea, TextEdit, TextFormat, TextStyle, Ui};
+use eframe::epaint::{text::cursor::CCursor, Color32, Pos2, Stroke};
+use egui_node_graph::NodeId;
+use json::JsonValue;
+
+use crate::app::Identifier;
+use crate::errors::AppError;
+use crate::nodes::rebuild_node;
+use crate::serializer::canonical;
+use crate::window::Window;
+
+/// A value in the text of `pretty_with_spans`.
+pub struct Span<'a> {
+    /// JSON path of the value, like `$.noise_router.barrier`
+    pub path: String,
+    pub range: Range<usize>,
+    pub value: &'a JsonValue,
+}
+
+/// Same text as `JsonValue::pretty(4)`, with where every value of it is.
+pub fn pretty_with_spans(json: &JsonValue) -> (String, Vec<Span<'_>>) {
+    let mut text = String::new();
+    let mut spans = Vec::new();
+    write_value(json, "$".to_string(), 0, &mut text, &mut spans);
+    (text, spans)
+}
+
+fn write_value<'a>(
+    value: &'a JsonValue,
+    path: String,
+    depth: usize,
+    text: &mut String,
+    spans: &mut Vec<Span<'a>>,
+) {
+    let start = text.len();
+    let new_line = |text: &mut String, depth: usize| {
+        text.push('\n');
+        text.push_str(&" ".repeat(4 * depth));
+    };
+    match value {
+        JsonValue::Object(object) if !object.is_empty() => {
+            text.push('{');
+            for (i, (key, value)) in object.iter().enumerate() {
+                if i > 0 {
+                    text.push(',');
+                }
+                new_line(text, depth + 1);
+                text.push_str(&JsonValue::from(key).dump());
+                text.push_str(": ");
+                write_value(value, format!("{}.{}", path, key), depth + 1, text, spans);
+            }
+            new_line(text, depth);
+            text.push('}');
+        }
+        JsonValue::Array(array) if !array.is_empty() => {
+            text.push('[');
+            for (i, value) in array.iter().enumerate() {
+                if i > 0 {
+                    text.push(',');
+                }
+                new_line(text, depth + 1);
+                write_value(value, format!("{}[{}]", path, i), depth + 1, text, spans);
+            }
+            new_line(text, depth);
+            text.push(']');
+        }
+        value => text.push_str(&value.dump()),
+    }
+    spans.push(Span {
+        path,
+        range: start..text.len(),
+        value,
+    });
+}
+
+/// Where the JSON of `node_id` is among `spans` of the whole file. A node used in several
+/// places, or written the same as another node next to it, is found in all of them.
+pub fn node_spans(window: &Window, node_id: NodeId, spans: &[Span]) -> Vec<Range<usize>> {
+    node_paths(window, node_id, spans, &mut HashSet::new())
+        .into_iter()
+        .filter_map(|path| spans.iter().find(|span| span.path == path))
+        .map(|span| span.range.clone())
+        .collect()
+}
+
+/// Paths of the values `node_id` was written as, found below the paths of the nodes it's
+/// connected to. `visited` are the nodes being looked for further down.
+fn node_paths(
+    window: &Window,
+    node_id: NodeId,
+    spans: &[Span],
+    visited: &mut HashSet<NodeId>,
+) -> Vec<String> {
+    if node_id == window.root_node {
+        return vec!["$".to_string()];
+    }
+    if !visited.insert(node_id) {
+        return Vec::new();
+    }
+    let graph = &window.state.graph;
+    let outputs: Vec<_> = graph[node_id].output_ids().collect();
+    let consumers: HashSet<NodeId> = graph
+        .connections
+        .iter()
+        .filter(|(_, output_id)| outputs.contains(output_id))
+        .map(|(input_id, _)| graph.get_input(input_id).node)
+        .collect();
+    let parent_paths: Vec<String> = consumers
+        .into_iter()
+        .flat_map(|consumer| node_paths(window, consumer, spans, visited))
+        .collect();
+    visited.remove(&node_id);
+    let value = match window.serialize_node(node_id) {
+        Ok(value) => canonical(value),
+        Err(_) => return Vec::new(),
+    };
+    let below = |path: &str, parent: &str| {
+        path.strip_prefix(parent)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(['.', '[']))
+    };
+    spans
+        .iter()
+        .filter(|span| parent_paths.iter().any(|parent| below(&span.path, parent)))
+        .filter(|span| *span.value == value)
+        .map(|span| span.path.clone())
+        .collect()
+}
+
+impl Window {
+    /// Builds what is connected to the output node again from `json`, nodes that aren't part
+    /// of the file are kept. Nodes whose value is still at the same place in the file stay
+    /// where they were, the others are placed next to what they feed.
+    pub fn read_json(&mut self, json: &JsonValue) -> Vec<AppError> {
+        let file_nodes = self.file_nodes();
+        let mut old_positions: HashMap<String, Pos2> = self
+            .first_paths(&file_nodes)
+            .into_iter()
+            .filter_map(|(node_id, path)| Some((path, *self.state.node_positions.get(node_id)?)))
+            .collect();
+        for node_id in &file_nodes {
+            self.state.graph.remove_node(*node_id);
+            self.state.node_positions.remove(*node_id);
+        }
+        self.state.node_order.retain(|id| !file_nodes.contains(id));
+        self.state
+            .selected_nodes
+            .retain(|id| !file_nodes.contains(id));
+        if self
+            .user_state
+            .active_node
+            .is_some_and(|id| file_nodes.contains(&id))
+        {
+            self.user_state.active_node = None;
+        }
+        let template = self.state.graph[self.root_node].user_data.template;
+        rebuild_node(
+            self.root_node,
+            &mut self.state.graph,
+            &mut self.user_state,
+            template,
+        );
+        let root = &mut self.state.graph[self.root_node].user_data;
+        root.unknown_fields.clear();
+        root.single_id_lists.clear();
+
+        let errors = self.deserialize_nodes(json);
+        let file_nodes = self.file_nodes();
+        let paths = self.first_paths(&file_nodes);
+        let mut placed = HashSet::new();
+        for (node_id, path) in paths {
+            // Two nodes written the same way are found at the same paths, only one gets it
+            if let Some(pos) = old_positions.remove(&path) {
+                self.state.node_positions.insert(node_id, pos);
+                placed.insert(node_id);
+            }
+        }
+        let new_nodes: Vec<NodeId> = file_nodes
+            .into_iter()
+            .filter(|id| !placed.contains(id))
+            .collect();
+        self.place_new(&new_nodes);
+        errors
+    }
+
+    /// Nodes connected to the output node, directly or through others.
+    fn file_nodes(&self) -> Vec<NodeId> {
+        let graph = &self.state.graph;
+        let mut file_nodes = HashSet::new();
+        let mut stack = vec![self.root_node];
+        while let Some(node_id) = stack.pop() {
+            for input_id in graph[node_id].input_ids() {
+                if let Some(output_id) = graph.connection(input_id) {
+                    let next = graph.get_output(output_id).node;
+                    if next != self.root_node && file_nodes.insert(next) {
+                        stack.push(next);
+                    }
+                }
+            }
+        }
+        self.state
+            .node_order
+            .iter()
+            .copied()
+            .filter(|id| file_nodes.contains(id))
+            .collect()
+    }
+
+    /// The first path each of `nodes` is written at in the file, nodes that aren't found are
+    /// left out.
+    fn first_paths(&self, nodes: &[NodeId]) -> Vec<(NodeId, String)> {
+        let json = match self.serialize() {
+            Ok(json) => canonical(json),
+            Err(_) => return Vec::new(),
+        };
+        let (_, spans) = pretty_with_spans(&json);
+        nodes
+            .iter()
+            .filter_map(|node_id| {
+                let path = node_paths(self, *node_id, &spans, &mut HashSet::new())
+                    .into_iter()
+                    .next()?;
+                Some((*node_id, path))
+            })
+            .collect()
+    }
+}
+
+/// Byte offset of a 1-based line and column (in characters) of `text`.
+fn text_offset(text: &str, line: usize, column: usize) -> Option<usize> {
+    let line_start = if line <= 1 {
+        0
+    } else {
+        text.match_indices('\n').nth(line - 2)?.0 + 1
+    };
+    text[line_start..]
+        .char_indices()
+        .nth(column.saturating_sub(1))
+        .map(|(i, _)| line_start + i)
+}
+
+/// Text of the active window, kept in sync with its graph.
+pub struct JsonView {
+    pub show: bool,
+    text: String,
+    /// Window `text` belongs to
+    window: Option<Identifier>,
+    /// Why `text` can't be read, and where if that's known
+    parse_error: Option<(String, Option<usize>)>,
+    /// Problems found while reading `text` into the graph
+    errors: Vec<String>,
+    /// The graph couldn't be written, `text` is what it was before
+    graph_error: Option<String>,
+    /// Canonical JSON of the graph and its text, only made again when the graph changes
+    graph_json: Option<JsonValue>,
+    graph_text: String,
+    /// What the graph was written as when `graph_json` was made
+    graph_key: Option<String>,
+    /// Byte ranges of the selected nodes in `text`
+    highlighted: Vec<Range<usize>>,
+    /// Selection `highlighted` was found for, `None` once the text changed
+    key: Option<Vec<NodeId>>,
+    /// Selection the text was last scrolled to
+    scrolled_to: Vec<NodeId>,
+    has_focus: bool,
+}
+
+impl JsonView {
+    pub fn new() -> Self {
+        Self {
+            show: false,
+            text: String::new(),
+            window: None,
+            parse_error: None,
+            errors: Vec::new(),
+            graph_error: None,
+            graph_json: None,
+            graph_text: String::new(),
+            graph_key: None,
+            highlighted: Vec::new(),
+            key: None,
+            scrolled_to: Vec::new(),
+            has_focus: false,
+        }
+    }
+
+    /// Returns whether the window was read again from the text without problems.
+    pub fn ui(&mut self, ui: &mut Ui, window: &mut Window) -> bool {
+        let mut read = false;
+        let id = window.identifier();
+        if self.window.as_ref() != Some(&id) {
+            *self = Self {
+                show: self.show,
+                window: Some(id),
+                ..Self::new()
+            };
+        }
+        let serialized = window.serialize();
+        let graph_key = format!(
+            "{:?}",
+            serialized
+                .as_ref()
+                .map(|json| json.dump())
+                .map_err(|e| e.to_string())
+        );
+        if self.graph_key.as_ref() != Some(&graph_key) {
+            match serialized {
+                Ok(json) => {
+                    let json = canonical(json);
+                    self.graph_text = json.pretty(4);
+                    self.graph_json = Some(json);
+                    self.graph_error = None;
+                }
+                Err(e) => {
+                    self.graph_json = None;
+                    self.graph_text.clear();
+                    self.graph_error = Some(e.to_string());
+                }
+            }
+            self.graph_key = Some(graph_key);
+            self.key = None;
+        }
+        // The text follows the graph unless it's being edited or can't be read
+        if !self.has_focus
+            && self.parse_error.is_none()
+            && self.graph_error.is_none()
+            && self.text != self.graph_text
+        {
+            self.text = self.graph_text.clone();
+            self.key = None;
+        }
+
+        let selection = &window.state.selected_nodes;
+        let selection_changed = self.scrolled_to != *selection;
+        if self.key.as_ref() != Some(selection) {
+            self.highlighted = match &self.graph_json {
+                Some(json) if self.text == self.graph_text => {
+                    let (_, spans) = pretty_with_spans(json);
+                    selection
+                        .iter()
+                        .flat_map(|node_id| node_spans(window, *node_id, &spans))
+                        .collect()
+                }
+                _ => Vec::new(),
+            };
+            self.key = Some(selection.clone());
+        }
+        self.scrolled_to = selection.clone();
+
+        if let Some(e) = &self.graph_error {
+            ui.colored_label(Color32::RED, format!("the graph can't be written: {}", e));
+        }
+        if let Some((e, _)) = &self.parse_error {
+            ui.colored_label(Color32::RED, e);
+        }
+        for e in &self.errors {
+            ui.colored_label(Color32::YELLOW, e);
+        }
+
+        let highlighted = self.highlighted.clone();
+        let error_at = self.parse_error.as_ref().and_then(|(_, at)| *at);
+        let mut layouter = |ui: &Ui, text: &str, wrap_width: f32| {
+            let format = TextFormat {
+                font_id: TextStyle::Monospace.resolve(ui.style()),
+                color: ui.visuals().text_color(),
+                ..Default::default()
+            };
+            let mut bounds = vec![0, text.len()];
+            for range in &highlighted {
+                bounds.extend([range.start, range.end]);
+            }
+            // The error is from before the last edit, the text may be shorter by now
+            if let Some(at) = error_at.filter(|at| text.is_char_boundary(*at)) {
+                let end = text[at..]
+                    .chars()
+                    .next()
+                    .map_or(at, |ch| at + ch.len_utf8());
+                bounds.extend([at, end]);
+            }
+            bounds.retain(|i| text.is_char_boundary(*i));
+            bounds.sort_unstable();
+            bounds.dedup();
+            let mut job = LayoutJob::default();
+            for pair in bounds.windows(2) {
+                let (start, end) = (pair[0], pair[1]);
+                let mut format = format.clone();
+                if highlighted.iter().any(|r| r.start <= start && end <= r.end) {
+                    format.background = ui.visuals().selection.bg_fill.linear_multiply(0.5);
+                }
+                if error_at == Some(start) {
+                    format.underline = Stroke::new(2., Color32::RED);
+                }
+                job.append(&text[start..end], 0., format);
+            }
+            job.wrap.max_width = wrap_width;
+            ui.fonts().layout_job(job)
+        };
+
+        ScrollArea::both().show(ui, |ui| {
+            let output = TextEdit::multiline(&mut self.text)
+                .code_editor()
+                .desired_width(f32::INFINITY)
+                .layouter(&mut layouter)
+                .show(ui);
+            self.has_focus = output.response.has_focus();
+            if selection_changed {
+                if let Some(range) = self.highlighted.first() {
+                    let chars = self.text[..range.start].chars().count();
+                    let cursor = output.galley.from_ccursor(CCursor::new(chars));
+                    let rect = output.galley.pos_from_cursor(&cursor);
+                    ui.scroll_to_rect(
+                        rect.translate(output.text_draw_pos.to_vec2()),
+                        Some(Align::Center),
+                    );
+                }
+            }
+            if output.response.changed() {
+                self.highlighted.clear();
+                self.key = None;
+                self.parse_error = None;
+                match json::parse(&self.text) {
+                    Ok(json) if self.graph_json.as_ref() != Some(&canonical(json.clone())) => {
+                        self.errors = window
+                            .read_json(&json)
+                            .iter()
+                            .map(|e| e.to_string())
+                            .collect();
+                        read = self.errors.is_empty();
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        let at = match e {
+                            json::Error::UnexpectedCharacter { line, column, .. } => {
+                                text_offset(&self.text, line, column)
+                            }
+                            json::Error::UnexpectedEndOfJson => Some(self.text.len()),
+                            _ => None,
+                        };
+                        self.parse_error = Some((e.to_string(), at));
+                    }
+                }
+            }
+        });
+        read
+    }
+}
+
+impl Default for JsonView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
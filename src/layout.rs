@@ -0,0 +1,298 @@
+//! Automatic placement of nodes, in columns from the output on the right to the leaves on the
+//! left.
+
+use std::collections::{HashMap, HashSet};
+
+use eframe::epaint::{Pos2, Rect, Vec2};
+use egui_node_graph::NodeId;
+
+use crate::nodes::data_types::ValueType;
+use crate::window::Window;
+
+/// Space between columns of nodes
+const COLUMN_GAP: f32 = 60.;
+/// Space between nodes of the same column
+const ROW_GAP: f32 = 20.;
+
+impl Window {
+    /// Arranges every node of the graph.
+    pub fn arrange_all(&mut self) {
+        let nodes = self.state.node_order.clone();
+        self.arrange(&nodes);
+    }
+
+    /// Arranges the selected nodes among themselves, the others stay where they are.
+    pub fn arrange_selection(&mut self) {
+        let nodes = self.state.selected_nodes.clone();
+        self.arrange(&nodes);
+    }
+
+    /// Places `nodes` in layers by their distance from the nodes they feed, a node used by
+    /// several others goes left of all of them. Entries of lists keep their order. The top
+    /// right corner of the nodes stays where it was.
+    pub(crate) fn arrange(&mut self, nodes: &[NodeId]) {
+        let graph = &self.state.graph;
+        let set: HashSet<NodeId> = nodes
+            .iter()
+            .copied()
+            .filter(|id| graph.nodes.contains_key(*id))
+            .collect();
+        if set.is_empty() {
+            return;
+        }
+        // Nodes connected to the inputs of each node, in the order of the inputs
+        let children: HashMap<NodeId, Vec<NodeId>> = set
+            .iter()
+            .map(|id| {
+                let mut children = Vec::new();
+                for (_, input_id) in &graph[*id].inputs {
+                    if let Some(output_id) = graph.connection(*input_id) {
+                        let child = graph.get_output(output_id).node;
+                        if set.contains(&child) && child != *id && !children.contains(&child) {
+                            children.push(child);
+                        }
+                    }
+                }
+                (*id, children)
+            })
+            .collect();
+        let sizes: HashMap<NodeId, Vec2> =
+            set.iter().map(|id| (*id, self.node_size(*id))).collect();
+        let old_bounds = set
+            .iter()
+            .filter_map(|id| {
+                let pos = *self.state.node_positions.get(*id)?;
+                Some(Rect::from_min_size(pos, sizes[id]))
+            })
+            .reduce(|a, b| a.union(b));
+
+        let has_parent: HashSet<NodeId> = children.values().flatten().copied().collect();
+        let mut roots: Vec<NodeId> = set
+            .iter()
+            .copied()
+            .filter(|id| !has_parent.contains(id))
+            .collect();
+        let position = |id: &NodeId| {
+            let pos = self
+                .state
+                .node_positions
+                .get(*id)
+                .copied()
+                .unwrap_or_default();
+            (*id != self.root_node, pos.y, pos.x)
+        };
+        let by_position = |a: &NodeId, b: &NodeId| {
+            let (a, b) = (position(a), position(b));
+            a.0.cmp(&b.0)
+                .then_with(|| a.1.total_cmp(&b.1))
+                .then_with(|| a.2.total_cmp(&b.2))
+        };
+        roots.sort_by(by_position);
+        // Nodes that are only part of a cycle have no root, the first one of them becomes one
+        let mut ordered: Vec<NodeId> = set.iter().copied().collect();
+        ordered.sort_by(by_position);
+
+        // Longest distance from a root, a cycle can't push nodes further than there are nodes
+        let mut layers: HashMap<NodeId, usize> = HashMap::new();
+        let mut stack: Vec<NodeId> = Vec::new();
+        let starts: Vec<NodeId> = roots.iter().chain(&ordered).copied().collect();
+        for start in starts {
+            if layers.contains_key(&start) {
+                continue;
+            }
+            layers.insert(start, 0);
+            stack.push(start);
+            while let Some(id) = stack.pop() {
+                let layer = layers[&id] + 1;
+                for child in &children[&id] {
+                    if layer < set.len() && layers.get(child).is_none_or(|l| *l < layer) {
+                        layers.insert(*child, layer);
+                        stack.push(*child);
+                    }
+                }
+            }
+            if !roots.contains(&start) {
+                roots.push(start);
+            }
+        }
+
+        let mut placer = Placer {
+            children: &children,
+            sizes: &sizes,
+            layers: &layers,
+            next_y: vec![0.; layers.values().max().map_or(0, |max| max + 1)],
+            y: HashMap::new(),
+        };
+        for root in &roots {
+            placer.place(*root);
+        }
+
+        // Columns are as wide as their widest node, nodes are aligned to the right of them
+        let mut widths = vec![0f32; placer.next_y.len()];
+        for (id, layer) in &layers {
+            widths[*layer] = widths[*layer].max(sizes[id].x);
+        }
+        let mut rights = Vec::with_capacity(widths.len());
+        let mut right = 0.;
+        for width in &widths {
+            rights.push(right);
+            right -= width + COLUMN_GAP;
+        }
+        let new_pos = |id: &NodeId| Pos2::new(rights[layers[id]] - sizes[id].x, placer.y[id]);
+        let new_bounds = set
+            .iter()
+            .map(|id| Rect::from_min_size(new_pos(id), sizes[id]))
+            .reduce(|a, b| a.union(b));
+        let offset = match (old_bounds, new_bounds) {
+            (Some(old), Some(new)) => old.right_top() - new.right_top(),
+            _ => Vec2::ZERO,
+        };
+        for id in &set {
+            self.state.node_positions.insert(*id, new_pos(id) + offset);
+        }
+    }
+
+    /// Arranges `nodes`, added to a graph that is laid out already, left of the first other
+    /// node they are part of. The other nodes stay where they are.
+    pub(crate) fn place_new(&mut self, nodes: &[NodeId]) {
+        let new: HashSet<NodeId> = nodes.iter().copied().collect();
+        // New nodes by the node they end up feeding
+        let mut groups: Vec<(NodeId, Vec<NodeId>)> = Vec::new();
+        {
+            let graph = &self.state.graph;
+            let consumer = |id: NodeId| {
+                let outputs: Vec<_> = graph[id].output_ids().collect();
+                graph
+                    .connections
+                    .iter()
+                    .find(|(_, output_id)| outputs.contains(output_id))
+                    .map(|(input_id, _)| graph.get_input(input_id).node)
+            };
+            for id in nodes.iter().filter(|id| graph.nodes.contains_key(**id)) {
+                let mut anchor = *id;
+                // A cycle of new nodes can't be followed further than there are nodes
+                for _ in 0..=new.len() {
+                    match consumer(anchor) {
+                        Some(next) if new.contains(&anchor) => anchor = next,
+                        _ => break,
+                    }
+                }
+                match groups.iter_mut().find(|(a, _)| *a == anchor) {
+                    Some((_, group)) => group.push(*id),
+                    None => groups.push((anchor, vec![*id])),
+                }
+            }
+        }
+        for (anchor, group) in groups {
+            self.arrange(&group);
+            let anchor_pos = match self.state.node_positions.get(anchor) {
+                Some(pos) if !new.contains(&anchor) => *pos,
+                _ => continue,
+            };
+            let bounds = group
+                .iter()
+                .filter_map(|id| {
+                    let pos = *self.state.node_positions.get(*id)?;
+                    Some(Rect::from_min_size(pos, self.node_size(*id)))
+                })
+                .reduce(|a, b| a.union(b));
+            let bounds = match bounds {
+                Some(bounds) => bounds,
+                None => continue,
+            };
+            let mut offset = anchor_pos - Vec2::new(COLUMN_GAP, 0.) - bounds.right_top();
+            // Moved down below the nodes that are already there
+            let others: Vec<Rect> = self
+                .state
+                .node_order
+                .iter()
+                .filter(|id| !group.contains(id))
+                .filter_map(|id| {
+                    let pos = *self.state.node_positions.get(*id)?;
+                    Some(Rect::from_min_size(pos, self.node_size(*id)))
+                })
+                .collect();
+            while let Some(bottom) = others
+                .iter()
+                .filter(|other| other.intersects(bounds.translate(offset)))
+                .map(|other| other.bottom())
+                .reduce(f32::max)
+            {
+                offset.y = bottom + ROW_GAP - bounds.top();
+            }
+            for id in &group {
+                if let Some(pos) = self.state.node_positions.get_mut(*id) {
+                    *pos += offset;
+                }
+            }
+        }
+    }
+
+    /// Size of the node when it was last drawn, estimated from its inputs if it never was.
+    fn node_size(&self, node_id: NodeId) -> Vec2 {
+        if let Some(size) = self.user_state.node_sizes.get(&node_id) {
+            return *size;
+        }
+        let graph = &self.state.graph;
+        let node = &graph[node_id];
+        let rows: usize = node
+            .inputs
+            .iter()
+            .map(|(_, input_id)| graph.get_input(*input_id))
+            .filter(|input| input.shown_inline)
+            .map(|input| {
+                if graph.connection(input.id).is_some() {
+                    return 1;
+                }
+                match &input.value {
+                    ValueType::Block(state) => 1 + state.properties.len(),
+                    ValueType::RawJson(text) => 1 + text.lines().count(),
+                    _ => 1,
+                }
+            })
+            .sum::<usize>()
+            + node.outputs.len()
+            // the button to set the node active and the unknown fields
+            + 1
+            + usize::from(!node.user_data.unknown_fields.is_empty());
+        // title and margins
+        Vec2::new(250., 40. + 22. * rows as f32)
+    }
+}
+
+/// Vertical placement, depth first so that every node is next to the first node it's connected
+/// to.
+struct Placer<'a> {
+    children: &'a HashMap<NodeId, Vec<NodeId>>,
+    sizes: &'a HashMap<NodeId, Vec2>,
+    layers: &'a HashMap<NodeId, usize>,
+    /// Top of the free space of every layer
+    next_y: Vec<f32>,
+    y: HashMap<NodeId, f32>,
+}
+
+impl Placer<'_> {
+    fn place(&mut self, id: NodeId) {
+        if self.y.contains_key(&id) {
+            return;
+        }
+        let layer = self.layers[&id];
+        let top = self.next_y[layer];
+        // Placeholder so that cycles end here
+        self.y.insert(id, top);
+        // What the node is connected to doesn't go above it
+        for next_y in &mut self.next_y[layer + 1..] {
+            *next_y = next_y.max(top);
+        }
+        let mut first_child = None;
+        for child in &self.children[&id] {
+            if !self.y.contains_key(child) {
+                self.place(*child);
+                first_child = first_child.or_else(|| self.y.get(child).copied());
+            }
+        }
+        let y = first_child.unwrap_or(top).max(self.next_y[layer]);
+        self.y.insert(id, y);
+        self.next_y[layer] = y + self.sizes[&id].y + ROW_GAP;
+    }
+}
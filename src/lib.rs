@@ -0,0 +1,21 @@
+pub mod app;
+pub mod cli;
+pub mod clipboard;
+pub mod dependencies;
+pub mod diagnostics;
+pub mod errors;
+pub mod evaluator;
+pub mod history;
+pub mod json_view;
+pub mod layout;
+pub mod nodes;
+pub mod pack;
+pub mod preview;
+pub mod references;
+pub mod serializer;
+pub mod sidecar;
+pub mod ui;
+pub mod usages;
+pub mod vanilla;
+pub mod version;
+pub mod window;
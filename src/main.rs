@@ -1,19 +1,17 @@
 // #![feature(once_cell)] 
-use app::App;
+use datapack_creato_rs::{app::App, cli};
 use std::path::PathBuf;
+use std::process::ExitCode;
 use simple_logger::SimpleLogger;
-mod app;
-mod nodes;
-mod window;
-mod ui;
-mod serializer;
-mod errors;
 
-fn main() {
-    // TODO: Rather than just logging error messages, display them in the UI (global queue of messages?)
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(exit_code) = cli::run(&args) {
+        return exit_code;
+    }
     SimpleLogger::new().init().unwrap();
     let native_options = eframe::NativeOptions::default();
-    let path = std::env::args().nth(1).map(|s| PathBuf::from(s));
+    let path = args.first().map(PathBuf::from);
     eframe::run_native(
         "Datapack creato(rs)", 
         native_options, 
@@ -23,4 +21,5 @@ fn main() {
             }
         )
     );
+    ExitCode::SUCCESS
 }
@@ -1,15 +1,170 @@
-use lazy_static::lazy_static;
+//! Block states and the blocks of the game they can name.
+
+use std::collections::{BTreeMap, HashMap};
 
 use eframe::epaint::Color32;
+use json::JsonValue;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::AppError;
+
+/// Blocks of vanilla 1.20 with their properties and map colors. Every property lists the value
+/// of the default state first.
+const BLOCKS_JSON: &str = include_str!("blocks.json");
 
-#[derive(Clone)]
 pub struct Block {
-    pub id: &'static str,
-    pub color: Color32
+    pub id: String,
+    /// Color of the block on maps, transparent for blocks maps don't show
+    pub color: Color32,
+    /// Names of the properties and their allowed values, the default one first
+    pub properties: Vec<(String, Vec<String>)>,
+}
+
+pub struct BlockRegistry {
+    blocks: Vec<Block>,
+    by_id: HashMap<String, usize>,
 }
-//TODO: should be a map???
+
 lazy_static! {
-    pub static ref BLOCK_LIST: Vec<Block> = {
-        vec![Block{id:"minecraft:dirt", color: Color32::BROWN }]
-    };
+    pub static ref BLOCKS: BlockRegistry =
+        BlockRegistry::parse(BLOCKS_JSON).expect("bundled block list is valid");
+}
+
+impl BlockRegistry {
+    fn parse(text: &str) -> Result<Self, json::Error> {
+        let json = json::parse(text)?;
+        let blocks: Vec<Block> = json
+            .entries()
+            .map(|(id, block)| Block {
+                id: id.to_string(),
+                color: block["color"]
+                    .as_str()
+                    .and_then(|hex| u32::from_str_radix(hex.trim_start_matches('#'), 16).ok())
+                    .map_or(Color32::TRANSPARENT, |rgb| {
+                        let [_, r, g, b] = rgb.to_be_bytes();
+                        Color32::from_rgb(r, g, b)
+                    }),
+                properties: block["properties"]
+                    .entries()
+                    .map(|(name, values)| {
+                        let values = values.members().filter_map(|v| v.as_str());
+                        (name.to_string(), values.map(str::to_string).collect())
+                    })
+                    .collect(),
+            })
+            .collect();
+        let by_id = blocks
+            .iter()
+            .enumerate()
+            .map(|(i, block)| (block.id.clone(), i))
+            .collect();
+        Ok(Self { blocks, by_id })
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Block> {
+        self.by_id.get(id).map(|i| &self.blocks[*i])
+    }
+
+    /// Blocks whose id contains every word of `query`, in alphabetical order.
+    pub fn search<'a>(&'a self, query: &str) -> impl Iterator<Item = &'a Block> {
+        let query = query.to_lowercase();
+        self.blocks
+            .iter()
+            .filter(move |block| query.split_whitespace().all(|word| block.id.contains(word)))
+    }
+}
+
+/// A block with the values of its properties, `{"Name": ..., "Properties": {...}}` in JSON.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct BlockState {
+    pub name: String,
+    pub properties: BTreeMap<String, String>,
+}
+
+impl Default for BlockState {
+    fn default() -> Self {
+        Self::new("minecraft:stone")
+    }
+}
+
+impl BlockState {
+    /// Default state of the block, with every property it has.
+    pub fn new(name: &str) -> Self {
+        let properties = BLOCKS
+            .get(name)
+            .map(|block| {
+                block
+                    .properties
+                    .iter()
+                    .filter_map(|(name, values)| Some((name.clone(), values.first()?.clone())))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            name: name.to_string(),
+            properties,
+        }
+    }
+
+    /// Reads a block state object, or just the name of a block. Blocks and properties the
+    /// registry doesn't know are kept, they may come from mods or other versions.
+    pub fn from_json(value: &JsonValue) -> Result<Self, AppError> {
+        let wrong_type = || AppError::JsonError(json::Error::wrong_type("block state"));
+        let name = if value.is_object() {
+            value["Name"].as_str()
+        } else {
+            value.as_str()
+        }
+        .ok_or_else(wrong_type)?;
+        let name = match name.split_once(':') {
+            Some(_) => name.to_string(),
+            None => format!("minecraft:{}", name),
+        };
+        let properties = value["Properties"]
+            .entries()
+            .map(|(key, value)| Some((key.to_string(), value.as_str()?.to_string())))
+            .collect::<Option<_>>()
+            .ok_or_else(wrong_type)?;
+        Ok(Self { name, properties })
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        let mut json = json::object! { "Name": self.name.as_str() };
+        if !self.properties.is_empty() {
+            let mut properties = JsonValue::new_object();
+            for (key, value) in &self.properties {
+                properties[key.as_str()] = value.as_str().into();
+            }
+            json["Properties"] = properties;
+        }
+        json
+    }
+
+    pub fn block(&self) -> Option<&'static Block> {
+        BLOCKS.get(&self.name)
+    }
+
+    pub fn color(&self) -> Color32 {
+        self.block()
+            .map_or(Color32::TRANSPARENT, |block| block.color)
+    }
+
+    /// Properties set to a value the block doesn't have, or that the block doesn't have at all.
+    pub fn invalid_properties(&self) -> Vec<&str> {
+        let block = match self.block() {
+            Some(block) => block,
+            None => return Vec::new(),
+        };
+        self.properties
+            .iter()
+            .filter(|(key, value)| {
+                !block
+                    .properties
+                    .iter()
+                    .any(|(name, values)| name == *key && values.contains(value))
+            })
+            .map(|(key, _)| key.as_str())
+            .collect()
+    }
 }
@@ -1,12 +1,16 @@
 use eframe::egui::{self, Checkbox, ComboBox, DragValue, Ui};
 use eframe::epaint::Color32;
 use egui_node_graph::{DataTypeTrait, NodeId, WidgetValueTrait, InputId};
+use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 
+use crate::app::Identifier;
 use crate::ui::ComboBoxEnum;
+use crate::version::McVersion;
 use crate::window::WindowType;
 
-use super::blocks::BLOCK_LIST;
+use super::blocks::{BlockState, BLOCKS};
+use super::inner_data_types::biome::{GrassColorModifier, Precipitation, TemperatureModifier};
 use super::inner_data_types::surface_rule_condition::{VerticalAnchor, SurfaceType};
 use super::inner_data_types::{density_function, surface_rule_condition};
 use super::inner_data_types::{
@@ -16,7 +20,7 @@ use super::inner_data_types::{
 use super::node_types::NodeTemplate;
 use super::{GraphState, GraphType, NodeData, Response};
 
-#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum DataType {
     Value,
     Integer,
@@ -29,32 +33,79 @@ pub enum DataType {
     WeirdScaledSampleRarityValueMapper,
     VerticalAnchor,
     SurfaceType,
+    /// Climate parameter, a single number or a `[min, max]` pair in JSON
+    Range,
+    TemperatureModifier,
+    GrassColorModifier,
+    Precipitation,
+    Color,
+    /// Nested JSON object made of the inputs that follow it on the node, see `ValueType::Object`
+    Object,
+    /// Same as `Object` but written as an array, labels of the inputs are only shown in the editor
+    Tuple,
     List(ComplexDataType),
+    /// List written as a JSON object, keyed by the first input of each connected node
+    Map(ComplexDataType),
     Single(ComplexDataType),
+    /// JSON text the editor doesn't understand, kept as it was read
+    RawJson,
 }
-#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum ComplexDataType {
     Noise,
     DensityFunction,
     SurfaceRule,
     SurfaceRuleCondition,
     Biome,
+    Spline,
+    SplinePoint,
+    ParameterPoint,
+    MoodSound,
+    AdditionsSound,
+    Music,
+    Particle,
+    /// Only written by name, there are no placed feature windows
+    PlacedFeature,
+    /// Only written by name, there are no configured carver windows
+    ConfiguredCarver,
+    Spawner,
+    SpawnCost,
+}
+
+impl ComplexDataType {
+    /// Returns the `WindowType` a JSON string in place of this type refers to, if it can be one.
+    pub fn reference_window_type(&self) -> Option<WindowType> {
+        match self {
+            ComplexDataType::Noise => Some(WindowType::Noise),
+            ComplexDataType::DensityFunction => Some(WindowType::DensityFunction),
+            ComplexDataType::Biome => Some(WindowType::Biome),
+            _ => None,
+        }
+    }
+
+    /// Lists of it are holder sets, which the game reads from a single id as well.
+    pub fn in_holder_sets(&self) -> bool {
+        matches!(
+            self,
+            ComplexDataType::ConfiguredCarver | ComplexDataType::PlacedFeature
+        )
+    }
 }
 
 impl DataType {
     #[allow(non_snake_case)]
     /// Returns
     /// - 'NodeTemplate' if DataType can be represented by a node and is an object in JSON
-    /// - 'None' if DataType is never represented by a node but is an object in JSON
-    /// - is unimplemented for DataTypes that are never objects in JSON
+    /// - 'None' if DataType is never represented by a node, objects of it are read as values
     pub fn defualt_NodeTemplate(&self) -> Option<NodeTemplate> {
         match self {
             DataType::Value => Some(NodeTemplate::ConstantValue),
-            DataType::Block => Some(NodeTemplate::ConstantBlock),
-            DataType::ValuesArray => unimplemented!(),
+            // Block states are objects, but they are read into the value
+            DataType::Block => None,
+            DataType::ValuesArray => None,
             DataType::Reference(x) => Some(NodeTemplate::Reference(*x)),
-            DataType::ValueTypeSwitcher => unimplemented!(),
-            DataType::List(_x) => unimplemented!(),
+            DataType::ValueTypeSwitcher => None,
+            DataType::List(_x) => None,
             DataType::Single(x) => match x {
                 ComplexDataType::Noise => Some(NodeTemplate::Noise),
                 ComplexDataType::DensityFunction => {
@@ -67,30 +118,60 @@ impl DataType {
                     Some(NodeTemplate::SurfaceRuleCondition(SurfaceRuleConditionType::YAbove))
                 }
                 ComplexDataType::Biome => Some(NodeTemplate::Reference(WindowType::Biome)),
+                ComplexDataType::Spline => Some(NodeTemplate::Spline),
+                ComplexDataType::SplinePoint => Some(NodeTemplate::SplinePoint),
+                ComplexDataType::ParameterPoint => Some(NodeTemplate::ParameterPoint),
+                ComplexDataType::MoodSound => Some(NodeTemplate::MoodSound),
+                ComplexDataType::AdditionsSound => Some(NodeTemplate::AdditionsSound),
+                ComplexDataType::Music => Some(NodeTemplate::Music),
+                ComplexDataType::Particle => Some(NodeTemplate::Particle),
+                ComplexDataType::PlacedFeature | ComplexDataType::ConfiguredCarver => None,
+                ComplexDataType::Spawner => Some(NodeTemplate::Spawner),
+                ComplexDataType::SpawnCost => Some(NodeTemplate::SpawnCost),
             },
-            DataType::WeirdScaledSampleRarityValueMapper => unimplemented!(),
-            DataType::Integer => unimplemented!(),
-            DataType::DullReference => unimplemented!(),
-            DataType::VerticalAnchor => None,
-            DataType::Bool => unimplemented!(),
-            DataType::SurfaceType => unimplemented!(),
+            DataType::WeirdScaledSampleRarityValueMapper
+            | DataType::Integer
+            | DataType::DullReference
+            | DataType::VerticalAnchor
+            | DataType::Bool
+            | DataType::SurfaceType
+            | DataType::Range
+            | DataType::TemperatureModifier
+            | DataType::GrassColorModifier
+            | DataType::Precipitation
+            | DataType::Color => None,
+            DataType::Object | DataType::Tuple | DataType::Map(_) | DataType::RawJson => None,
         }
     }
     #[allow(non_snake_case)]
     pub fn default_ValueType(&self) -> ValueType {
         match self {
             DataType::Value => ValueType::Value(0.),
-            DataType::Block => ValueType::Block(0),
+            DataType::Block => ValueType::Block(BlockState::default()),
             DataType::ValuesArray => ValueType::ValuesArray(vec![0.]),
             DataType::Reference(x) => ValueType::Reference(*x, "".to_string()),
-            DataType::ValueTypeSwitcher => unimplemented!(),
-            DataType::List(_x) => ValueType::List(1),
+            DataType::ValueTypeSwitcher => ValueType::InnerTypeSwitch(
+                SwitchableInnerValueType::DensityFunction(DensityFunctionType::Constant),
+            ),
+            DataType::List(_x) | DataType::Map(_x) => ValueType::List(1),
             DataType::Single(x) => match x {
                 ComplexDataType::Noise => ValueType::Noise,
-                ComplexDataType::DensityFunction => ValueType::DensityFunction,
+                ComplexDataType::DensityFunction => ValueType::Value(0.),
                 ComplexDataType::SurfaceRule => ValueType::SurfaceRule,
                 ComplexDataType::SurfaceRuleCondition => ValueType::SurfaceRuleCondition,
-                ComplexDataType::Biome => todo!(),
+                ComplexDataType::Biome => ValueType::Biome,
+                ComplexDataType::Spline => ValueType::Value(0.),
+                ComplexDataType::SplinePoint => ValueType::SplinePoint,
+                ComplexDataType::ParameterPoint => ValueType::ParameterPoint,
+                ComplexDataType::MoodSound => ValueType::MoodSound,
+                ComplexDataType::AdditionsSound => ValueType::AdditionsSound,
+                ComplexDataType::Music => ValueType::Music,
+                ComplexDataType::Particle => ValueType::Particle,
+                ComplexDataType::PlacedFeature | ComplexDataType::ConfiguredCarver => {
+                    ValueType::DullReference(String::new())
+                }
+                ComplexDataType::Spawner => ValueType::Spawner,
+                ComplexDataType::SpawnCost => ValueType::SpawnCost,
             },
             DataType::WeirdScaledSampleRarityValueMapper => {
                 ValueType::WeirdScaledSampleRarityValueMapper(
@@ -104,6 +185,16 @@ impl DataType {
             }
             DataType::Bool => ValueType::Bool(false),
             DataType::SurfaceType => ValueType::SurfaceType(surface_rule_condition::SurfaceType::Ceiling),
+            DataType::Range => ValueType::Range(0., 0.),
+            DataType::TemperatureModifier => {
+                ValueType::TemperatureModifier(TemperatureModifier::None)
+            }
+            DataType::GrassColorModifier => ValueType::GrassColorModifier(GrassColorModifier::None),
+            DataType::Precipitation => ValueType::Precipitation(Precipitation::Rain),
+            DataType::Color => ValueType::Color(None),
+            DataType::Object => ValueType::Object(0),
+            DataType::Tuple => ValueType::Tuple(0),
+            DataType::RawJson => ValueType::RawJson("{}".to_string()),
         }
     }
 }
@@ -119,13 +210,23 @@ impl DataTypeTrait<GraphState> for DataType {
             DataType::Reference(_) => Color32::BLUE,
             DataType::Single(ComplexDataType::SurfaceRule) => Color32::RED,
             DataType::Single(ComplexDataType::SurfaceRuleCondition) => Color32::LIGHT_RED,
-            DataType::List(_x) => unimplemented!(),
+            DataType::Single(ComplexDataType::Spline) => Color32::LIGHT_BLUE,
+            DataType::Single(ComplexDataType::SplinePoint) => Color32::LIGHT_GREEN,
+            DataType::Single(ComplexDataType::ParameterPoint) => Color32::KHAKI,
+            DataType::Single(ComplexDataType::MoodSound) => Color32::from_rgb(120, 80, 200),
+            DataType::Single(ComplexDataType::AdditionsSound) => Color32::from_rgb(160, 100, 220),
+            DataType::Single(ComplexDataType::Music) => Color32::from_rgb(200, 120, 240),
+            DataType::Single(ComplexDataType::Particle) => Color32::from_rgb(240, 160, 60),
+            DataType::Single(ComplexDataType::Spawner) => Color32::from_rgb(60, 180, 120),
+            DataType::Single(ComplexDataType::SpawnCost) => Color32::from_rgb(60, 140, 180),
+            DataType::List(_x) | DataType::Map(_x) => Color32::DARK_GRAY,
             DataType::DullReference => Color32::BROWN,
-            _ => unimplemented!(),
+            // Only ever constants, their ports aren't shown
+            _ => Color32::GRAY,
         }
     }
 
-    fn name(&self) -> std::borrow::Cow<str> {
+    fn name(&self) -> std::borrow::Cow<'_, str> {
         match self {
             DataType::Value => Cow::Borrowed("value"),
             DataType::Block => Cow::Borrowed("block"),
@@ -138,7 +239,21 @@ impl DataTypeTrait<GraphState> for DataType {
                 Cow::Borrowed("surface rule condition")
             }
             DataType::Single(ComplexDataType::Biome) => Cow::Borrowed("biome"),
+            DataType::Single(ComplexDataType::Spline) => Cow::Borrowed("spline"),
+            DataType::Single(ComplexDataType::SplinePoint) => Cow::Borrowed("spline point"),
+            DataType::Single(ComplexDataType::ParameterPoint) => Cow::Borrowed("parameter point"),
+            DataType::Single(ComplexDataType::MoodSound) => Cow::Borrowed("mood sound"),
+            DataType::Single(ComplexDataType::AdditionsSound) => Cow::Borrowed("additions sound"),
+            DataType::Single(ComplexDataType::Music) => Cow::Borrowed("music"),
+            DataType::Single(ComplexDataType::Particle) => Cow::Borrowed("particle"),
+            DataType::Single(ComplexDataType::PlacedFeature) => Cow::Borrowed("placed feature"),
+            DataType::Single(ComplexDataType::ConfiguredCarver) => {
+                Cow::Borrowed("configured carver")
+            }
+            DataType::Single(ComplexDataType::Spawner) => Cow::Borrowed("spawner"),
+            DataType::Single(ComplexDataType::SpawnCost) => Cow::Borrowed("spawn cost"),
             DataType::List(x) => Cow::Owned(format!("list ({})", DataType::Single(*x).name())),
+            DataType::Map(x) => Cow::Owned(format!("map ({})", DataType::Single(*x).name())),
             DataType::ValueTypeSwitcher => Cow::Borrowed("value type switcher"),
             DataType::WeirdScaledSampleRarityValueMapper => Cow::Borrowed("rarity value mapper"),
             DataType::Integer => Cow::Borrowed("integer value"),
@@ -146,18 +261,25 @@ impl DataTypeTrait<GraphState> for DataType {
             DataType::VerticalAnchor => Cow::Borrowed("vertical anchor"),
             DataType::Bool => Cow::Borrowed("boolean"),
             DataType::SurfaceType => Cow::Borrowed("surface type"),
+            DataType::Range => Cow::Borrowed("range"),
+            DataType::TemperatureModifier => Cow::Borrowed("temperature modifier"),
+            DataType::GrassColorModifier => Cow::Borrowed("grass color modifier"),
+            DataType::Precipitation => Cow::Borrowed("precipitation"),
+            DataType::Color => Cow::Borrowed("color"),
+            DataType::Object => Cow::Borrowed("object"),
+            DataType::Tuple => Cow::Borrowed("tuple"),
+            DataType::RawJson => Cow::Borrowed("raw JSON"),
         }
     }
 }
-type BlockId = usize;
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub enum ValueType {
     // TODO: allow specifing min-max value
-    Value(f32),
-    ValuesArray(Vec<f32>),
+    Value(f64),
+    ValuesArray(Vec<f64>),
     Bool(bool),
     Integer(i32),
-    Block(BlockId),
+    Block(BlockState),
     Noise,
     Biome,
     DensityFunction,
@@ -165,13 +287,34 @@ pub enum ValueType {
     DullReference(String),
     SurfaceRule,
     SurfaceRuleCondition,
+    SplinePoint,
+    ParameterPoint,
+    MoodSound,
+    AdditionsSound,
+    Music,
+    Particle,
+    Spawner,
+    SpawnCost,
     WeirdScaledSampleRarityValueMapper(density_function::WeirdScaledSampleRarityValueMapper),
     VerticalAnchor(surface_rule_condition::VerticalAnchor, i32),
     SurfaceType(surface_rule_condition::SurfaceType),
+    /// `min` and `max`, equal when the JSON holds a single number
+    Range(f64, f64),
+    TemperatureModifier(TemperatureModifier),
+    GrassColorModifier(GrassColorModifier),
+    Precipitation(Precipitation),
+    /// `None` leaves an optional color out of the JSON
+    Color(Option<Color32>),
+    /// Number of inputs after this one that are written inside of it, a list counts as one
+    Object(usize),
+    /// Same as `Object`
+    Tuple(usize),
+    /// Number of entries of a list or map
     List(i32),
     InnerTypeSwitch(SwitchableInnerValueType),
+    RawJson(String),
 }
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum SwitchableInnerValueType {
     SurfaceRule(SurfaceRuleType),
     SurfaceRuleCondition(SurfaceRuleConditionType),
@@ -181,7 +324,7 @@ pub enum SwitchableInnerValueType {
 impl SwitchableInnerValueType {
     /// Returns exact `NodeTemplate` for this type.
     #[allow(non_snake_case)]
-    pub fn to_NodeTemplate(&self) -> NodeTemplate {
+    pub fn to_NodeTemplate(self) -> NodeTemplate {
         match self {
             SwitchableInnerValueType::SurfaceRule(x) => x.to_NodeTemplate(),
             SwitchableInnerValueType::SurfaceRuleCondition(x) => x.to_NodeTemplate(),
@@ -206,7 +349,7 @@ impl WidgetValueTrait for ValueType {
         param_name: &str,
         node_id: NodeId,
         ui: &mut egui::Ui,
-        _user_state: &mut Self::UserState,
+        user_state: &mut Self::UserState,
         _node_data: &Self::NodeData,
     ) -> Vec<Self::Response> {
         let mut ret = Vec::new();
@@ -223,15 +366,17 @@ impl WidgetValueTrait for ValueType {
                     ui.add(DragValue::new(x));
                 });
             }
-            ValueType::Bool(x) => {
-                ui.add(Checkbox::new(x, param_name));
-            }
-            ValueType::Block(x) => {
+            ValueType::Range(min, max) => {
                 ui.horizontal(|ui| {
-                    ComboBox::from_label(param_name)
-                        .show_index(ui, x, BLOCK_LIST.len(), |i| BLOCK_LIST[i].id.to_string());
+                    ui.label(param_name);
+                    ui.add(DragValue::new(min).speed(0.01));
+                    ui.add(DragValue::new(max).speed(0.01));
                 });
             }
+            ValueType::Bool(x) => {
+                ui.add(Checkbox::new(x, param_name));
+            }
+            ValueType::Block(state) => block_state_widget(ui, param_name, state),
             ValueType::ValuesArray(arr) => {
                 ui.vertical(|ui| {
                     for (i, val) in arr.iter_mut().enumerate() {
@@ -253,8 +398,45 @@ impl WidgetValueTrait for ValueType {
             ValueType::Reference(window_type, id) => {
                 ui.horizontal(|ui| {
                     ui.label(param_name);
-                    //TODO: add autocompletion here somehow
-                    ui.text_edit_singleline(id);
+                    let resolved = user_state.references.resolves(*window_type, id);
+                    let mut text_edit = egui::TextEdit::singleline(id);
+                    if !resolved {
+                        text_edit = text_edit.text_color(Color32::RED);
+                    }
+                    let response = ui.add(text_edit).on_hover_text(if resolved {
+                        "found"
+                    } else {
+                        "not found in the project or vanilla"
+                    });
+                    let popup_id = response.id.with("suggestions");
+                    if response.gained_focus() || response.changed() {
+                        ui.memory().open_popup(popup_id);
+                    }
+                    if ui.memory().is_popup_open(popup_id) {
+                        let suggestions = user_state.references.suggestions(*window_type, id);
+                        if suggestions.is_empty() || suggestions == [id.as_str()] {
+                            ui.memory().close_popup();
+                        }
+                        egui::popup_below_widget(ui, popup_id, &response, |ui| {
+                            egui::ScrollArea::vertical().max_height(200.).show(ui, |ui| {
+                                for suggestion in suggestions {
+                                    if ui.selectable_label(false, &suggestion).clicked() {
+                                        *id = suggestion;
+                                        ui.memory().close_popup();
+                                    }
+                                }
+                            });
+                        });
+                    }
+                    let target = Identifier::from_string(id.clone(), *window_type)
+                        .filter(|target| user_state.references.in_project(target));
+                    if ui
+                        .add_enabled(target.is_some(), egui::Button::new("→").small())
+                        .on_hover_text("go to definition")
+                        .clicked()
+                    {
+                        ret.extend(target.map(Response::GoToDefinition));
+                    }
                 });
             }
             ValueType::DullReference(s) => {
@@ -263,16 +445,24 @@ impl WidgetValueTrait for ValueType {
                     ui.text_edit_singleline(s);
                 });
             }
+            ValueType::RawJson(s) => {
+                let valid = json::parse(s).is_ok();
+                let mut text_edit = egui::TextEdit::multiline(s).code_editor().desired_rows(3);
+                if !valid {
+                    text_edit = text_edit.text_color(Color32::RED);
+                }
+                ui.add(text_edit);
+            }
             ValueType::List(x) => {
                 ui.horizontal(|ui| {
                     ui.label(param_name);
                     if *x > 0 {
                         if ui.small_button("+").clicked() {
-                            ret.push(Response::IncreaseInputs(node_id));
+                            ret.push(Response::IncreaseInputs(node_id, param_name.into()));
                             *x += 1;
                         }
                         if *x > 1 && ui.small_button("-").clicked() {
-                            ret.push(Response::DecreaseInputs(node_id));
+                            ret.push(Response::DecreaseInputs(node_id, param_name.into()));
                             *x -= 1;
                         }
                     }
@@ -280,13 +470,13 @@ impl WidgetValueTrait for ValueType {
             }
             ValueType::InnerTypeSwitch(x) => match x {
                 SwitchableInnerValueType::SurfaceRule(x) => {
-                    switcher_widget(x, ui, param_name, &mut ret, node_id)
+                    switcher_widget(x, ui, param_name, &mut ret, node_id, user_state.version)
                 }
                 SwitchableInnerValueType::SurfaceRuleCondition(x) => {
-                    switcher_widget(x, ui, param_name, &mut ret, node_id)
+                    switcher_widget(x, ui, param_name, &mut ret, node_id, user_state.version)
                 }
                 SwitchableInnerValueType::DensityFunction(x) => {
-                    switcher_widget(x, ui, param_name, &mut ret, node_id)
+                    switcher_widget(x, ui, param_name, &mut ret, node_id, user_state.version)
                 }
             },
             // TODO: Refactor into common type for enumerations
@@ -308,21 +498,52 @@ impl WidgetValueTrait for ValueType {
                         })
                 });
             }
+            ValueType::TemperatureModifier(x) => {
+                ui.horizontal(|ui| {
+                    ComboBox::from_label(param_name)
+                        .selected_text(x.as_ref())
+                        .show_ui(ui, |ui| TemperatureModifier::show_ui(ui, x))
+                });
+            }
+            ValueType::GrassColorModifier(x) => {
+                ui.horizontal(|ui| {
+                    ComboBox::from_label(param_name)
+                        .selected_text(x.as_ref())
+                        .show_ui(ui, |ui| GrassColorModifier::show_ui(ui, x))
+                });
+            }
+            ValueType::Precipitation(x) => {
+                ui.horizontal(|ui| {
+                    ComboBox::from_label(param_name)
+                        .selected_text(x.as_ref())
+                        .show_ui(ui, |ui| Precipitation::show_ui(ui, x))
+                });
+            }
+            ValueType::Color(x) => {
+                ui.horizontal(|ui| {
+                    ui.label(param_name);
+                    match x {
+                        Some(color) => {
+                            ui.color_edit_button_srgba(color);
+                            if ui.small_button("x").clicked() {
+                                *x = None;
+                            }
+                        }
+                        None => {
+                            if ui.small_button("+").clicked() {
+                                *x = Some(Color32::WHITE);
+                            }
+                        }
+                    }
+                });
+            }
             ValueType::VerticalAnchor(x, i) => {
-                let y = x.clone();
                 ui.horizontal(|ui| {
                     ComboBox::from_label(param_name)
                         .selected_text(x.as_ref())
                         .show_ui(ui, |ui| VerticalAnchor::show_ui(ui, x));
                     ui.add(DragValue::new(i));
                 });
-                if y != *x {
-                    ret.push(Response::ChangeInputLabel(
-                        node_id,
-                        y.as_ref().into(),
-                        x.as_ref().into(),
-                    ))
-                }
             }
             _ => {
                 ui.horizontal(|ui| {
@@ -333,19 +554,23 @@ impl WidgetValueTrait for ValueType {
         ret
     }
 }
+/// Lists the types the pack's version has.
 fn switcher_widget<T: InnerDataType>(
     x: &mut T,
     ui: &mut Ui,
     param_name: &str,
     ret: &mut Vec<Response>,
     node_id: NodeId,
+    version: McVersion,
 ) {
     let y = x.clone();
     ui.horizontal(|ui| {
         ComboBox::from_label(param_name)
             .selected_text(x.as_ref())
             .show_ui(ui, |ui| {
-                T::show_ui(ui, x);
+                for value in version.types::<T>() {
+                    ui.selectable_value(x, value.clone(), value.as_ref());
+                }
             });
     });
     if *x != y {
@@ -354,31 +579,129 @@ fn switcher_widget<T: InnerDataType>(
     }
 }
 
-/// Decreases inputs by one by removing the last input.
-pub fn decrease_node_list_length(graph: &mut GraphType, node_id: NodeId) -> Option<InputId> {
-    if let Some(in_id) = graph.nodes.get(node_id).unwrap().input_ids().last() {
-        graph.connections.remove(in_id);
-        graph
-            .nodes
-            .get_mut(node_id)
-            .unwrap()
-            .inputs
-            .retain(|(_, id)| *id != in_id);
-        Some(in_id)
-    } else {
-        None
+/// Name of the block with suggestions from the registry, and a combo box for each property.
+fn block_state_widget(ui: &mut Ui, param_name: &str, state: &mut BlockState) {
+    ui.horizontal(|ui| {
+        ui.label(param_name);
+        let (rect, _) = ui.allocate_exact_size(egui::vec2(10., 10.), egui::Sense::hover());
+        ui.painter().rect_filled(rect, 2., state.color());
+        let known = state.block().is_some();
+        let mut name = state.name.clone();
+        let mut text_edit = egui::TextEdit::singleline(&mut name);
+        if !known {
+            text_edit = text_edit.text_color(Color32::RED);
+        }
+        let response = ui.add(text_edit).on_hover_text(if known {
+            "found"
+        } else {
+            "not a block of vanilla"
+        });
+        if response.changed() {
+            *state = BlockState::new(&name);
+        }
+        let popup_id = response.id.with("suggestions");
+        if response.gained_focus() || response.changed() {
+            ui.memory().open_popup(popup_id);
+        }
+        if ui.memory().is_popup_open(popup_id) {
+            let suggestions: Vec<&str> = BLOCKS
+                .search(&state.name)
+                .take(100)
+                .map(|block| block.id.as_str())
+                .collect();
+            if suggestions.is_empty() || suggestions == [state.name.as_str()] {
+                ui.memory().close_popup();
+            }
+            egui::popup_below_widget(ui, popup_id, &response, |ui| {
+                egui::ScrollArea::vertical().max_height(200.).show(ui, |ui| {
+                    for suggestion in suggestions {
+                        if ui.selectable_label(false, suggestion).clicked() {
+                            *state = BlockState::new(suggestion);
+                            ui.memory().close_popup();
+                        }
+                    }
+                });
+            });
+        }
+    });
+    let invalid = state.invalid_properties();
+    if !invalid.is_empty() {
+        ui.colored_label(
+            Color32::RED,
+            format!("not properties of this block: {}", invalid.join(", ")),
+        );
+    }
+    let block = match state.block() {
+        Some(block) => block,
+        None => return,
+    };
+    for (property, values) in &block.properties {
+        // Properties that aren't set show the block's default and are only written once picked
+        let selected = state
+            .properties
+            .get(property)
+            .or_else(|| values.first())
+            .cloned()
+            .unwrap_or_default();
+        ComboBox::from_label(property)
+            .selected_text(selected.as_str())
+            .show_ui(ui, |ui| {
+                for v in values {
+                    if ui.selectable_label(*v == selected, v).clicked() {
+                        state.properties.insert(property.clone(), v.clone());
+                    }
+                }
+            });
     }
 }
-/// Increses inputs by one by copying everything from the last input.
-pub fn increase_node_list_length(graph: &mut GraphType, node_id: NodeId) -> InputId {
-    let in_id = graph.nodes.get(node_id).unwrap().inputs.last().unwrap().1;
-    let input = graph.inputs.get(in_id).unwrap();
-    graph.add_input_param(
+
+/// Returns the position of the list input called `label` and the number of entries after it.
+pub fn list_entries(graph: &GraphType, node_id: NodeId, label: &str) -> Option<(usize, usize)> {
+    let inputs = &graph.nodes.get(node_id)?.inputs;
+    let position = inputs.iter().position(|(name, _)| name == label)?;
+    let len = inputs[position + 1..]
+        .iter()
+        .take_while(|(name, _)| name.is_empty())
+        .count();
+    Some((position, len))
+}
+/// Decreases inputs of list `label` by one by removing its last entry.
+pub fn decrease_node_list_length(
+    graph: &mut GraphType,
+    node_id: NodeId,
+    label: &str,
+) -> Option<InputId> {
+    let (position, len) = list_entries(graph, node_id, label)?;
+    if len == 0 {
+        return None;
+    }
+    let in_id = graph.nodes.get(node_id).unwrap().inputs[position + len].1;
+    graph.remove_input_param(in_id);
+    Some(in_id)
+}
+/// Increses inputs of list `label` by one by copying everything from its last entry.
+pub fn increase_node_list_length(
+    graph: &mut GraphType,
+    node_id: NodeId,
+    label: &str,
+) -> Option<InputId> {
+    let (position, len) = list_entries(graph, node_id, label)?;
+    if len == 0 {
+        return None;
+    }
+    let in_id = graph.nodes.get(node_id)?.inputs[position + len].1;
+    let input = graph.inputs.get(in_id)?;
+    let new_id = graph.add_input_param(
         node_id,
         "".to_string(),
         input.typ,
         input.value.clone(),
         input.kind,
         input.shown_inline,
-    )
+    );
+    // `add_input_param` always puts the input at the end of the node
+    let inputs = &mut graph.nodes.get_mut(node_id)?.inputs;
+    let new_input = inputs.pop()?;
+    inputs.insert(position + len + 1, new_input);
+    Some(new_id)
 }
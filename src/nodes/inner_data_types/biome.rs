@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+use strum::{AsRefStr, EnumCount, EnumIter, EnumString};
+
+use crate::ui::ComboBoxEnum;
+
+#[derive(
+    Copy, Clone, Debug, EnumIter, AsRefStr, EnumCount, PartialEq, EnumString, Serialize, Deserialize,
+)]
+#[strum(serialize_all = "snake_case")]
+pub enum TemperatureModifier {
+    None,
+    Frozen,
+}
+
+impl ComboBoxEnum for TemperatureModifier {}
+
+/// Kind of precipitation of biomes before 1.19.4, replaced by `has_precipitation` since.
+#[derive(
+    Copy, Clone, Debug, EnumIter, AsRefStr, EnumCount, PartialEq, EnumString, Serialize, Deserialize,
+)]
+#[strum(serialize_all = "snake_case")]
+pub enum Precipitation {
+    None,
+    Rain,
+    Snow,
+}
+
+impl ComboBoxEnum for Precipitation {}
+
+#[derive(
+    Copy, Clone, Debug, EnumIter, AsRefStr, EnumCount, PartialEq, EnumString, Serialize, Deserialize,
+)]
+#[strum(serialize_all = "snake_case")]
+pub enum GrassColorModifier {
+    None,
+    DarkForest,
+    Swamp,
+}
+
+impl ComboBoxEnum for GrassColorModifier {}
@@ -1,5 +1,6 @@
 use std::fmt::Display;
 
+use serde::{Deserialize, Serialize};
 use strum::{AsRefStr, EnumCount, EnumIter};
 use strum_macros::EnumString;
 
@@ -8,7 +9,7 @@ use crate::ui::ComboBoxEnum;
 
 use super::InnerDataType;
 
-#[derive(Copy, Clone, Debug, EnumIter, AsRefStr, EnumCount, PartialEq)]
+#[derive(Copy, Clone, Debug, EnumIter, AsRefStr, EnumCount, PartialEq, Serialize, Deserialize)]
 #[strum(serialize_all = "snake_case")]
 pub enum DensityFunctionType {
     // maker function
@@ -48,6 +49,8 @@ pub enum DensityFunctionType {
     Spline,
     Constant,
     YClampedGradient,
+    /// Highest y below `upper_bound` where `density` is positive, since 1.21.2
+    FindTopSurface,
 }
 impl Display for DensityFunctionType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -67,7 +70,9 @@ impl InnerDataType for DensityFunctionType {
     }
 }
 
-#[derive(Copy, Clone, Debug, EnumIter, AsRefStr, EnumCount, PartialEq, EnumString)]
+#[derive(
+    Copy, Clone, Debug, EnumIter, AsRefStr, EnumCount, PartialEq, EnumString, Serialize, Deserialize,
+)]
 pub enum WeirdScaledSampleRarityValueMapper {
     #[strum(serialize = "type_1")]
     Type1,
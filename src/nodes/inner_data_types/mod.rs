@@ -1,3 +1,4 @@
+pub mod biome;
 pub mod density_function;
 pub mod surface_rule;
 pub mod surface_rule_condition;
@@ -17,11 +18,6 @@ pub trait InnerDataType: AsRef<str> + Display + ComboBoxEnum + IntoEnumIterator
     fn to_SwitchableInnerValueType(&self) -> SwitchableInnerValueType;
 
     fn inner_data_type_from(str: &str) -> Option<Self> {
-        for x in Self::iter() {
-            if x.as_ref() == str {
-                return Some(x);
-            }
-        }
-        None
+        Self::iter().find(|x| x.as_ref() == str)
     }
 }
@@ -1,5 +1,6 @@
 use std::fmt::Display;
 
+use serde::{Deserialize, Serialize};
 use strum::{AsRefStr, EnumCount, EnumIter};
 
 use crate::nodes::{data_types::SwitchableInnerValueType, node_types::NodeTemplate};
@@ -7,7 +8,7 @@ use crate::ui::ComboBoxEnum;
 
 use super::InnerDataType;
 
-#[derive(Copy, Clone, Debug, EnumIter, AsRefStr, EnumCount, PartialEq)]
+#[derive(Copy, Clone, Debug, EnumIter, AsRefStr, EnumCount, PartialEq, Serialize, Deserialize)]
 #[strum(serialize_all = "snake_case")]
 pub enum SurfaceRuleType {
     Bandlands,
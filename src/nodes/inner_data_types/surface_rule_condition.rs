@@ -1,5 +1,6 @@
 use std::fmt::Display;
 
+use serde::{Deserialize, Serialize};
 use strum::{AsRefStr, EnumCount, EnumIter, EnumString};
 
 use crate::nodes::{data_types::SwitchableInnerValueType, node_types::NodeTemplate};
@@ -7,7 +8,7 @@ use crate::ui::ComboBoxEnum;
 
 use super::InnerDataType;
 
-#[derive(Copy, Clone, Debug, EnumIter, AsRefStr, EnumCount, PartialEq)]
+#[derive(Copy, Clone, Debug, EnumIter, AsRefStr, EnumCount, PartialEq, Serialize, Deserialize)]
 #[strum(serialize_all = "snake_case")]
 pub enum SurfaceRuleConditionType {
     Biome,
@@ -40,7 +41,10 @@ impl InnerDataType for SurfaceRuleConditionType {
     }
 }
 
-#[derive(Copy, Clone, Debug, EnumIter, AsRefStr, EnumCount, PartialEq, EnumString)]
+#[derive(
+    Copy, Clone, Debug, EnumIter, AsRefStr, EnumCount, PartialEq, EnumString, Serialize, Deserialize,
+)]
+#[strum(serialize_all = "snake_case")]
 pub enum VerticalAnchor {
     Absolute,
     AboveBottom,
@@ -49,7 +53,10 @@ pub enum VerticalAnchor {
 
 impl ComboBoxEnum for VerticalAnchor {}
 
-#[derive(Copy, Clone, Debug, EnumIter, AsRefStr, EnumCount, PartialEq, EnumString)]
+#[derive(
+    Copy, Clone, Debug, EnumIter, AsRefStr, EnumCount, PartialEq, EnumString, Serialize, Deserialize,
+)]
+#[strum(serialize_all = "snake_case")]
 pub enum SurfaceType {
     Floor,
     Ceiling
@@ -3,12 +3,21 @@ pub mod data_types;
 pub mod inner_data_types;
 pub mod node_types;
 
-use eframe::{egui, epaint::Pos2};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use eframe::{
+    egui,
+    epaint::{Pos2, Vec2},
+};
 use egui_node_graph::{
-    self, Graph, NodeDataTrait, NodeId, NodeResponse, NodeTemplateTrait, UserResponseTrait, InputId,
+    self, Graph, NodeDataTrait, NodeId, NodeResponse, NodeTemplateTrait, UserResponseTrait,
 };
+use serde::{Deserialize, Serialize};
 
-use crate::app::EditorStateType;
+use crate::app::{EditorStateType, Identifier};
+use crate::references::References;
+use crate::version::McVersion;
 
 use self::{
     data_types::{DataType, ValueType},
@@ -17,9 +26,26 @@ use self::{
 
 pub type GraphType = Graph<NodeData, DataType, ValueType>;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct NodeData {
     pub template: NodeTemplate,
+    /// Read from the JSON but not understood by the editor, written back as they were
+    #[serde(default)]
+    pub unknown_fields: Vec<UnknownField>,
+    /// Lists that were a single id instead of an array, written back like that while they
+    /// have one entry
+    #[serde(default)]
+    pub single_id_lists: Vec<String>,
+}
+
+/// A JSON field a node has no input for, or one whose value couldn't be read.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct UnknownField {
+    /// `Object` input the field was found in, `None` for fields of the node itself
+    pub parent: Option<String>,
+    pub key: String,
+    /// JSON text of the value
+    pub value: String,
 }
 
 impl NodeDataTrait for NodeData {
@@ -55,16 +81,49 @@ impl NodeDataTrait for NodeData {
                 responses.push(NodeResponse::User(Response::ClearActiveNode));
             }
         }
+        if !self.unknown_fields.is_empty() {
+            let title = format!("unknown fields ({})", self.unknown_fields.len());
+            ui.collapsing(title, |ui| {
+                for (i, field) in self.unknown_fields.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        let key = match &field.parent {
+                            Some(parent) => format!("{}.{}", parent, field.key),
+                            None => field.key.clone(),
+                        };
+                        ui.label(key).on_hover_text(&field.value);
+                        if ui.small_button("x").on_hover_text("leave out of the file").clicked() {
+                            responses.push(NodeResponse::User(Response::RemoveUnknownField(
+                                node_id, i,
+                            )));
+                        }
+                    });
+                }
+            });
+        }
+        // The margins of the node are outside of `ui`
+        let size = ui.min_rect().size() + Vec2::new(30., 10.);
+        user_state.node_sizes.insert(node_id, size);
         responses
     }
 
     fn titlebar_color(
         &self,
         _ui: &egui::Ui,
-        _node_id: NodeId,
-        _graph: &Graph<Self, Self::DataType, Self::ValueType>,
-        _user_state: &mut Self::UserState,
+        node_id: NodeId,
+        graph: &Graph<Self, Self::DataType, Self::ValueType>,
+        user_state: &mut Self::UserState,
     ) -> Option<egui::Color32> {
+        // Highlights references to files that don't exist
+        if let NodeTemplate::Reference(_) = self.template {
+            let value = graph[node_id]
+                .get_input("Reference")
+                .map(|in_id| graph.get_input(in_id).value());
+            if let Ok(ValueType::Reference(window_type, name)) = value {
+                if !user_state.references.resolves(*window_type, name) {
+                    return Some(egui::Color32::DARK_RED);
+                }
+            }
+        }
         None
     }
 
@@ -85,22 +144,23 @@ impl NodeDataTrait for NodeData {
 pub enum Response {
     SetActiveNode(NodeId),
     ClearActiveNode,
-    IncreaseInputs(NodeId),
-    DecreaseInputs(NodeId),
+    IncreaseInputs(NodeId, Box<str>),
+    DecreaseInputs(NodeId, Box<str>),
     ChangeNodeType(NodeId, NodeTemplate),
-    ChangeInputLabel(NodeId, Box<str>, Box<str>)
+    /// Opens the window a reference points to
+    GoToDefinition(Identifier),
+    /// Index into `NodeData::unknown_fields`
+    RemoveUnknownField(NodeId, usize),
 }
 impl UserResponseTrait for Response {}
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct GraphState {
     pub active_node: Option<NodeId>,
-}
-impl Default for GraphState {
-    fn default() -> Self {
-        Self {
-            active_node: Default::default(),
-        }
-    }
+    pub references: Rc<References>,
+    /// Version the pack is made for, decides which nodes and inputs there are
+    pub version: McVersion,
+    /// Size of every node the last time it was drawn, used to arrange them
+    pub node_sizes: HashMap<NodeId, Vec2>,
 }
 /// rebuilds node in place. Keeps output connection.
 pub fn rebuild_node(
@@ -109,28 +169,26 @@ pub fn rebuild_node(
     user_state: &mut GraphState,
     template: NodeTemplate,
 ) {
-    let node = graph.nodes.get(node_id).unwrap().clone();
-    let old_ouput = node.output_ids().next().unwrap();
-    let old_input_opt = graph
-        .connections
-        .iter()
-        .find(|(_, &o)| o == old_ouput)
-        .map(|(i, _o)| i);
+    let node = match graph.nodes.get(node_id) {
+        Some(node) => node.clone(),
+        None => return,
+    };
+    let old_input_opt = node.output_ids().next().and_then(|old_output| {
+        graph
+            .connections
+            .iter()
+            .find(|(_, &o)| o == old_output)
+            .map(|(i, _o)| i)
+    });
     node.input_ids().for_each(|x| graph.remove_input_param(x));
     node.output_ids().for_each(|x| graph.remove_output_param(x));
 
     template.build_node(graph, user_state, node_id);
-    let mut node = graph.nodes.get_mut(node_id).unwrap();
+    let node = &mut graph[node_id];
     node.user_data.template = template;
     node.label = template.node_graph_label(user_state);
-    if let Some(old_input) = old_input_opt {
-        let new_output = graph
-            .nodes
-            .get(node_id)
-            .unwrap()
-            .output_ids()
-            .next()
-            .unwrap();
+    let new_output = node.output_ids().next();
+    if let (Some(old_input), Some(new_output)) = (old_input_opt, new_output) {
         graph.add_connection(new_output, old_input);
     }
 }
@@ -1,15 +1,23 @@
 use std::borrow::Cow;
 
-use egui_node_graph::{InputParamKind, NodeId, NodeTemplateIter, NodeTemplateTrait};
+use eframe::epaint::Color32;
+use egui_node_graph::{
+    DataTypeTrait, InputId, InputParamKind, NodeId, NodeTemplateIter, NodeTemplateTrait,
+};
+use serde::{Deserialize, Serialize};
 
 use crate::{
     nodes::inner_data_types::{
-        density_function::WeirdScaledSampleRarityValueMapper, surface_rule_condition,
+        biome::{GrassColorModifier, Precipitation, TemperatureModifier},
+        density_function::WeirdScaledSampleRarityValueMapper,
+        surface_rule_condition,
     },
+    version::McVersion,
     window::WindowType,
 };
 
 use super::{
+    blocks::BlockState,
     data_types::{ComplexDataType, DataType, SwitchableInnerValueType, ValueType},
     inner_data_types::{
         density_function::DensityFunctionType, surface_rule::SurfaceRuleType,
@@ -18,7 +26,55 @@ use super::{
     GraphState, GraphType, NodeData,
 };
 
-#[derive(Copy, Clone, Debug)]
+/// Density functions of a `noise_router`, in the order the game lists them. Nodes are built
+/// with the ones their version reads, see `version::VERSIONED_FIELDS`.
+pub const NOISE_ROUTER: [&str; 16] = [
+    "barrier",
+    "fluid_level_floodedness",
+    "fluid_level_spread",
+    "lava",
+    "temperature",
+    "vegetation",
+    "continents",
+    "erosion",
+    "depth",
+    "ridges",
+    "initial_density_without_jaggedness",
+    "preliminary_surface_level",
+    "final_density",
+    "vein_toggle",
+    "vein_ridged",
+    "vein_gap",
+];
+
+/// `GenerationStep.Decoration`s, each one is a list of placed features in a biome.
+pub const GENERATION_STEPS: [&str; 11] = [
+    "raw_generation",
+    "lakes",
+    "local_modifications",
+    "underground_structures",
+    "surface_structures",
+    "strongholds",
+    "underground_ores",
+    "underground_decoration",
+    "fluid_springs",
+    "vegetal_decoration",
+    "top_layer_modification",
+];
+
+/// `MobCategory`s a biome has spawners for.
+pub const MOB_CATEGORIES: [&str; 8] = [
+    "monster",
+    "creature",
+    "ambient",
+    "axolotls",
+    "underground_water_creature",
+    "water_creature",
+    "water_ambient",
+    "misc",
+];
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum NodeTemplate {
     ConstantValue,
     AddValue,
@@ -29,6 +85,17 @@ pub enum NodeTemplate {
     Output(WindowType),
     SurfaceRule(SurfaceRuleType),
     SurfaceRuleCondition(SurfaceRuleConditionType),
+    Spline,
+    SplinePoint,
+    ParameterPoint,
+    MoodSound,
+    AdditionsSound,
+    Music,
+    Particle,
+    Spawner,
+    SpawnCost,
+    /// Holds JSON of the given type as text, for objects the editor can't show as nodes
+    RawJson(ComplexDataType),
 }
 
 impl NodeTemplateTrait for NodeTemplate {
@@ -37,7 +104,7 @@ impl NodeTemplateTrait for NodeTemplate {
     type ValueType = ValueType;
     type UserState = GraphState;
 
-    fn node_finder_label(&self, _user_state: &mut Self::UserState) -> std::borrow::Cow<str> {
+    fn node_finder_label(&self, _user_state: &mut Self::UserState) -> std::borrow::Cow<'_, str> {
         match self {
             NodeTemplate::ConstantValue => Cow::Borrowed("Value"),
             NodeTemplate::AddValue => Cow::Borrowed("Add"),
@@ -48,6 +115,18 @@ impl NodeTemplateTrait for NodeTemplate {
             NodeTemplate::Output(x) => Cow::Owned(format!("Output ({})", x.as_ref())),
             NodeTemplate::SurfaceRule(_) => Cow::Borrowed("Surface Rule"),
             NodeTemplate::SurfaceRuleCondition(_) => Cow::Borrowed("Surface Rule Condition"),
+            NodeTemplate::Spline => Cow::Borrowed("Spline"),
+            NodeTemplate::SplinePoint => Cow::Borrowed("Spline Point"),
+            NodeTemplate::ParameterPoint => Cow::Borrowed("Parameter Point"),
+            NodeTemplate::MoodSound => Cow::Borrowed("Mood Sound"),
+            NodeTemplate::AdditionsSound => Cow::Borrowed("Additions Sound"),
+            NodeTemplate::Music => Cow::Borrowed("Music"),
+            NodeTemplate::Particle => Cow::Borrowed("Particle"),
+            NodeTemplate::Spawner => Cow::Borrowed("Spawner"),
+            NodeTemplate::SpawnCost => Cow::Borrowed("Spawn Cost"),
+            NodeTemplate::RawJson(x) => {
+                Cow::Owned(format!("Raw JSON ({})", DataType::Single(*x).name()))
+            }
         }
     }
 
@@ -60,15 +139,22 @@ impl NodeTemplateTrait for NodeTemplate {
     }
 
     fn user_data(&self, _user_state: &mut Self::UserState) -> Self::NodeData {
-        NodeData { template: *self }
+        NodeData {
+            template: *self,
+            unknown_fields: Vec::new(),
+            single_id_lists: Vec::new(),
+        }
     }
 
     fn build_node(
         &self,
         graph: &mut egui_node_graph::Graph<Self::NodeData, Self::DataType, Self::ValueType>,
-        _user_state: &mut Self::UserState,
+        user_state: &mut Self::UserState,
         node_id: NodeId,
     ) {
+        let output_single = |graph: &mut GraphType, cdt: ComplexDataType| {
+            graph.add_output_param(node_id, "out".to_string(), DataType::Single(cdt));
+        };
         let input_value = |graph: &mut GraphType, name: &str, kind: InputParamKind| {
             graph.add_input_param(
                 node_id,
@@ -102,12 +188,12 @@ impl NodeTemplateTrait for NodeTemplate {
                 true,
             );
         };
-        let input_block = |graph: &mut GraphType, name: &str, kind: InputParamKind| {
+        let input_block = |graph: &mut GraphType, name: &str, block: &str, kind: InputParamKind| {
             graph.add_input_param(
                 node_id,
                 name.to_string(),
                 DataType::Block,
-                ValueType::Block(0),
+                ValueType::Block(BlockState::new(block)),
                 kind,
                 true,
             );
@@ -115,13 +201,14 @@ impl NodeTemplateTrait for NodeTemplate {
         let output_block = |graph: &mut GraphType, name: &str| {
             graph.add_output_param(node_id, name.to_string(), DataType::Block);
         };
+        // A density function can also be a plain number, written when nothing is connected
         let input_df = |graph: &mut GraphType, name: &str| {
             graph.add_input_param(
                 node_id,
                 name.to_string(),
                 DataType::Single(ComplexDataType::DensityFunction),
-                ValueType::DensityFunction,
-                InputParamKind::ConnectionOnly,
+                ValueType::Value(0.),
+                InputParamKind::ConnectionOrConstant,
                 true,
             );
         };
@@ -132,16 +219,6 @@ impl NodeTemplateTrait for NodeTemplate {
                 DataType::Single(ComplexDataType::DensityFunction),
             );
         };
-        let input_noise = |graph: &mut GraphType, name: &str| {
-            graph.add_input_param(
-                node_id,
-                name.to_string(),
-                DataType::Single(ComplexDataType::Noise),
-                ValueType::Noise,
-                InputParamKind::ConnectionOnly,
-                true,
-            );
-        };
         let output_noise = |graph: &mut GraphType, name: &str| {
             graph.add_output_param(
                 node_id,
@@ -197,6 +274,98 @@ impl NodeTemplateTrait for NodeTemplate {
                 DataType::Single(ComplexDataType::Biome),
             );
         };
+        let input_range = |graph: &mut GraphType, name: &str| {
+            graph.add_input_param(
+                node_id,
+                name.to_string(),
+                DataType::Range,
+                ValueType::Range(0., 0.),
+                InputParamKind::ConstantOnly,
+                true,
+            );
+        };
+        // Inputs added right after this one are written inside of an object called `name`
+        let input_object = |graph: &mut GraphType, name: &str, len: usize| {
+            graph.add_input_param(
+                node_id,
+                name.to_string(),
+                DataType::Object,
+                ValueType::Object(len),
+                InputParamKind::ConstantOnly,
+                true,
+            );
+        };
+        let input_dull_reference = |graph: &mut GraphType, name: &str| {
+            graph.add_input_param(
+                node_id,
+                name.to_string(),
+                DataType::DullReference,
+                ValueType::DullReference(String::new()),
+                InputParamKind::ConstantOnly,
+                true,
+            );
+        };
+        let input_color = |graph: &mut GraphType, name: &str, color: Option<Color32>| {
+            graph.add_input_param(
+                node_id,
+                name.to_string(),
+                DataType::Color,
+                ValueType::Color(color),
+                InputParamKind::ConstantOnly,
+                true,
+            );
+        };
+        // Optional objects are left out of the JSON while nothing is connected
+        let input_single = |graph: &mut GraphType, name: &str, cdt: ComplexDataType| {
+            graph.add_input_param(
+                node_id,
+                name.to_string(),
+                DataType::Single(cdt),
+                DataType::Single(cdt).default_ValueType(),
+                InputParamKind::ConnectionOnly,
+                true,
+            );
+        };
+        // Adds the list input and its first entry, `list` is either `DataType::List` or `DataType::Map`
+        let input_list = |graph: &mut GraphType, name: &str, list: DataType| {
+            let cdt = match list {
+                DataType::List(cdt) | DataType::Map(cdt) => cdt,
+                _ => unreachable!(),
+            };
+            graph.add_input_param(
+                node_id,
+                name.to_string(),
+                list,
+                ValueType::List(1),
+                InputParamKind::ConstantOnly,
+                true,
+            );
+            let kind = match cdt.reference_window_type() {
+                None if DataType::Single(cdt).defualt_NodeTemplate().is_none() => {
+                    InputParamKind::ConstantOnly
+                }
+                _ => InputParamKind::ConnectionOnly,
+            };
+            graph.add_input_param(
+                node_id,
+                "".to_string(),
+                DataType::Single(cdt),
+                DataType::Single(cdt).default_ValueType(),
+                kind,
+                true,
+            );
+        };
+        // A spline can also be a plain number, so the inline value is kept when nothing is connected
+        let input_spline = |graph: &mut GraphType, name: &str| {
+            graph.add_input_param(
+                node_id,
+                name.to_string(),
+                DataType::Single(ComplexDataType::Spline),
+                ValueType::Value(0.),
+                InputParamKind::ConnectionOrConstant,
+                true,
+            );
+        };
         //TODO: Make sure label wording matches JSON
         match self {
             NodeTemplate::ConstantValue => {
@@ -209,7 +378,12 @@ impl NodeTemplateTrait for NodeTemplate {
                 output_value(graph, "out");
             }
             NodeTemplate::ConstantBlock => {
-                input_block(graph, "block", InputParamKind::ConstantOnly);
+                input_block(
+                    graph,
+                    "block",
+                    "minecraft:stone",
+                    InputParamKind::ConstantOnly,
+                );
                 output_block(graph, "out");
             }
             NodeTemplate::DensityFunction(x) => {
@@ -290,7 +464,7 @@ impl NodeTemplateTrait for NodeTemplate {
                     RangeChoice => {
                         input_df(graph, "input");
                         input_value(graph, "min_inclusive", InputParamKind::ConnectionOrConstant);
-                        input_value(graph, "max_inclusive", InputParamKind::ConnectionOrConstant);
+                        input_value(graph, "max_exclusive", InputParamKind::ConnectionOrConstant);
                         input_df(graph, "when_in_range");
                         input_df(graph, "when_out_of_range");
                     }
@@ -308,7 +482,7 @@ impl NodeTemplateTrait for NodeTemplate {
                         input_value(graph, "max", InputParamKind::ConnectionOrConstant);
                     }
                     Spline => {
-                        todo!("Splines are a lot of work, huh");
+                        input_spline(graph, "spline");
                     }
                     YClampedGradient => {
                         input_int(graph, "from_y");
@@ -316,6 +490,12 @@ impl NodeTemplateTrait for NodeTemplate {
                         input_value(graph, "from_value", InputParamKind::ConnectionOrConstant);
                         input_value(graph, "to_value", InputParamKind::ConnectionOrConstant);
                     }
+                    FindTopSurface => {
+                        input_df(graph, "density");
+                        input_df(graph, "upper_bound");
+                        input_int(graph, "lower_bound");
+                        input_int(graph, "cell_height");
+                    }
                 }
             }
 
@@ -330,6 +510,8 @@ impl NodeTemplateTrait for NodeTemplate {
                     WindowType::DensityFunction => output_df(graph, "out"),
                     WindowType::Noise => output_noise(graph, "out"),
                     WindowType::Biome => output_biome(graph, "out"),
+                    // Only dimensions refer to noise settings
+                    WindowType::NoiseSettings => {}
                 }
             }
             NodeTemplate::Output(x) => match x {
@@ -340,7 +522,127 @@ impl NodeTemplateTrait for NodeTemplate {
                     input_value(graph, "firstOctave", InputParamKind::ConstantOnly);
                     input_values_arr(graph, "amplitudes");
                 }
-                WindowType::Biome => todo!(),
+                WindowType::NoiseSettings => {
+                    input_int(graph, "sea_level");
+                    input_bool(graph, "disable_mob_generation");
+                    input_bool(graph, "aquifers_enabled");
+                    input_bool(graph, "ore_veins_enabled");
+                    input_bool(graph, "legacy_random_source");
+                    input_block(
+                        graph,
+                        "default_block",
+                        "minecraft:stone",
+                        InputParamKind::ConnectionOrConstant,
+                    );
+                    input_block(
+                        graph,
+                        "default_fluid",
+                        "minecraft:water",
+                        InputParamKind::ConnectionOrConstant,
+                    );
+                    input_object(graph, "noise", 4);
+                    input_int(graph, "min_y");
+                    input_int(graph, "height");
+                    input_int(graph, "size_horizontal");
+                    input_int(graph, "size_vertical");
+                    input_object(graph, "noise_router", NOISE_ROUTER.len());
+                    for name in NOISE_ROUTER {
+                        input_df(graph, name);
+                    }
+                    graph.add_input_param(
+                        node_id,
+                        "surface_rule".to_string(),
+                        DataType::Single(ComplexDataType::SurfaceRule),
+                        ValueType::SurfaceRule,
+                        InputParamKind::ConnectionOnly,
+                        true,
+                    );
+                    graph.add_input_param(
+                        node_id,
+                        "spawn_target".to_string(),
+                        DataType::List(ComplexDataType::ParameterPoint),
+                        ValueType::List(1),
+                        InputParamKind::ConstantOnly,
+                        true,
+                    );
+                    graph.add_input_param(
+                        node_id,
+                        "".to_string(),
+                        DataType::Single(ComplexDataType::ParameterPoint),
+                        ValueType::ParameterPoint,
+                        InputParamKind::ConnectionOnly,
+                        true,
+                    );
+                }
+                WindowType::Biome => {
+                    input_value(graph, "temperature", InputParamKind::ConstantOnly);
+                    input_value(graph, "downfall", InputParamKind::ConstantOnly);
+                    input_bool(graph, "has_precipitation");
+                    graph.add_input_param(
+                        node_id,
+                        "precipitation".to_string(),
+                        DataType::Precipitation,
+                        ValueType::Precipitation(Precipitation::Rain),
+                        InputParamKind::ConstantOnly,
+                        true,
+                    );
+                    graph.add_input_param(
+                        node_id,
+                        "temperature_modifier".to_string(),
+                        DataType::TemperatureModifier,
+                        ValueType::TemperatureModifier(TemperatureModifier::None),
+                        InputParamKind::ConstantOnly,
+                        true,
+                    );
+                    input_object(graph, "effects", 12);
+                    input_color(graph, "fog_color", Some(Color32::from_rgb(192, 216, 255)));
+                    input_color(graph, "sky_color", Some(Color32::from_rgb(120, 167, 255)));
+                    input_color(graph, "water_color", Some(Color32::from_rgb(63, 118, 228)));
+                    input_color(graph, "water_fog_color", Some(Color32::from_rgb(5, 5, 51)));
+                    input_color(graph, "foliage_color", None);
+                    input_color(graph, "grass_color", None);
+                    graph.add_input_param(
+                        node_id,
+                        "grass_color_modifier".to_string(),
+                        DataType::GrassColorModifier,
+                        ValueType::GrassColorModifier(GrassColorModifier::None),
+                        InputParamKind::ConstantOnly,
+                        true,
+                    );
+                    input_dull_reference(graph, "ambient_sound");
+                    input_single(graph, "mood_sound", ComplexDataType::MoodSound);
+                    input_single(graph, "additions_sound", ComplexDataType::AdditionsSound);
+                    input_single(graph, "music", ComplexDataType::Music);
+                    input_single(graph, "particle", ComplexDataType::Particle);
+                    input_object(graph, "carvers", 2);
+                    for name in ["air", "liquid"] {
+                        input_list(
+                            graph,
+                            name,
+                            DataType::List(ComplexDataType::ConfiguredCarver),
+                        );
+                    }
+                    graph.add_input_param(
+                        node_id,
+                        "features".to_string(),
+                        DataType::Tuple,
+                        ValueType::Tuple(GENERATION_STEPS.len()),
+                        InputParamKind::ConstantOnly,
+                        true,
+                    );
+                    for name in GENERATION_STEPS {
+                        input_list(graph, name, DataType::List(ComplexDataType::PlacedFeature));
+                    }
+                    input_object(graph, "spawners", MOB_CATEGORIES.len());
+                    for name in MOB_CATEGORIES {
+                        input_list(graph, name, DataType::List(ComplexDataType::Spawner));
+                    }
+                    input_list(
+                        graph,
+                        "spawn_costs",
+                        DataType::Map(ComplexDataType::SpawnCost),
+                    );
+                }
             },
             NodeTemplate::SurfaceRule(x) => {
                 graph.add_output_param(
@@ -357,7 +659,7 @@ impl NodeTemplateTrait for NodeTemplate {
                             node_id,
                             "result_state".to_string(),
                             DataType::Block,
-                            ValueType::Block(0),
+                            ValueType::Block(BlockState::default()),
                             InputParamKind::ConnectionOrConstant,
                             true,
                         );
@@ -383,7 +685,7 @@ impl NodeTemplateTrait for NodeTemplate {
                     Condition => {
                         graph.add_input_param(
                             node_id,
-                            "if true".to_string(),
+                            "if_true".to_string(),
                             DataType::Single(ComplexDataType::SurfaceRuleCondition),
                             ValueType::SurfaceRuleCondition,
                             InputParamKind::ConnectionOnly,
@@ -391,7 +693,7 @@ impl NodeTemplateTrait for NodeTemplate {
                         );
                         graph.add_input_param(
                             node_id,
-                            "then run".to_string(),
+                            "then_run".to_string(),
                             DataType::Single(ComplexDataType::SurfaceRule),
                             ValueType::SurfaceRule,
                             InputParamKind::ConnectionOnly,
@@ -508,25 +810,167 @@ impl NodeTemplateTrait for NodeTemplate {
                     }
                 }
             }
+            NodeTemplate::Spline => {
+                graph.add_output_param(
+                    node_id,
+                    "out".to_string(),
+                    DataType::Single(ComplexDataType::Spline),
+                );
+                input_df(graph, "coordinate");
+                graph.add_input_param(
+                    node_id,
+                    "points".to_string(),
+                    DataType::List(ComplexDataType::SplinePoint),
+                    ValueType::List(1),
+                    InputParamKind::ConstantOnly,
+                    true,
+                );
+                graph.add_input_param(
+                    node_id,
+                    "".to_string(),
+                    DataType::Single(ComplexDataType::SplinePoint),
+                    ValueType::SplinePoint,
+                    InputParamKind::ConnectionOnly,
+                    true,
+                );
+            }
+            NodeTemplate::SplinePoint => {
+                graph.add_output_param(
+                    node_id,
+                    "out".to_string(),
+                    DataType::Single(ComplexDataType::SplinePoint),
+                );
+                input_value(graph, "location", InputParamKind::ConstantOnly);
+                input_value(graph, "derivative", InputParamKind::ConstantOnly);
+                input_spline(graph, "value");
+            }
+            NodeTemplate::ParameterPoint => {
+                graph.add_output_param(
+                    node_id,
+                    "out".to_string(),
+                    DataType::Single(ComplexDataType::ParameterPoint),
+                );
+                input_range(graph, "temperature");
+                input_range(graph, "humidity");
+                input_range(graph, "continentalness");
+                input_range(graph, "erosion");
+                input_range(graph, "weirdness");
+                input_range(graph, "depth");
+                input_value(graph, "offset", InputParamKind::ConstantOnly);
+            }
+            NodeTemplate::MoodSound => {
+                output_single(graph, ComplexDataType::MoodSound);
+                input_dull_reference(graph, "sound");
+                input_int(graph, "tick_delay");
+                input_int(graph, "block_search_extent");
+                input_value(graph, "offset", InputParamKind::ConstantOnly);
+            }
+            NodeTemplate::AdditionsSound => {
+                output_single(graph, ComplexDataType::AdditionsSound);
+                input_dull_reference(graph, "sound");
+                input_value(graph, "tick_chance", InputParamKind::ConstantOnly);
+            }
+            NodeTemplate::Music => {
+                output_single(graph, ComplexDataType::Music);
+                input_dull_reference(graph, "sound");
+                input_int(graph, "min_delay");
+                input_int(graph, "max_delay");
+                input_bool(graph, "replace_current_music");
+            }
+            NodeTemplate::Particle => {
+                output_single(graph, ComplexDataType::Particle);
+                input_object(graph, "options", 1);
+                input_dull_reference(graph, "type");
+                input_value(graph, "probability", InputParamKind::ConstantOnly);
+            }
+            NodeTemplate::Spawner => {
+                output_single(graph, ComplexDataType::Spawner);
+                input_dull_reference(graph, "type");
+                input_int(graph, "weight");
+                input_int(graph, "minCount");
+                input_int(graph, "maxCount");
+            }
+            NodeTemplate::SpawnCost => {
+                output_single(graph, ComplexDataType::SpawnCost);
+                // Key of the entry in `spawn_costs`
+                input_dull_reference(graph, "entity");
+                input_value(graph, "energy_budget", InputParamKind::ConstantOnly);
+                input_value(graph, "charge", InputParamKind::ConstantOnly);
+            }
+            NodeTemplate::RawJson(x) => {
+                output_single(graph, *x);
+                graph.add_input_param(
+                    node_id,
+                    "json".to_string(),
+                    DataType::RawJson,
+                    DataType::RawJson.default_ValueType(),
+                    InputParamKind::ConstantOnly,
+                    true,
+                );
+            }
+        }
+        // Fields the pack's version doesn't read, along with the object they are written in
+        let mut unread: Vec<(InputId, Option<InputId>)> = Vec::new();
+        let mut nested: Option<(InputId, usize)> = None;
+        for (name, input_id) in &graph[node_id].inputs {
+            // Entries of a list don't count, the list does
+            let object = match nested.as_mut() {
+                Some((object_id, len)) if *len > 0 && !name.is_empty() => {
+                    *len -= 1;
+                    Some(*object_id)
+                }
+                _ => None,
+            };
+            if !user_state.version.has_field(*self, name) {
+                unread.push((*input_id, object));
+            }
+            if let ValueType::Object(len) | ValueType::Tuple(len) = graph[*input_id].value {
+                nested = Some((*input_id, len));
+            }
+        }
+        for (input_id, object) in unread {
+            graph.remove_input_param(input_id);
+            if let Some(ValueType::Object(len) | ValueType::Tuple(len)) =
+                object.map(|object_id| &mut graph[object_id].value)
+            {
+                *len -= 1;
+            }
         }
     }
 }
 
-pub struct AllNodeTemplates;
+/// Templates of the node finder, those the version doesn't have are left out.
+pub struct AllNodeTemplates(pub McVersion);
 impl NodeTemplateIter for AllNodeTemplates {
     type Item = NodeTemplate;
 
     fn all_kinds(&self) -> Vec<Self::Item> {
-        vec![
+        let all = vec![
             NodeTemplate::ConstantValue,
             NodeTemplate::AddValue,
             NodeTemplate::ConstantBlock,
             NodeTemplate::DensityFunction(DensityFunctionType::Constant),
             NodeTemplate::SurfaceRule(SurfaceRuleType::Sequence),
             NodeTemplate::SurfaceRuleCondition(SurfaceRuleConditionType::VerticalGradient),
+            NodeTemplate::Spline,
+            NodeTemplate::SplinePoint,
+            NodeTemplate::ParameterPoint,
+            NodeTemplate::MoodSound,
+            NodeTemplate::AdditionsSound,
+            NodeTemplate::Music,
+            NodeTemplate::Particle,
+            NodeTemplate::Spawner,
+            NodeTemplate::SpawnCost,
             NodeTemplate::Noise,
             NodeTemplate::Reference(WindowType::DensityFunction),
             NodeTemplate::Reference(WindowType::Noise),
-        ]
+            NodeTemplate::Reference(WindowType::Biome),
+            NodeTemplate::RawJson(ComplexDataType::DensityFunction),
+            NodeTemplate::RawJson(ComplexDataType::SurfaceRule),
+            NodeTemplate::RawJson(ComplexDataType::SurfaceRuleCondition),
+        ];
+        all.into_iter()
+            .filter(|template| self.0.supports(*template))
+            .collect()
     }
 }
@@ -0,0 +1,442 @@
+//! The datapack itself: its `pack.mcmeta` and the zip it's exported as.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Cursor, Write};
+use std::path::{Component, Path, PathBuf};
+
+use eframe::egui::{self, DragValue, Ui};
+use json::{object, JsonValue};
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::app::Identifier;
+use crate::errors::AppError;
+use crate::ui::ComboBoxEnum;
+use crate::version::McVersion;
+use crate::window::Window;
+
+pub const PACK_MCMETA: &str = "pack.mcmeta";
+const PACK_PNG: &str = "pack.png";
+const DATA_DIR: &str = "data";
+/// Inclusive range of `pack_format`s, written as a single number if both ends are the same.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FormatRange {
+    pub min: u32,
+    pub max: u32,
+}
+
+impl FormatRange {
+    /// Reads `15`, `[15, 18]` or `{"min_inclusive": 15, "max_inclusive": 18}`.
+    fn from_json(json: &JsonValue) -> Option<Self> {
+        let (min, max) = if let Some(format) = json.as_u32() {
+            (format, format)
+        } else if json.is_array() && json.len() == 2 {
+            (json[0].as_u32()?, json[1].as_u32()?)
+        } else {
+            (
+                json["min_inclusive"].as_u32()?,
+                json["max_inclusive"].as_u32()?,
+            )
+        };
+        Some(Self { min, max })
+    }
+
+    fn to_json(self) -> JsonValue {
+        if self.min == self.max {
+            self.min.into()
+        } else {
+            json::array![self.min, self.max]
+        }
+    }
+
+    fn ui(&mut self, ui: &mut Ui) -> bool {
+        ui.add(DragValue::new(&mut self.min).clamp_range(1..=self.max))
+            .changed()
+            | ui.add(DragValue::new(&mut self.max).clamp_range(self.min..=u32::MAX))
+                .changed()
+    }
+}
+
+/// Files of other packs hidden by this one, both are regular expressions.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct FilterEntry {
+    pub namespace: String,
+    pub path: String,
+}
+
+/// Folder next to `data/` whose files replace the pack's own when the game runs one of `formats`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Overlay {
+    pub formats: FormatRange,
+    pub directory: String,
+}
+
+/// What `pack.mcmeta` says about the pack.
+#[derive(Clone, PartialEq, Debug)]
+pub struct PackSettings {
+    pub description: String,
+    pub pack_format: u32,
+    pub supported_formats: Option<FormatRange>,
+    pub filter: Vec<FilterEntry>,
+    pub overlays: Vec<Overlay>,
+    /// The whole file as it was read, fields the editor doesn't know about are written back
+    original: JsonValue,
+    /// Changed since it was last read or written
+    pub dirty: bool,
+}
+
+impl Default for PackSettings {
+    fn default() -> Self {
+        Self {
+            description: String::new(),
+            pack_format: McVersion::default().pack_format(),
+            supported_formats: None,
+            filter: Vec::new(),
+            overlays: Vec::new(),
+            original: JsonValue::new_object(),
+            dirty: false,
+        }
+    }
+}
+
+impl PackSettings {
+    /// Reads `pack.mcmeta` of the project, the defaults are used if there is none.
+    pub fn load(project_path: &Path) -> Result<Self, AppError> {
+        let path = project_path.join(PACK_MCMETA);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let s = fs::read_to_string(&path)
+            .map_err(|e| AppError::FileRead(e.to_string()).with_file(path.clone()))?;
+        let json = json::parse(&s).map_err(|e| AppError::JsonError(e).with_file(path.clone()))?;
+        Self::from_json(json).map_err(|e| e.with_file(path))
+    }
+
+    pub fn save(&mut self, project_path: &Path) -> Result<(), AppError> {
+        let path = project_path.join(PACK_MCMETA);
+        fs::write(&path, self.to_json().pretty(4) + "\n")
+            .map_err(|e| AppError::FileWrite(e.to_string()).with_file(path))?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Version the pack is made for.
+    pub fn version(&self) -> McVersion {
+        McVersion::from_pack_format(self.pack_format)
+    }
+
+    /// Folders of `data/<namespace>/` read by the versions the pack supports.
+    pub fn known_folders(&self) -> Vec<&'static str> {
+        let range = self.supported_formats.unwrap_or(FormatRange {
+            min: self.pack_format,
+            max: self.pack_format,
+        });
+        let min = range.min.min(self.pack_format);
+        let max = range.max.max(self.pack_format);
+        let mut folders: Vec<&str> = McVersion::in_range(min, max)
+            .flat_map(McVersion::folders)
+            .collect();
+        folders.sort_unstable();
+        folders.dedup();
+        folders
+    }
+
+    pub fn from_json(json: JsonValue) -> Result<Self, AppError> {
+        let wrong = |message: &str, path: &str| {
+            AppError::WrongData(message.to_string().into()).with_json_path(path)
+        };
+        let pack = &json["pack"];
+        if !pack.is_object() {
+            return Err(wrong("missing pack", ".pack"));
+        }
+        let pack_format = pack["pack_format"]
+            .as_u32()
+            .ok_or_else(|| wrong("pack_format is not a number", ".pack.pack_format"))?;
+        let supported_formats = match &pack["supported_formats"] {
+            JsonValue::Null => None,
+            formats => Some(
+                FormatRange::from_json(formats)
+                    .ok_or_else(|| wrong("not a range of formats", ".pack.supported_formats"))?,
+            ),
+        };
+        let mut filter = Vec::new();
+        for (i, entry) in json["filter"]["block"].members().enumerate() {
+            if !entry.is_object() {
+                return Err(wrong("not an object", &format!(".filter.block[{}]", i)));
+            }
+            filter.push(FilterEntry {
+                namespace: entry["namespace"].as_str().unwrap_or_default().to_string(),
+                path: entry["path"].as_str().unwrap_or_default().to_string(),
+            });
+        }
+        let mut overlays = Vec::new();
+        for (i, entry) in json["overlays"]["entries"].members().enumerate() {
+            let path = format!(".overlays.entries[{}]", i);
+            overlays.push(Overlay {
+                formats: FormatRange::from_json(&entry["formats"])
+                    .ok_or_else(|| wrong("not a range of formats", &(path.clone() + ".formats")))?,
+                directory: entry["directory"]
+                    .as_str()
+                    .ok_or_else(|| wrong("directory is not a string", &(path + ".directory")))?
+                    .to_string(),
+            });
+        }
+        Ok(Self {
+            // Text components other than plain strings are kept as they are
+            description: match &pack["description"] {
+                JsonValue::Null => String::new(),
+                description => description
+                    .as_str()
+                    .map_or_else(|| description.dump(), str::to_string),
+            },
+            pack_format,
+            supported_formats,
+            filter,
+            overlays,
+            original: json,
+            dirty: false,
+        })
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        let mut json = self.original.clone();
+        let description = match json::parse(&self.description) {
+            Ok(component) if component.is_object() || component.is_array() => component,
+            _ => self.description.as_str().into(),
+        };
+        let mut pack = object! {
+            pack_format: self.pack_format,
+            description: description,
+        };
+        if let Some(formats) = self.supported_formats {
+            pack["supported_formats"] = formats.to_json();
+        }
+        for (key, value) in self.original["pack"].entries() {
+            if !pack.has_key(key) && key != "supported_formats" {
+                pack[key] = value.clone();
+            }
+        }
+        json["pack"] = pack;
+        json.remove("filter");
+        if !self.filter.is_empty() {
+            let block: Vec<JsonValue> = self
+                .filter
+                .iter()
+                .map(|entry| {
+                    let mut json = JsonValue::new_object();
+                    // An empty pattern matches everything, the game does the same without it
+                    if !entry.namespace.is_empty() {
+                        json["namespace"] = entry.namespace.as_str().into();
+                    }
+                    if !entry.path.is_empty() {
+                        json["path"] = entry.path.as_str().into();
+                    }
+                    json
+                })
+                .collect();
+            json["filter"] = object! { block: block };
+        }
+        json.remove("overlays");
+        if !self.overlays.is_empty() {
+            let entries: Vec<JsonValue> = self
+                .overlays
+                .iter()
+                .map(|overlay| {
+                    object! {
+                        formats: overlay.formats.to_json(),
+                        directory: overlay.directory.as_str(),
+                    }
+                })
+                .collect();
+            json["overlays"] = object! { entries: entries };
+        }
+        json
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) {
+        let mut changed = false;
+        egui::Grid::new("pack settings").show(ui, |ui| {
+            ui.label("version");
+            let mut version = self.version();
+            egui::ComboBox::from_id_source("version")
+                .selected_text(version.as_ref())
+                .show_ui(ui, |ui| McVersion::show_ui(ui, &mut version));
+            if version != self.version() {
+                self.pack_format = version.pack_format();
+                changed = true;
+            }
+            ui.end_row();
+            ui.label("description");
+            changed |= ui.text_edit_multiline(&mut self.description).changed();
+            ui.end_row();
+            ui.label("pack_format");
+            changed |= ui
+                .add(DragValue::new(&mut self.pack_format).clamp_range(1..=u32::MAX))
+                .changed();
+            ui.end_row();
+            ui.label("supported formats");
+            ui.horizontal(|ui| {
+                let mut supported = self.supported_formats.is_some();
+                if ui.checkbox(&mut supported, "").changed() {
+                    changed = true;
+                    self.supported_formats = supported.then_some(FormatRange {
+                        min: self.pack_format,
+                        max: self.pack_format,
+                    });
+                }
+                if let Some(formats) = &mut self.supported_formats {
+                    changed |= formats.ui(ui);
+                }
+            });
+            ui.end_row();
+        });
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.strong("filter");
+            if ui.small_button("+").clicked() {
+                self.filter.push(FilterEntry::default());
+                changed = true;
+            }
+        });
+        let mut remove = None;
+        for (i, entry) in self.filter.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                changed |= ui
+                    .add(egui::TextEdit::singleline(&mut entry.namespace).hint_text("namespace"))
+                    .changed();
+                changed |= ui
+                    .add(egui::TextEdit::singleline(&mut entry.path).hint_text("path"))
+                    .changed();
+                if ui.small_button("x").clicked() {
+                    remove = Some(i);
+                }
+            });
+        }
+        if let Some(i) = remove.take() {
+            self.filter.remove(i);
+            changed = true;
+        }
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.strong("overlays");
+            if ui.small_button("+").clicked() {
+                self.overlays.push(Overlay {
+                    formats: FormatRange {
+                        min: self.pack_format,
+                        max: self.pack_format,
+                    },
+                    directory: String::new(),
+                });
+                changed = true;
+            }
+        });
+        for (i, overlay) in self.overlays.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                changed |= ui
+                    .add(egui::TextEdit::singleline(&mut overlay.directory).hint_text("directory"))
+                    .changed();
+                changed |= overlay.formats.ui(ui);
+                if ui.small_button("x").clicked() {
+                    remove = Some(i);
+                }
+            });
+        }
+        if let Some(i) = remove {
+            self.overlays.remove(i);
+            changed = true;
+        }
+        self.dirty |= changed;
+    }
+}
+
+/// Folder the namespaces are in. That's `data/` of a datapack, or the project folder itself
+/// for projects that only have `<namespace>/worldgen/` folders, as the editor used to save them.
+/// A project with a `pack.mcmeta` is a datapack even before it has a `data/` folder.
+pub fn data_root(project_path: &Path) -> PathBuf {
+    let data = project_path.join(DATA_DIR);
+    if project_path.join(PACK_MCMETA).is_file() {
+        return data;
+    }
+    // `data/worldgen/` is the namespace `data` of the old layout
+    let old_layout = fs::read_dir(project_path).is_ok_and(|entries| {
+        entries
+            .filter_map(|e| e.ok())
+            .any(|e| e.path().join("worldgen").is_dir())
+    });
+    if old_layout {
+        project_path.to_path_buf()
+    } else {
+        data
+    }
+}
+
+/// Writes the pack as a zip that can be put in the `datapacks` folder of a world. Nothing is
+/// written if a file can't be, files that couldn't be read are copied as they are.
+pub fn export_zip(
+    project_path: &Path,
+    settings: &PackSettings,
+    file_structure: &[HashMap<Identifier, Window>],
+    zip_path: &Path,
+) -> Result<usize, Vec<AppError>> {
+    let mut files = vec![(
+        PACK_MCMETA.to_string(),
+        (settings.to_json().pretty(4) + "\n").into_bytes(),
+    )];
+    if let Ok(icon) = fs::read(project_path.join(PACK_PNG)) {
+        files.push((PACK_PNG.to_string(), icon));
+    }
+    let mut errors = Vec::new();
+    let data_root = data_root(project_path);
+    let mut windows: Vec<(&Identifier, &Window)> =
+        file_structure.iter().flat_map(|map| map.iter()).collect();
+    windows.sort_unstable_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap());
+    for (id, window) in windows {
+        let relative_path = window.relative_path();
+        let content = if window.load_failed && !window.history.can_undo() {
+            let path = data_root.join(&relative_path);
+            fs::read(&path).map_err(|e| {
+                AppError::FileRead(e.to_string())
+                    .with_window(id.clone())
+                    .with_file(path)
+            })
+        } else {
+            window.serialize_to_string().map(String::into_bytes)
+        };
+        match content {
+            Ok(content) => files.push((zip_name(&relative_path), content)),
+            Err(e) => errors.push(e),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    let count = files.len();
+    write_zip(files, zip_path)
+        .map_err(|e| vec![AppError::FileWrite(e.to_string()).with_file(zip_path.to_path_buf())])?;
+    Ok(count)
+}
+
+/// `data/` followed by `relative_path`, with `/` as separator on every platform.
+fn zip_name(relative_path: &Path) -> String {
+    let mut name = String::from(DATA_DIR);
+    for component in relative_path.components() {
+        if let Component::Normal(part) = component {
+            name.push('/');
+            name.push_str(&part.to_string_lossy());
+        }
+    }
+    name
+}
+
+fn write_zip(files: Vec<(String, Vec<u8>)>, zip_path: &Path) -> zip::result::ZipResult<()> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (name, content) in files {
+        zip.start_file(name, options)?;
+        zip.write_all(&content)?;
+    }
+    let buffer = zip.finish()?.into_inner();
+    fs::write(zip_path, buffer)?;
+    Ok(())
+}
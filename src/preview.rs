@@ -0,0 +1,559 @@
+use std::collections::HashSet;
+
+use eframe::egui::{self, ComboBox, DragValue, Sense, TextureFilter, TextureHandle, Ui};
+use eframe::epaint::{Color32, ColorImage, Vec2};
+use egui_node_graph::NodeId;
+use strum::{AsRefStr, EnumIter};
+
+use crate::app::Identifier;
+use crate::evaluator::density_function::DensityFunction;
+use crate::evaluator::surface_rule::{Column, ColumnBlock, Compiler};
+use crate::nodes::blocks::BlockState;
+use crate::nodes::data_types::ValueType;
+use crate::nodes::node_types::NodeTemplate;
+use crate::ui::ComboBoxEnum;
+use crate::window::{Window, WindowType};
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug, EnumIter, AsRefStr)]
+pub enum Slice {
+    /// horizontal slice at a chosen y
+    #[strum(serialize = "XZ slice")]
+    XZ,
+    /// vertical cross-section at a chosen z
+    #[strum(serialize = "XY cross-section")]
+    XY,
+}
+
+impl ComboBoxEnum for Slice {}
+
+/// Samples along each side of the heatmap while it's panned or zoomed, or values are dragged.
+const DRAFT_RESOLUTION: usize = 64;
+
+/// Heatmap of the active window's density function (or its active node).
+pub struct Preview {
+    pub show: bool,
+    pub seed: i64,
+    pub slice: Slice,
+    /// y of a `Slice::XZ`, z of a `Slice::XY`
+    pub depth: i32,
+    /// block coordinates shown in the middle of the heatmap
+    pub center: Vec2,
+    /// blocks per sample
+    pub scale: f32,
+    /// samples along each side of the heatmap
+    pub resolution: usize,
+    texture: Option<TextureHandle>,
+    function: Option<DensityFunction>,
+    error: Option<String>,
+    /// everything the last render depended on, re-rendered when it changes
+    key: Option<String>,
+    /// something was dragged or zoomed last frame, the heatmap is rendered at a lower resolution
+    interacting: bool,
+    range: (f64, f64),
+    column: ColumnPreview,
+}
+
+impl Preview {
+    pub fn new() -> Self {
+        Self {
+            show: true,
+            seed: 0,
+            slice: Slice::XY,
+            depth: 0,
+            center: Vec2::new(0., 64.),
+            scale: 4.,
+            resolution: 128,
+            texture: None,
+            function: None,
+            error: None,
+            key: None,
+            interacting: false,
+            range: (0., 0.),
+            column: ColumnPreview::new(),
+        }
+    }
+
+    /// Heatmap of density functions, column of the surface rule for noise settings.
+    pub fn ui<'a>(
+        &mut self,
+        ui: &mut Ui,
+        window: &Window,
+        resolve: &'a dyn Fn(&Identifier) -> Option<&'a Window>,
+    ) {
+        let mut refresh = false;
+        ui.horizontal(|ui| {
+            ui.label("seed");
+            ui.add(DragValue::new(&mut self.seed));
+            refresh |= ui.small_button("refresh").clicked();
+        });
+        if window.window_type == WindowType::NoiseSettings {
+            self.column.ui(ui, window, self.seed, refresh, resolve);
+        } else {
+            self.heatmap_ui(ui, window, refresh, resolve);
+        }
+    }
+
+    fn heatmap_ui<'a>(
+        &mut self,
+        ui: &mut Ui,
+        window: &Window,
+        refresh: bool,
+        resolve: &'a dyn Fn(&Identifier) -> Option<&'a Window>,
+    ) {
+        ui.horizontal(|ui| {
+            ComboBox::from_id_source("preview slice")
+                .selected_text(self.slice.as_ref())
+                .show_ui(ui, |ui| Slice::show_ui(ui, &mut self.slice));
+            ui.label(match self.slice {
+                Slice::XZ => "y",
+                Slice::XY => "z",
+            });
+            ui.add(DragValue::new(&mut self.depth));
+        });
+        ui.horizontal(|ui| {
+            ui.label("resolution");
+            ui.add(DragValue::new(&mut self.resolution).clamp_range(16..=512));
+        });
+
+        let node_id = window
+            .user_state
+            .active_node
+            .filter(|id| window.state.graph.nodes.contains_key(*id));
+        let resolution = match self.interacting {
+            true => self.resolution.min(DRAFT_RESOLUTION),
+            false => self.resolution,
+        };
+        let key = format!(
+            "{} {:?} {} {} {:?} {} {:?} {}",
+            generations(window, resolve),
+            node_id,
+            self.seed,
+            self.depth,
+            self.slice,
+            self.scale,
+            self.center,
+            resolution
+        );
+        if refresh || self.key.as_ref() != Some(&key) {
+            self.key = Some(key);
+            match window.compile_density_function(node_id, self.seed, resolve) {
+                Ok(function) => {
+                    self.error = None;
+                    self.render(ui.ctx(), &function, resolution);
+                    self.function = Some(function);
+                }
+                Err(e) => {
+                    self.error = Some(e.to_string());
+                    self.function = None;
+                }
+            }
+        }
+
+        ui.label(match node_id {
+            Some(_) => "previewing active node",
+            None => "previewing output",
+        });
+        if let Some(e) = &self.error {
+            ui.colored_label(Color32::RED, e);
+            return;
+        }
+        let texture = match &self.texture {
+            Some(texture) => texture,
+            None => return,
+        };
+        let side = ui
+            .available_width()
+            .min(ui.available_height() - 40.)
+            .max(16.);
+        let response =
+            ui.add(egui::Image::new(texture, Vec2::splat(side)).sense(Sense::click_and_drag()));
+        let blocks_per_point = self.scale * self.resolution as f32 / side;
+
+        if response.dragged() {
+            let delta = response.drag_delta() * blocks_per_point;
+            self.center.x -= delta.x;
+            self.center.y += match self.slice {
+                Slice::XZ => -delta.y,
+                Slice::XY => delta.y,
+            };
+        }
+        let mut zoomed = false;
+        if let Some(pos) = response.hover_pos() {
+            let scroll = ui.input().scroll_delta.y;
+            if scroll != 0. {
+                self.scale = (self.scale * (1. - scroll * 0.002)).clamp(1. / 16., 1024.);
+                zoomed = true;
+            }
+            let offset = (pos - response.rect.center()) * blocks_per_point;
+            let (x, y, z) = self.block_at(offset);
+            if let Some(function) = &self.function {
+                ui.label(format!(
+                    "{} {} {}: {:.5}",
+                    x,
+                    y,
+                    z,
+                    function.compute(x, y, z)
+                ));
+            }
+        } else {
+            ui.label(format!("min {:.5} max {:.5}", self.range.0, self.range.1));
+        }
+        // Renders at full resolution again once the interaction is over
+        let interacting = zoomed || ui.memory().is_anything_being_dragged();
+        if interacting != self.interacting {
+            self.interacting = interacting;
+            ui.ctx().request_repaint();
+        }
+    }
+
+    /// Block position at `offset` blocks from the center of the heatmap, y grows upwards on screen.
+    fn block_at(&self, offset: Vec2) -> (i32, i32, i32) {
+        let a = (self.center.x + offset.x).floor() as i32;
+        match self.slice {
+            Slice::XZ => (a, self.depth, (self.center.y + offset.y).floor() as i32),
+            Slice::XY => (a, (self.center.y - offset.y).floor() as i32, self.depth),
+        }
+    }
+
+    /// Renders `n` by `n` samples covering the same blocks whatever `n` is.
+    fn render(&mut self, ctx: &egui::Context, function: &DensityFunction, n: usize) {
+        let half = n as f32 / 2.;
+        let step = self.scale * self.resolution as f32 / n as f32;
+        let mut values = Vec::with_capacity(n * n);
+        for row in 0..n {
+            for column in 0..n {
+                let offset = Vec2::new(column as f32 - half, row as f32 - half) * step;
+                let (x, y, z) = self.block_at(offset);
+                values.push(function.compute(x, y, z));
+            }
+        }
+        let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+        let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        self.range = (min, max);
+        let extent = min.abs().max(max.abs()).max(f64::EPSILON);
+
+        let mut image = ColorImage::new([n, n], Color32::BLACK);
+        for (pixel, value) in image.pixels.iter_mut().zip(values) {
+            *pixel = heat_color(value / extent);
+        }
+        match &mut self.texture {
+            Some(texture) => texture.set(image, TextureFilter::Nearest),
+            None => self.texture = Some(ctx.load_texture("preview", image, TextureFilter::Nearest)),
+        }
+    }
+}
+
+impl Default for Preview {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Files `window` refers to, directly or through other files.
+fn references(window: &Window) -> Vec<Identifier> {
+    window
+        .outgoing_references()
+        .into_iter()
+        .filter_map(|(window_type, name)| Identifier::from_string(name, window_type?))
+        .collect()
+}
+
+/// History generation of `window` and of every file its preview can depend on, so editing any
+/// of them renders it again.
+fn generations<'a>(
+    window: &Window,
+    resolve: &'a dyn Fn(&Identifier) -> Option<&'a Window>,
+) -> String {
+    let mut visited = HashSet::new();
+    let mut pending = references(window);
+    let mut generations = format!("{} {}\n", window.identifier(), window.history.generation());
+    while let Some(id) = pending.pop() {
+        if !visited.insert(id.clone()) {
+            continue;
+        }
+        if let Some(dependency) = resolve(&id) {
+            generations += &format!("{} {}\n", id, dependency.history.generation());
+            pending.extend(references(dependency));
+        }
+    }
+    generations
+}
+
+/// Diverging color map, `t` in `-1.0..=1.0`: blue below zero (air), red above (solid).
+fn heat_color(t: f64) -> Color32 {
+    let t = t.clamp(-1., 1.);
+    let fade = (255. * (1. - t.abs())) as u8;
+    if t >= 0. {
+        Color32::from_rgb(255, fade, fade)
+    } else {
+        Color32::from_rgb(fade, fade, 255)
+    }
+}
+
+/// What the column preview shows.
+enum ColumnResult {
+    /// Blocks placed by a rule
+    Blocks(Vec<ColumnBlock>),
+    /// Where a condition holds, `None` above the ground
+    Condition(Vec<Option<bool>>),
+}
+
+/// Blocks the surface rule of noise settings (or its active node) places in a made up column.
+pub struct ColumnPreview {
+    pub column: Column,
+    /// highest y shown
+    pub top: i32,
+    /// number of blocks shown
+    pub rows: i32,
+    result: Option<ColumnResult>,
+    error: Option<String>,
+    /// everything the last evaluation depended on, evaluated again when it changes
+    key: Option<String>,
+}
+
+impl ColumnPreview {
+    pub fn new() -> Self {
+        Self {
+            column: Column {
+                x: 0,
+                z: 0,
+                min_y: -64,
+                height: 384,
+                surface_y: 70,
+                water_level: 63,
+                biome: "minecraft:plains".to_string(),
+                surface_depth: 4,
+                surface_secondary: 0.,
+                steep: false,
+                cold: false,
+                default_block: BlockState::new("minecraft:stone"),
+                default_fluid: BlockState::new("minecraft:water"),
+            },
+            top: 80,
+            rows: 64,
+            result: None,
+            error: None,
+            key: None,
+        }
+    }
+
+    fn ui<'a>(
+        &mut self,
+        ui: &mut Ui,
+        window: &Window,
+        seed: i64,
+        refresh: bool,
+        resolve: &'a dyn Fn(&Identifier) -> Option<&'a Window>,
+    ) {
+        self.read_noise_settings(window);
+        let column = &mut self.column;
+        egui::Grid::new("preview column")
+            .num_columns(2)
+            .show(ui, |ui| {
+                for (label, value) in [
+                    ("x", &mut column.x),
+                    ("z", &mut column.z),
+                    ("surface y", &mut column.surface_y),
+                    ("water level", &mut column.water_level),
+                    ("surface depth", &mut column.surface_depth),
+                ] {
+                    ui.label(label);
+                    ui.add(DragValue::new(value));
+                    ui.end_row();
+                }
+                ui.label("surface secondary");
+                ui.add(
+                    DragValue::new(&mut column.surface_secondary)
+                        .speed(0.01)
+                        .clamp_range(-1.0..=1.0),
+                );
+                ui.end_row();
+                ui.label("biome");
+                ui.text_edit_singleline(&mut column.biome);
+                ui.end_row();
+            });
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut column.steep, "steep");
+            ui.checkbox(&mut column.cold, "cold")
+                .on_hover_text("it snows at the surface, what the temperature condition checks");
+        });
+
+        let graph = &window.state.graph;
+        let node_id = window.user_state.active_node.filter(|id| {
+            graph.nodes.get(*id).is_some_and(|node| {
+                matches!(
+                    node.user_data.template,
+                    NodeTemplate::SurfaceRule(_) | NodeTemplate::SurfaceRuleCondition(_)
+                )
+            })
+        });
+        let key = format!(
+            "{} {:?} {} {:?}",
+            generations(window, resolve),
+            node_id,
+            seed,
+            self.column
+        );
+        if refresh || self.key.as_ref() != Some(&key) {
+            self.key = Some(key);
+            let mut compiler = Compiler::new(seed, resolve);
+            let result = match node_id {
+                Some(id)
+                    if matches!(
+                        graph[id].user_data.template,
+                        NodeTemplate::SurfaceRuleCondition(_)
+                    ) =>
+                {
+                    compiler
+                        .compile_condition(window, id)
+                        .map(|condition| ColumnResult::Condition(self.column.test(&condition)))
+                }
+                _ => compiler
+                    .compile_rule(window, node_id.unwrap_or(window.root_node))
+                    .map(|rule| ColumnResult::Blocks(self.column.blocks(&rule))),
+            };
+            match result {
+                Ok(result) => {
+                    self.error = None;
+                    self.result = Some(result);
+                }
+                Err(e) => {
+                    self.error = Some(e.to_string());
+                    self.result = None;
+                }
+            }
+        }
+        ui.label(match node_id {
+            Some(_) => "previewing active node",
+            None => "previewing surface rule",
+        });
+        if let Some(e) = &self.error {
+            ui.colored_label(Color32::RED, e);
+            return;
+        }
+        let result = match &self.result {
+            Some(result) => result,
+            None => return,
+        };
+        ui.horizontal(|ui| {
+            ui.label("top");
+            ui.add(DragValue::new(&mut self.top));
+            ui.label("blocks");
+            ui.add(DragValue::new(&mut self.rows).clamp_range(8..=384));
+        });
+
+        // What each row shows: its color, a description and the rule that placed the block
+        let min_y = self.column.min_y;
+        let rows: Vec<(i32, Color32, String, Option<NodeId>)> = (0..self.rows)
+            .map(|i| self.top - i)
+            .map(|y| {
+                let index = usize::try_from(y - min_y).ok();
+                let (color, text, rule) = match result {
+                    ColumnResult::Blocks(blocks) => match index.and_then(|i| blocks.get(i)) {
+                        Some(block) => (block.state.color(), describe(block), block.rule),
+                        None => (
+                            Color32::TRANSPARENT,
+                            "outside of the world".to_string(),
+                            None,
+                        ),
+                    },
+                    ColumnResult::Condition(tests) => {
+                        let (color, text) =
+                            match index.and_then(|i| tests.get(i)).copied().flatten() {
+                                Some(true) => (Color32::from_rgb(80, 200, 80), "true"),
+                                Some(false) => (Color32::from_rgb(200, 60, 60), "false"),
+                                None => (Color32::TRANSPARENT, "not ground"),
+                            };
+                        (color, text.to_string(), None)
+                    }
+                };
+                (y, color, text, rule)
+            })
+            .collect();
+
+        ui.horizontal_top(|ui| {
+            let row_height = ((ui.available_height() - 20.) / rows.len() as f32).clamp(2., 16.);
+            let (rect, response) = ui.allocate_exact_size(
+                Vec2::new(40., row_height * rows.len() as f32),
+                Sense::hover(),
+            );
+            let painter = ui.painter();
+            painter.rect_filled(rect, 0., Color32::from_gray(24));
+            for (i, (_, color, _, _)) in rows.iter().enumerate() {
+                let min = rect.min + Vec2::new(0., i as f32 * row_height);
+                painter.rect_filled(
+                    egui::Rect::from_min_size(min, Vec2::new(rect.width(), row_height)),
+                    0.,
+                    *color,
+                );
+            }
+            if let Some(pos) = response.hover_pos() {
+                let scroll = ui.input().scroll_delta.y;
+                self.top += (scroll / row_height).round() as i32;
+                let i = ((pos.y - rect.min.y) / row_height) as usize;
+                if let Some((y, _, text, _)) = rows.get(i) {
+                    response.on_hover_text(format!("{}: {}", y, text));
+                }
+            }
+            // Consecutive rows with the same block from the same rule, from the top
+            ui.vertical(|ui| {
+                let mut start = 0;
+                for i in 1..=rows.len() {
+                    if i < rows.len() && (&rows[i].2, rows[i].3) == (&rows[start].2, rows[start].3)
+                    {
+                        continue;
+                    }
+                    let (top, bottom) = (rows[start].0, rows[i - 1].0);
+                    let range = if top == bottom {
+                        top.to_string()
+                    } else {
+                        format!("{}..{}", bottom, top)
+                    };
+                    ui.label(format!("{}  {}", range, rows[start].2));
+                    start = i;
+                }
+            });
+        });
+    }
+
+    /// Takes the height of the world and the default blocks from the noise settings.
+    fn read_noise_settings(&mut self, window: &Window) {
+        let graph = &window.state.graph;
+        let node = match graph.nodes.get(window.root_node) {
+            Some(node) => node,
+            None => return,
+        };
+        let value = |name| {
+            node.get_input(name)
+                .ok()
+                .map(|in_id| graph.get_input(in_id).value())
+        };
+        if let Some(ValueType::Integer(min_y)) = value("min_y") {
+            self.column.min_y = *min_y;
+        }
+        if let Some(ValueType::Integer(height)) = value("height") {
+            self.column.height = *height;
+        }
+        if let Some(ValueType::Block(state)) = value("default_block") {
+            self.column.default_block = state.clone();
+        }
+        if let Some(ValueType::Block(state)) = value("default_fluid") {
+            self.column.default_fluid = state.clone();
+        }
+    }
+}
+
+impl Default for ColumnPreview {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Name of the block without the `minecraft` namespace, blocks no rule placed are marked.
+fn describe(block: &ColumnBlock) -> String {
+    let name = block.state.name.trim_start_matches("minecraft:");
+    match block.rule {
+        Some(_) => name.to_string(),
+        None => format!("{} (no rule)", name),
+    }
+}
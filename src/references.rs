@@ -0,0 +1,64 @@
+//! Checks `namespace:path` strings against the loaded project and vanilla files.
+
+use std::collections::HashSet;
+
+use crate::app::Identifier;
+use crate::vanilla;
+use crate::window::WindowType;
+
+/// Most suggestions shown below a reference field.
+pub const MAX_SUGGESTIONS: usize = 50;
+
+#[derive(Default)]
+pub struct References {
+    project: HashSet<Identifier>,
+}
+
+impl References {
+    pub fn new<'a>(ids: impl Iterator<Item = &'a Identifier>) -> Self {
+        Self {
+            project: ids.cloned().collect(),
+        }
+    }
+
+    pub fn in_project(&self, id: &Identifier) -> bool {
+        self.project.contains(id)
+    }
+
+    /// Whether the game would find what `name` points to.
+    pub fn resolves(&self, window_type: WindowType, name: &str) -> bool {
+        match Identifier::from_string(name.to_string(), window_type) {
+            Some(id) => self.in_project(&id) || id.is_vanilla(),
+            None => false,
+        }
+    }
+
+    /// `namespace:path` of every `window_type` file containing `filter`, project files first.
+    pub fn suggestions(&self, window_type: WindowType, filter: &str) -> Vec<String> {
+        let mut project: Vec<String> = self
+            .project
+            .iter()
+            .filter(|id| id.window_type() == window_type)
+            .map(|id| id.to_string())
+            .collect();
+        project.sort_unstable();
+        let vanilla = vanilla::paths(window_type)
+            .iter()
+            .map(|path| format!("minecraft:{}", path));
+        project
+            .into_iter()
+            .chain(vanilla)
+            .filter(|id| id.contains(filter))
+            .take(MAX_SUGGESTIONS)
+            .collect()
+    }
+}
+
+impl Identifier {
+    pub fn is_vanilla(&self) -> bool {
+        self.namespace() == "minecraft"
+            && vanilla::paths(self.window_type())
+                .binary_search(&self.path())
+                .is_ok()
+    }
+}
@@ -1,271 +1,702 @@
 use std::collections::HashSet;
 use std::str::FromStr;
 
-use eframe::epaint::Vec2;
+use eframe::epaint::{Color32, Vec2};
 use egui_node_graph::{InputId, InputParam, NodeId};
 use json::{self, object::Object, JsonValue};
-use log::{error, warn};
 
-use crate::app::App;
 use crate::errors::AppError;
+use crate::nodes::inner_data_types::biome::{
+    GrassColorModifier, Precipitation, TemperatureModifier,
+};
 use crate::nodes::inner_data_types::density_function::WeirdScaledSampleRarityValueMapper;
 use crate::nodes::inner_data_types::surface_rule_condition::{SurfaceType, VerticalAnchor};
 use crate::nodes::{
-    add_node, blocks,
+    add_node,
+    blocks::BlockState,
     data_types::{
-        increase_node_list_length, ComplexDataType, DataType, SwitchableInnerValueType, ValueType,
+        increase_node_list_length, list_entries, ComplexDataType, DataType,
+        SwitchableInnerValueType, ValueType,
     },
     inner_data_types::{
         density_function::DensityFunctionType, surface_rule::SurfaceRuleType,
         surface_rule_condition::SurfaceRuleConditionType, InnerDataType,
     },
     node_types::NodeTemplate,
-    rebuild_node,
+    rebuild_node, UnknownField,
 };
 use crate::window::{Window, WindowType};
 
+/// Sorts keys of every object in the editor's own order, `type` first and the rest in
+/// alphabetical order, so saved files don't change with the order nodes were edited in. The
+/// game doesn't care about key order and its own files don't follow this one.
+pub fn canonical(value: JsonValue) -> JsonValue {
+    match value {
+        JsonValue::Object(object) => {
+            let mut entries: Vec<(String, JsonValue)> = object
+                .iter()
+                .map(|(key, value)| (key.to_string(), canonical(value.clone())))
+                .collect();
+            entries.sort_by(|(a, _), (b, _)| (a != "type", a).cmp(&(b != "type", b)));
+            let mut sorted = Object::with_capacity(entries.len());
+            for (key, value) in entries {
+                sorted.insert(&key, value);
+            }
+            JsonValue::Object(sorted)
+        }
+        JsonValue::Array(array) => JsonValue::Array(array.into_iter().map(canonical).collect()),
+        value => value,
+    }
+}
+
 impl Window {
-    pub fn serialize(&self) -> Option<json::JsonValue> {
-        self.serialize_inner(self.root_node, &mut HashSet::new())
+    pub fn serialize(&self) -> Result<JsonValue, AppError> {
+        self.serialize_node(self.root_node)
+    }
+    /// Content of the file, with canonical key order and indentation.
+    pub fn serialize_to_string(&self) -> Result<String, AppError> {
+        let mut s = canonical(self.serialize()?).pretty(4);
+        s.push('\n');
+        Ok(s)
+    }
+    /// Serializes only the part of the graph starting at `node_id`.
+    pub fn serialize_node(&self, node_id: NodeId) -> Result<JsonValue, AppError> {
+        self.serialize_inner(node_id, &mut HashSet::new())
+            .map_err(|e| e.with_window(self.identifier()))
     }
 
-    fn serialize_inner(&self, node_id: NodeId, visited: &mut HashSet<NodeId>) -> Option<JsonValue> {
-        if visited.contains(&node_id) {
-            warn!("Circular graph cannot be serialized! {:?}", node_id);
-            return None;
+    /// `visited` holds the nodes on the path from the output, the same node may be used twice
+    /// but not inside of itself.
+    fn serialize_inner(
+        &self,
+        node_id: NodeId,
+        visited: &mut HashSet<NodeId>,
+    ) -> Result<JsonValue, AppError> {
+        if !visited.insert(node_id) {
+            return Err(AppError::Serialization("circular graph".into()).with_node(node_id));
         }
-        visited.insert(node_id);
+        let result = self.serialize_fields(node_id, visited);
+        visited.remove(&node_id);
+        result
+    }
+
+    fn serialize_fields(
+        &self,
+        node_id: NodeId,
+        visited: &mut HashSet<NodeId>,
+    ) -> Result<JsonValue, AppError> {
         let graph = &self.state.graph;
-        let node = graph.nodes.get(node_id)?;
+        let error = |message: String| AppError::Serialization(message.into()).with_node(node_id);
+        let node = graph
+            .nodes
+            .get(node_id)
+            .ok_or_else(|| error("node doesn't exist".to_string()))?;
 
         match node.user_data.template {
+            // The file is whatever is connected, or a plain number
             NodeTemplate::Output(WindowType::DensityFunction) => {
-                let (_, in_id) = node.inputs.first().unwrap();
-                if let Some(out_id) = graph.connection(*in_id) {
-                    let next_node = graph.get_output(out_id).node;
-                    return self.serialize_inner(next_node, visited);
-                } else {
-                    warn!("Nothing connected to output!");
-                    return None;
-                }
+                return match node.inputs.first() {
+                    Some((_, in_id)) => self
+                        .input_to_json_value(in_id, graph.get_input(*in_id), visited)?
+                        .ok_or_else(|| error("nothing connected to output".to_string())),
+                    None => Err(error("nothing connected to output".to_string())),
+                };
+            }
+            // References are written in place of the object they point to
+            NodeTemplate::Reference(_) => {
+                return match node
+                    .inputs
+                    .first()
+                    .map(|(_, in_id)| graph.get_input(*in_id))
+                {
+                    Some(input) => match input.value() {
+                        ValueType::Reference(_, name) => Ok(JsonValue::String(name.clone())),
+                        _ => Err(error("reference without a name".to_string())),
+                    },
+                    None => Err(error("reference without a name".to_string())),
+                };
+            }
+            // Constant blocks are written in place of the input they are connected to
+            NodeTemplate::ConstantBlock => {
+                return match node
+                    .inputs
+                    .first()
+                    .map(|(_, in_id)| graph.get_input(*in_id).value())
+                {
+                    Some(ValueType::Block(state)) => Ok(state.to_json()),
+                    _ => Err(error("block node without a block".to_string())),
+                };
+            }
+            NodeTemplate::RawJson(_) => {
+                return match node
+                    .inputs
+                    .first()
+                    .map(|(_, in_id)| graph.get_input(*in_id).value())
+                {
+                    Some(ValueType::RawJson(text)) => {
+                        json::parse(text).map_err(|e| error(format!("invalid JSON: {}", e)))
+                    }
+                    _ => Err(error("raw JSON node without a value".to_string())),
+                };
             }
             _ => {}
         }
         let mut o = Object::new();
+        // `Object` and `Tuple` inputs open a nested object or array that lasts for the next `len` entries
+        let mut nested: Option<(&str, JsonValue, usize)> = None;
 
-        for (n, (label, in_id)) in node.inputs.iter().enumerate() {
+        let mut inputs = node.inputs.iter();
+        while let Some((label, in_id)) = inputs.next() {
             let input = graph.get_input(*in_id);
 
-            if let DataType::List(_) = input.typ {
-                let rest = node
-                    .inputs
-                    .iter()
-                    .skip(n)
-                    .filter_map(|(_, o_id)| {
-                        let o_input = graph.get_input(*o_id);
-                        self.input_to_json_value(o_id, o_input, visited)
-                    })
-                    .collect();
-                o.insert(label, JsonValue::Array(rest));
-                break;
-            } else {
-                let value = self.input_to_json_value(in_id, input, visited);
-                if let Some(val) = value {
-                    o.insert(label, val);
-                } else {
-                    warn!(
-                        "Error serializing value {} at node: {:?} ({})",
-                        label, node_id, node.label
-                    );
-                    return None;
+            let value = match (input.typ, input.value()) {
+                (DataType::Object, ValueType::Object(len)) => {
+                    nested = Some((label, JsonValue::new_object(), *len));
+                    continue;
+                }
+                (DataType::Tuple, ValueType::Tuple(len)) => {
+                    nested = Some((label, JsonValue::new_array(), *len));
+                    continue;
+                }
+                (DataType::List(_) | DataType::Map(_), _) => {
+                    // Entries of a list are the unnamed inputs that follow it
+                    let len = inputs
+                        .clone()
+                        .take_while(|(name, _)| name.is_empty())
+                        .count();
+                    let entries = inputs.by_ref().take(len).map(|(_, id)| *id);
+                    if let DataType::Map(_) = input.typ {
+                        Some(self.map_to_json_value(entries, visited)?)
+                    } else {
+                        // Unconnected entries are skipped
+                        let mut array = Vec::new();
+                        for e_id in entries {
+                            match self.input_to_json_value(&e_id, graph.get_input(e_id), visited)? {
+                                Some(value) if !value.is_null() => array.push(value),
+                                _ => {}
+                            }
+                        }
+                        let single_id = node.user_data.single_id_lists.iter().any(|l| l == label);
+                        // Biomes leave out the carving steps they have no carvers for
+                        if array.is_empty()
+                            && input.typ == DataType::List(ComplexDataType::ConfiguredCarver)
+                        {
+                            Some(JsonValue::Null)
+                        } else if single_id && array.len() == 1 && array[0].is_string() {
+                            Some(array.remove(0))
+                        } else {
+                            Some(JsonValue::Array(array))
+                        }
+                    }
+                }
+                _ => self.input_to_json_value(in_id, input, visited)?,
+            };
+            let value = match value {
+                Some(value) => value,
+                None => return Err(error(format!("{} ({}) is missing", label, node.label))),
+            };
+            // Null stands for an optional field that is left out
+            match nested.as_mut() {
+                Some((_, inner, len)) => {
+                    if inner.is_array() {
+                        inner.push(value).map_err(AppError::JsonError)?;
+                    } else if !value.is_null() {
+                        inner.insert(label, value).map_err(AppError::JsonError)?;
+                    }
+                    *len -= 1;
+                }
+                None if !value.is_null() => o.insert(label, value),
+                None => {}
+            }
+            if let Some((name, inner, 0)) = &nested {
+                o.insert(name, inner.clone());
+                nested = None;
+            }
+        }
+        // Fields that couldn't be read win over the inputs they were meant for
+        for field in &node.user_data.unknown_fields {
+            let value = json::parse(&field.value)
+                .map_err(|e| error(format!("unknown field {}: {}", field.key, e)))?;
+            match &field.parent {
+                Some(parent) => {
+                    if !matches!(o.get(parent), Some(inner) if inner.is_object()) {
+                        o.insert(parent, JsonValue::new_object());
+                    }
+                    if let Some(inner) = o.get_mut(parent) {
+                        inner[field.key.as_str()] = value;
+                    }
                 }
+                None => o.insert(&field.key, value),
+            }
+        }
+        Ok(JsonValue::Object(o))
+    }
+
+    /// Writes entries of a `DataType::Map`, keyed by the first input of every connected node.
+    fn map_to_json_value(
+        &self,
+        entries: impl Iterator<Item = InputId>,
+        visited: &mut HashSet<NodeId>,
+    ) -> Result<JsonValue, AppError> {
+        let graph = &self.state.graph;
+        let mut map = Object::new();
+        for in_id in entries {
+            let next_node = match graph.connection(in_id) {
+                Some(out_id) => graph.get_output(out_id).node,
+                None => continue,
             };
+            let mut value = self.serialize_inner(next_node, visited)?;
+            let key = graph[next_node]
+                .inputs
+                .first()
+                .map(|(key_label, _)| value.remove(key_label))
+                .filter(|key| key.is_string())
+                .ok_or_else(|| {
+                    AppError::Serialization("map entry without a key".into()).with_node(next_node)
+                })?;
+            map.insert(key.as_str().unwrap(), value);
         }
-        Some(JsonValue::Object(o))
+        Ok(JsonValue::Object(map))
     }
 
-    pub fn deserialize(&mut self, s: &JsonValue) {
-        let root_id = self.root_node;
+    /// Builds the graph from `s`, parts that can't be read are left out and returned as errors.
+    pub fn deserialize(&mut self, s: &JsonValue) -> Vec<AppError> {
+        let errors = self.deserialize_nodes(s);
+        // New nodes are all placed on the root node until now
+        self.arrange_all();
+        self.arrange_when_drawn = true;
+        let root_pos = self
+            .state
+            .node_positions
+            .get(self.root_node)
+            .copied()
+            .unwrap_or_default();
+        self.state.pan_zoom.pan = Vec2::new(500., 50.) - root_pos.to_vec2();
+        errors
+    }
 
-        let rightmost_pos = self.state.node_positions.get(root_id).unwrap().clone();
-        let leftmost_vec: Vec2;
+    /// Like `deserialize`, but the new nodes are left on the node they are connected to and
+    /// the view doesn't move.
+    pub(crate) fn deserialize_nodes(&mut self, s: &JsonValue) -> Vec<AppError> {
+        let root_id = self.root_node;
+        let mut errors = Vec::new();
 
-        let (label, input_id) = &self.state.graph.nodes.get(root_id).unwrap().inputs[0];
+        let root_input = self.state.graph.nodes.get(root_id).and_then(|root| {
+            let (label, input_id) = root.inputs.first()?;
+            Some((label.clone(), *input_id))
+        });
+        let (label, input_id) = match root_input {
+            Some(root_input) => root_input,
+            None => {
+                return vec![AppError::WrongData("the output node is missing".into())
+                    .with_window(self.identifier())]
+            }
+        };
 
-        // If root node has only `out` input, create a new node and connect
+        // A root node with only an `output` input gets the file from what's connected to it
         if label == "output" {
-            let template = self
-                .state
-                .graph
-                .get_input(*input_id)
-                .typ
-                .defualt_NodeTemplate()
-                .unwrap();
-
-            let next = add_node(
-                &mut self.state,
-                &mut self.user_state,
-                template,
-                rightmost_pos + Vec2::new(-250., 0.),
-            );
-            let input_id = self.state.graph.nodes.get(root_id).unwrap().inputs[0].1;
-            let output_id = self
-                .state
-                .graph
-                .nodes
-                .get(next)
-                .unwrap()
-                .outputs
-                .last()
-                .unwrap()
-                .1;
-            self.state.graph.add_connection(output_id, input_id);
-
-            leftmost_vec = self.deserialize_inner(s, &next);
+            let data_type = self.state.graph.get_input(input_id).typ;
+            let result =
+                self.deserialize_input(s, &data_type, None, &root_id, input_id, "$", &mut errors);
+            if let Err(e) = result {
+                errors.push(e.with_json_path("$").with_node(root_id));
+            }
         } else {
-            leftmost_vec = self.deserialize_inner(s, &root_id);
+            self.deserialize_inner(s, &root_id, "$", &mut errors);
         }
-        self.state.pan_zoom.pan = (rightmost_pos.to_vec2() + leftmost_vec) / 2.0
+        let id = self.identifier();
+        errors
+            .into_iter()
+            .map(|e| e.with_window(id.clone()))
+            .collect()
     }
-    fn deserialize_inner(&mut self, s: &JsonValue, node_id: &NodeId) -> Vec2 {
-        let root = self.state.graph.nodes.get(*node_id).unwrap();
-        if let Some((_, entry)) = s.entries().find(|(label, _)| *label == "type") {
-            if let Ok(ValueType::InnerTypeSwitch(value_type)) = Self::json_value_to_value_type(
+    /// `path` is the JSON path of `s`, used for errors.
+    pub(crate) fn deserialize_inner(
+        &mut self,
+        s: &JsonValue,
+        node_id: &NodeId,
+        path: &str,
+        errors: &mut Vec<AppError>,
+    ) {
+        let root = match self.state.graph.nodes.get(*node_id) {
+            Some(root) => root,
+            None => return,
+        };
+        if !s.is_object() {
+            if !self.make_raw_node(*node_id, s) {
+                errors.push(
+                    AppError::WrongData(format!("expected an object, found {}", s).into())
+                        .with_json_path(path)
+                        .with_node(*node_id),
+                );
+            }
+            return;
+        }
+        // `type` is only a type switch on nodes that have one, elsewhere it's an ordinary field
+        let has_switch = root.get_input("type").is_ok_and(|in_id| {
+            self.state.graph.get_input(in_id).typ == DataType::ValueTypeSwitcher
+        });
+        if let (true, Some((_, entry))) =
+            (has_switch, s.entries().find(|(label, _)| *label == "type"))
+        {
+            match Self::json_value_to_value_type(
                 entry,
                 &DataType::ValueTypeSwitcher,
                 &root.user_data.template,
             ) {
-                rebuild_node(
+                // Types the pack's version doesn't have are kept as they are, like unknown ones
+                Ok(ValueType::InnerTypeSwitch(value_type))
+                    if !self
+                        .user_state
+                        .version
+                        .supports(value_type.to_NodeTemplate()) =>
+                {
+                    if !self.make_raw_node(*node_id, s) {
+                        errors.push(
+                            AppError::WrongData(
+                                format!("{} isn't read by this version", entry).into(),
+                            )
+                            .with_json_path(&format!("{}.type", path))
+                            .with_node(*node_id),
+                        );
+                    }
+                    return;
+                }
+                Ok(ValueType::InnerTypeSwitch(value_type)) => rebuild_node(
                     *node_id,
                     &mut self.state.graph,
                     &mut self.user_state,
                     value_type.to_NodeTemplate(),
-                )
+                ),
+                Ok(_) => {}
+                // Types the editor doesn't know yet are kept as they are
+                Err(e) => {
+                    if !self.make_raw_node(*node_id, s) {
+                        errors.push(
+                            e.with_json_path(&format!("{}.type", path))
+                                .with_node(*node_id),
+                        );
+                    }
+                    return;
+                }
             }
         }
 
-        let mut leftmost_vec = self.state.node_positions.get(*node_id).unwrap().to_vec2();
-
-        let root = self.state.graph.nodes.get(*node_id).unwrap().clone();
-        let template = &root.user_data.template;
-        for (i, (entry, json_value)) in s
+        let root = self.state.graph.nodes[*node_id].clone();
+        // Entries of nested objects and tuples are inputs of this same node, they are listed
+        // together with the name of the input they were found in
+        let entries = s
             .entries()
-            .filter(|(label, _)| *label != "type")
-            .enumerate()
-        {
-            if let Ok(input_id) = root.get_input(entry) {
-                let input = self.state.graph.get_input(input_id).clone();
-
-                if json_value.is_object() && input.typ.defualt_NodeTemplate().is_some() {
-                    let curr_pos = *self.state.node_positions.get(*node_id).unwrap();
-                    let next = add_node(
-                        &mut self.state,
-                        &mut self.user_state,
-                        input.typ.defualt_NodeTemplate().unwrap(),
-                        curr_pos + Vec2::new(-250., 200. * i as f32),
-                    );
-
-                    let output_id = self
-                        .state
-                        .graph
-                        .nodes
-                        .get(next)
-                        .unwrap()
-                        .outputs
-                        .last()
-                        .unwrap()
-                        .1;
-
-                    self.state.graph.add_connection(output_id, input_id);
-
-                    let new_vec = self.deserialize_inner(json_value, &next);
-                    if new_vec.x < leftmost_vec.x {
-                        leftmost_vec = new_vec;
-                    }
-                } else if json_value.is_array() {
-                    if let DataType::ValuesArray = input.typ {
-                        if let Ok(value) = Self::json_value_to_value_type(
-                            json_value,
-                            &DataType::ValuesArray,
-                            template,
-                        ) {
-                            let input_mut = self.state.graph.inputs.get_mut(input_id).unwrap();
-                            input_mut.value = value;
-                        }
-                    } else if let DataType::List(x) = input.typ {
-                        if let Ok(value) =
-                            Self::json_value_to_value_type(json_value, &DataType::List(x), template)
-                        {
-                            let input_mut = self.state.graph.inputs.get_mut(input_id).unwrap();
-                            input_mut.value = value;
-
-                            for item in json_value.members() {
-                                let new_input_id =
-                                    increase_node_list_length(&mut self.state.graph, *node_id);
-                                let data_type = DataType::Single(x);
-
-                                if item.is_object() && data_type.defualt_NodeTemplate().is_some() {
-                                    let curr_pos =
-                                        *self.state.node_positions.get(*node_id).unwrap();
+            .filter(|(label, _)| !has_switch || *label != "type")
+            .flat_map(|(label, json_value)| {
+                let typ = root
+                    .get_input(label)
+                    .map(|in_id| self.state.graph.get_input(in_id).typ);
+                match typ {
+                    Ok(DataType::Object) if json_value.is_object() => json_value
+                        .entries()
+                        .map(|(key, value)| (Some(label), key, value))
+                        .collect(),
+                    Ok(DataType::Tuple) if json_value.is_array() => root
+                        .inputs
+                        .iter()
+                        .skip_while(|(name, _)| name != label)
+                        .skip(1)
+                        .filter(|(name, _)| !name.is_empty())
+                        .map(|(name, _)| (Some(label), name.as_str(), json_value))
+                        .zip(json_value.members())
+                        .map(|((parent, name, _), value)| (parent, name, value))
+                        .collect(),
+                    _ => vec![(None, label, json_value)],
+                }
+            })
+            .collect::<Vec<_>>();
+        for (parent, entry, json_value) in entries {
+            let entry_path = match parent {
+                Some(parent) => format!("{}.{}.{}", path, parent, entry),
+                None => format!("{}.{}", path, entry),
+            };
+            let input_id = match root.get_input(entry) {
+                Ok(input_id) => input_id,
+                Err(_) => {
+                    self.keep_unknown_field(*node_id, parent, entry, json_value);
+                    continue;
+                }
+            };
+            let input = self.state.graph.get_input(input_id).clone();
+            // Set if the value couldn't be read, the list a bad entry is in is kept as a whole
+            let mut failed = None;
 
-                                    let next = add_node(
-                                        &mut self.state,
-                                        &mut self.user_state,
-                                        data_type.defualt_NodeTemplate().unwrap(),
-                                        curr_pos + Vec2::new(-250., 200. * i as f32),
-                                    );
-                                    let output_id = self
-                                        .state
-                                        .graph
-                                        .nodes
-                                        .get(next)
-                                        .unwrap()
-                                        .outputs
-                                        .last()
-                                        .unwrap()
-                                        .1;
-                                    self.state.graph.add_connection(output_id, new_input_id);
+            if let DataType::List(x) | DataType::Map(x) = input.typ {
+                // Maps are lists of nodes that hold their key as the first input
+                let single_id = json_value.is_string() && x.in_holder_sets();
+                let items = match input.typ {
+                    DataType::Map(_) => json_value
+                        .entries()
+                        .map(|(key, item)| (Some(key), item))
+                        .collect::<Vec<_>>(),
+                    _ if single_id => vec![(None, json_value)],
+                    _ => json_value.members().map(|item| (None, item)).collect(),
+                };
+                if single_id {
+                    self.state.graph[*node_id]
+                        .user_data
+                        .single_id_lists
+                        .push(entry.to_string());
+                }
+                if items.is_empty() && !(json_value.is_array() || json_value.is_object()) {
+                    failed = Some((
+                        AppError::WrongData(format!("{} is not a list", json_value).into()),
+                        entry_path.clone(),
+                    ));
+                }
+                // The node is built with one list entry already in place
+                self.state.graph[input_id].value = ValueType::List(items.len().max(1) as i32);
+                let first_id = match list_entries(&self.state.graph, *node_id, entry) {
+                    Some((position, _)) => self.state.graph[*node_id].inputs[position + 1].1,
+                    None => continue,
+                };
 
-                                    let new_vec = self.deserialize_inner(item, &next);
-                                    if new_vec.x < leftmost_vec.x {
-                                        leftmost_vec = new_vec;
-                                    }
-                                } else {
-                                    match Self::json_value_to_value_type(item, &data_type, template)
-                                    {
-                                        Ok(value_type) => {
-                                            self.state
-                                                .graph
-                                                .inputs
-                                                .get_mut(new_input_id)
-                                                .unwrap()
-                                                .value = value_type
-                                        }
-                                        Err(e) => error!("{}", e),
-                                    }
-                                }
+                for (j, (key, item)) in items.into_iter().enumerate() {
+                    let new_input_id = if j == 0 {
+                        first_id
+                    } else {
+                        match increase_node_list_length(&mut self.state.graph, *node_id, entry) {
+                            Some(new_input_id) => new_input_id,
+                            None => {
+                                failed = Some((
+                                    AppError::WrongData(
+                                        format!("couldn't add entry {} to {}", j, entry).into(),
+                                    ),
+                                    entry_path.clone(),
+                                ));
+                                break;
                             }
                         }
-                    }
-                } else {
-                    if let Ok(value) =
-                        Self::json_value_to_value_type(json_value, &input.typ, template)
-                    {
-                        let input_mut = self.state.graph.inputs.get_mut(input_id).unwrap();
-                        input_mut.value = value;
+                    };
+                    let item_path = match key {
+                        Some(key) => format!("{}.{}", entry_path, key),
+                        None => format!("{}[{}]", entry_path, j),
+                    };
+                    if let Err(e) = self.deserialize_input(
+                        item,
+                        &DataType::Single(x),
+                        key,
+                        node_id,
+                        new_input_id,
+                        &item_path,
+                        errors,
+                    ) {
+                        failed = Some((e, item_path));
                     }
                 }
+            } else if matches!(input.typ, DataType::Object | DataType::Tuple) {
+                failed = Some((
+                    AppError::WrongData(format!("{} is not an object", json_value).into()),
+                    entry_path.clone(),
+                ));
             } else {
-                error!("Wrong json data! \n{}", s);
+                if let Err(e) = self.deserialize_input(
+                    json_value,
+                    &input.typ,
+                    None,
+                    node_id,
+                    input_id,
+                    &entry_path,
+                    errors,
+                ) {
+                    failed = Some((e, entry_path.clone()));
+                }
+            }
+            // What couldn't be read is written back as it was
+            if let Some((e, path)) = failed {
+                errors.push(e.with_json_path(&path).with_node(*node_id));
+                self.keep_unknown_field(*node_id, parent, entry, json_value);
             }
         }
-        leftmost_vec
+    }
+
+    /// Fills `input_id` from `json_value`, objects become connected nodes and names of files
+    /// become reference nodes. Errors of connected nodes go to `errors`.
+    #[allow(clippy::too_many_arguments)]
+    fn deserialize_input(
+        &mut self,
+        json_value: &JsonValue,
+        data_type: &DataType,
+        key: Option<&str>,
+        node_id: &NodeId,
+        input_id: InputId,
+        path: &str,
+        errors: &mut Vec<AppError>,
+    ) -> Result<(), AppError> {
+        if let (Some(name), DataType::Single(cdt)) = (json_value.as_str(), data_type) {
+            if let Some(window_type) = cdt.reference_window_type() {
+                self.add_reference_node(name, window_type, node_id, input_id);
+                return Ok(());
+            }
+        }
+        if json_value.is_object() && data_type.defualt_NodeTemplate().is_some() {
+            self.add_connected_node(json_value, data_type, key, node_id, input_id, path, errors);
+            return Ok(());
+        }
+        let template = self.state.graph[*node_id].user_data.template;
+        match (
+            Self::json_value_to_value_type(json_value, data_type, &template),
+            data_type,
+        ) {
+            (Ok(value), _) => {
+                self.state.graph[input_id].value = value;
+                Ok(())
+            }
+            // Kept in a node of its own, instead of the one the editor would make for it
+            (Err(_), DataType::Single(cdt)) => {
+                self.add_raw_node(json_value, *cdt, node_id, input_id);
+                Ok(())
+            }
+            (Err(e), _) => Err(e),
+        }
+    }
+
+    /// Adds the default node of `data_type`, connects it to `input_id` and fills it from `json_value`.
+    /// `key` of a map entry goes to the first input of the new node.
+    #[allow(clippy::too_many_arguments)]
+    fn add_connected_node(
+        &mut self,
+        json_value: &JsonValue,
+        data_type: &DataType,
+        key: Option<&str>,
+        node_id: &NodeId,
+        input_id: InputId,
+        path: &str,
+        errors: &mut Vec<AppError>,
+    ) {
+        let template = match data_type.defualt_NodeTemplate() {
+            Some(template) => template,
+            None => return,
+        };
+        let curr_pos = self
+            .state
+            .node_positions
+            .get(*node_id)
+            .copied()
+            .unwrap_or_default();
+        let next = add_node(&mut self.state, &mut self.user_state, template, curr_pos);
+        if let Some((_, output_id)) = self.state.graph[next].outputs.last() {
+            self.state.graph.add_connection(*output_id, input_id);
+        }
+
+        match key {
+            Some(key) => {
+                let mut json_value = json_value.clone();
+                let (key_label, _) = &self.state.graph[next].inputs[0];
+                json_value.insert(key_label, key).ok();
+                self.deserialize_inner(&json_value, &next, path, errors)
+            }
+            None => self.deserialize_inner(json_value, &next, path, errors),
+        }
+    }
+
+    /// Adds a `NodeTemplate::RawJson` node holding `json_value` and connects it to `input_id`.
+    fn add_raw_node(
+        &mut self,
+        json_value: &JsonValue,
+        cdt: ComplexDataType,
+        node_id: &NodeId,
+        input_id: InputId,
+    ) {
+        let curr_pos = self
+            .state
+            .node_positions
+            .get(*node_id)
+            .copied()
+            .unwrap_or_default();
+        let next = add_node(
+            &mut self.state,
+            &mut self.user_state,
+            NodeTemplate::RawJson(cdt),
+            curr_pos,
+        );
+        let node = &self.state.graph[next];
+        let (json_id, output_id) = (node.inputs[0].1, node.outputs[0].1);
+        self.state.graph[json_id].value = ValueType::RawJson(json_value.pretty(2));
+        self.state.graph.add_connection(output_id, input_id);
+    }
+
+    /// Turns `node_id` into a `NodeTemplate::RawJson` node holding `json_value`, keeping its
+    /// output connection. Returns `false` for nodes that aren't an object of their own.
+    fn make_raw_node(&mut self, node_id: NodeId, json_value: &JsonValue) -> bool {
+        let graph = &self.state.graph;
+        let cdt = match graph[node_id]
+            .output_ids()
+            .next()
+            .map(|id| graph.get_output(id).typ)
+        {
+            Some(DataType::Single(cdt)) => cdt,
+            _ => return false,
+        };
+        rebuild_node(
+            node_id,
+            &mut self.state.graph,
+            &mut self.user_state,
+            NodeTemplate::RawJson(cdt),
+        );
+        let node = &mut self.state.graph[node_id];
+        node.user_data.unknown_fields.clear();
+        node.user_data.single_id_lists.clear();
+        if let Some((_, json_id)) = node.inputs.first() {
+            let json_id = *json_id;
+            self.state.graph[json_id].value = ValueType::RawJson(json_value.pretty(2));
+        }
+        true
+    }
+
+    /// Stores a field `node_id` can't hold in its inputs, so it's written back when saving.
+    fn keep_unknown_field(
+        &mut self,
+        node_id: NodeId,
+        parent: Option<&str>,
+        key: &str,
+        json_value: &JsonValue,
+    ) {
+        if let Some(node) = self.state.graph.nodes.get_mut(node_id) {
+            node.user_data.unknown_fields.push(UnknownField {
+                parent: parent.map(str::to_string),
+                key: key.to_string(),
+                value: json_value.dump(),
+            });
+        }
+    }
+
+    /// Adds a `NodeTemplate::Reference` node holding `name` and connects it to `input_id`.
+    fn add_reference_node(
+        &mut self,
+        name: &str,
+        window_type: WindowType,
+        node_id: &NodeId,
+        input_id: InputId,
+    ) {
+        let curr_pos = self
+            .state
+            .node_positions
+            .get(*node_id)
+            .copied()
+            .unwrap_or_default();
+        let next = add_node(
+            &mut self.state,
+            &mut self.user_state,
+            NodeTemplate::Reference(window_type),
+            curr_pos,
+        );
+        let node = &self.state.graph[next];
+        let (reference_id, output_id) = (node.inputs[0].1, node.outputs[0].1);
+        self.state.graph[reference_id].value = ValueType::Reference(window_type, name.to_string());
+        self.state.graph.add_connection(output_id, input_id);
     }
 
     /// Returns the `ValueType` variant and nothing else (complex data types must be taken care of elsewhere)
     ///  - for List returns `ValueType::List(N)`, N = Length of json array
     ///  - for Complex returns equivalent `ValueType`
-    ///  - TODO: for Block
+    ///  - for Block returns the block state
     ///
     /// This method is not recursive.
     fn json_value_to_value_type(
@@ -276,23 +707,21 @@ impl Window {
         match data_type {
             DataType::Value => {
                 let value = value
-                    .as_f32()
-                    .ok_or(AppError::JsonError(json::Error::wrong_type("f32")))?;
+                    .as_f64()
+                    .ok_or(AppError::JsonError(json::Error::wrong_type("f64")))?;
                 Ok(ValueType::Value(value))
             }
-            DataType::Block => {
-                // TODO: internal represenation of a block needs to be rethinked.
-                todo!()
-            }
+            // Either a block state object or just the name of a block
+            DataType::Block => BlockState::from_json(value).map(ValueType::Block),
             DataType::ValuesArray => {
                 if !value.is_array() {
-                    Err(AppError::JsonError(json::Error::wrong_type("Vec<f32>")))
+                    Err(AppError::JsonError(json::Error::wrong_type("Vec<f64>")))
                 } else {
-                    if value.members().any(|val| val.as_f32().is_none()) {
-                        Err(AppError::JsonError(json::Error::wrong_type("Vec<f32>")))
+                    if value.members().any(|val| val.as_f64().is_none()) {
+                        Err(AppError::JsonError(json::Error::wrong_type("Vec<f64>")))
                     } else {
                         Ok(ValueType::ValuesArray(
-                            value.members().filter_map(|val| val.as_f32()).collect(),
+                            value.members().filter_map(|val| val.as_f64()).collect(),
                         ))
                     }
                 }
@@ -340,7 +769,9 @@ impl Window {
                         }
                     }
                     //TODO: REMEMBER TO ADD ALL NEW NODE TYPES HERE IF NECESSARY
-                    _ => unimplemented!("{:?}", node_type),
+                    _ => Err(AppError::WrongData(
+                        format!("{:?} has no type to switch", node_type).into(),
+                    )),
                 }
             }
             DataType::List(_) => {
@@ -351,17 +782,58 @@ impl Window {
                     Err(AppError::WrongData("Arr".into()))
                 }
             }
+            DataType::Map(_) => {
+                if value.is_object() {
+                    Ok(ValueType::List(value.len() as i32))
+                } else {
+                    Err(AppError::WrongData("Obj".into()))
+                }
+            }
+            // Objects are read into connected nodes, whatever else is here would be lost
+            DataType::Single(
+                ComplexDataType::SurfaceRule
+                | ComplexDataType::SurfaceRuleCondition
+                | ComplexDataType::Noise
+                | ComplexDataType::Biome,
+            ) if !value.is_object() => Err(AppError::WrongData(
+                format!("expected an object, found {}", value).into(),
+            )),
             DataType::Single(x) => Ok(match x {
                 ComplexDataType::Noise => ValueType::Noise,
-                ComplexDataType::DensityFunction => ValueType::DensityFunction,
+                ComplexDataType::DensityFunction => ValueType::Value(
+                    value
+                        .as_f64()
+                        .ok_or(AppError::JsonError(json::Error::wrong_type("f64")))?,
+                ),
                 ComplexDataType::SurfaceRule => ValueType::SurfaceRule,
                 ComplexDataType::SurfaceRuleCondition => ValueType::SurfaceRuleCondition,
                 ComplexDataType::Biome => ValueType::Biome,
+                ComplexDataType::Spline => ValueType::Value(
+                    value
+                        .as_f64()
+                        .ok_or(AppError::JsonError(json::Error::wrong_type("f64")))?,
+                ),
+                ComplexDataType::SplinePoint => ValueType::SplinePoint,
+                ComplexDataType::ParameterPoint => ValueType::ParameterPoint,
+                ComplexDataType::MoodSound => ValueType::MoodSound,
+                ComplexDataType::AdditionsSound => ValueType::AdditionsSound,
+                ComplexDataType::Music => ValueType::Music,
+                ComplexDataType::Particle => ValueType::Particle,
+                ComplexDataType::PlacedFeature | ComplexDataType::ConfiguredCarver => {
+                    ValueType::DullReference(
+                        value
+                            .as_str()
+                            .ok_or(AppError::JsonError(json::Error::wrong_type("str")))?
+                            .to_string(),
+                    )
+                }
+                ComplexDataType::Spawner => ValueType::Spawner,
+                ComplexDataType::SpawnCost => ValueType::SpawnCost,
             }),
             DataType::Integer => {
                 let value = value
                     .as_i32()
-                    .ok_or(AppError::JsonError(json::Error::wrong_type("f32")))?;
+                    .ok_or(AppError::JsonError(json::Error::wrong_type("i32")))?;
                 Ok(ValueType::Integer(value))
             }
             DataType::WeirdScaledSampleRarityValueMapper => {
@@ -369,7 +841,7 @@ impl Window {
                     .as_str()
                     .ok_or(AppError::JsonError(json::Error::wrong_type("str")))?;
                 let value = WeirdScaledSampleRarityValueMapper::from_str(value)
-                    .map_err(|e| AppError::WrongData(value.into()))?;
+                    .map_err(|_e| AppError::WrongData(value.into()))?;
                 Ok(ValueType::WeirdScaledSampleRarityValueMapper(value))
             }
             DataType::Bool => {
@@ -390,7 +862,7 @@ impl Window {
                     .next()
                     .ok_or(AppError::JsonError(json::Error::wrong_type("obj")))?;
                 let label =
-                    VerticalAnchor::from_str(key).map_err(|e| AppError::WrongData(key.into()))?;
+                    VerticalAnchor::from_str(key).map_err(|_e| AppError::WrongData(key.into()))?;
                 let value = json_value
                     .as_i32()
                     .ok_or(AppError::JsonError(json::Error::wrong_type("i32")))?;
@@ -401,25 +873,70 @@ impl Window {
                     .as_str()
                     .ok_or(AppError::JsonError(json::Error::wrong_type("str")))?;
                 let value =
-                    SurfaceType::from_str(value).map_err(|e| AppError::WrongData(value.into()))?;
+                    SurfaceType::from_str(value).map_err(|_e| AppError::WrongData(value.into()))?;
                 Ok(ValueType::SurfaceType(value))
             }
+            DataType::Range => {
+                if let Some(value) = value.as_f64() {
+                    return Ok(ValueType::Range(value, value));
+                }
+                match (value.len(), value[0].as_f64(), value[1].as_f64()) {
+                    (2, Some(min), Some(max)) if value.is_array() => Ok(ValueType::Range(min, max)),
+                    _ => Err(AppError::JsonError(json::Error::wrong_type("[f64; 2]"))),
+                }
+            }
+            DataType::TemperatureModifier => {
+                let value = value
+                    .as_str()
+                    .ok_or(AppError::JsonError(json::Error::wrong_type("str")))?;
+                let value = TemperatureModifier::from_str(value)
+                    .map_err(|_e| AppError::WrongData(value.into()))?;
+                Ok(ValueType::TemperatureModifier(value))
+            }
+            DataType::GrassColorModifier => {
+                let value = value
+                    .as_str()
+                    .ok_or(AppError::JsonError(json::Error::wrong_type("str")))?;
+                let value = GrassColorModifier::from_str(value)
+                    .map_err(|_e| AppError::WrongData(value.into()))?;
+                Ok(ValueType::GrassColorModifier(value))
+            }
+            DataType::Precipitation => {
+                let value = value
+                    .as_str()
+                    .ok_or(AppError::JsonError(json::Error::wrong_type("str")))?;
+                let value = Precipitation::from_str(value)
+                    .map_err(|_e| AppError::WrongData(value.into()))?;
+                Ok(ValueType::Precipitation(value))
+            }
+            DataType::Color => {
+                let value = value
+                    .as_u32()
+                    .ok_or(AppError::JsonError(json::Error::wrong_type("u32")))?;
+                let [_, r, g, b] = value.to_be_bytes();
+                Ok(ValueType::Color(Some(Color32::from_rgb(r, g, b))))
+            }
+            DataType::Object | DataType::Tuple => {
+                Err(AppError::WrongData("objects are not values".into()))
+            }
+            DataType::RawJson => Ok(ValueType::RawJson(value.pretty(2))),
         }
     }
-    /// This method recursively calls `self.serialize_inner()`.
+    /// This method recursively calls `self.serialize_inner()`. Returns `None` if the input holds
+    /// no value.
     fn input_to_json_value(
         &self,
         in_id: &InputId,
         input: &InputParam<DataType, ValueType>,
         visited: &mut HashSet<NodeId>,
-    ) -> Option<JsonValue> {
+    ) -> Result<Option<JsonValue>, AppError> {
         let graph = &self.state.graph;
         let connected_node = graph.connection(*in_id);
         if let Some(out_id) = connected_node {
             let next_node = graph.get_output(out_id).node;
-            self.serialize_inner(next_node, visited)
+            self.serialize_inner(next_node, visited).map(Some)
         } else {
-            match input.typ {
+            Ok(match input.typ {
                 DataType::Value => {
                     if let ValueType::Value(x) = input.value() {
                         Some(JsonValue::from(*x))
@@ -427,13 +944,10 @@ impl Window {
                         None
                     }
                 }
-                DataType::Block => {
-                    if let ValueType::Block(x) = input.value() {
-                        Some(JsonValue::String(blocks::BLOCK_LIST[*x].id.to_string()))
-                    } else {
-                        None
-                    }
-                }
+                DataType::Block => match input.value() {
+                    ValueType::Block(state) => Some(state.to_json()),
+                    _ => None,
+                },
                 DataType::ValuesArray => {
                     if let ValueType::ValuesArray(x) = input.value() {
                         Some(JsonValue::Array(
@@ -445,12 +959,40 @@ impl Window {
                         None
                     }
                 }
-                // This should never happen
-                DataType::List(_) => unimplemented!(),
                 // Not a blanket `unimplemented!` as we may want to add some cdt that has constant type of input, a.k.a non empty ValueType.
                 DataType::Single(cdt) => match cdt {
-                    ComplexDataType::DensityFunction => Some(JsonValue::from(0.0)),
-                    _ => unimplemented!(),
+                    ComplexDataType::DensityFunction => match input.value() {
+                        ValueType::Value(x) => Some(JsonValue::from(*x)),
+                        // Made before constants could be typed in
+                        _ => Some(JsonValue::from(0.0)),
+                    },
+                    ComplexDataType::Spline => {
+                        if let ValueType::Value(x) = input.value() {
+                            Some(JsonValue::from(*x))
+                        } else {
+                            None
+                        }
+                    }
+                    // Unconnected list entries are skipped
+                    ComplexDataType::SplinePoint | ComplexDataType::ParameterPoint => None,
+                    ComplexDataType::Spawner | ComplexDataType::SpawnCost => None,
+                    // Optional fields are left out
+                    ComplexDataType::MoodSound
+                    | ComplexDataType::AdditionsSound
+                    | ComplexDataType::Music
+                    | ComplexDataType::Particle => Some(JsonValue::Null),
+                    ComplexDataType::PlacedFeature | ComplexDataType::ConfiguredCarver => {
+                        match input.value() {
+                            ValueType::DullReference(x) if x.is_empty() => Some(JsonValue::Null),
+                            ValueType::DullReference(x) => Some(JsonValue::String(x.clone())),
+                            _ => None,
+                        }
+                    }
+                    // Have to be connected
+                    ComplexDataType::SurfaceRule
+                    | ComplexDataType::SurfaceRuleCondition
+                    | ComplexDataType::Noise
+                    | ComplexDataType::Biome => None,
                 },
                 DataType::ValueTypeSwitcher => {
                     if let ValueType::InnerTypeSwitch(x) = input.value() {
@@ -492,17 +1034,16 @@ impl Window {
                         None
                     }
                 }
-                DataType::DullReference => {
-                    if let ValueType::DullReference(x) = input.value() {
-                        Some(JsonValue::String(x.clone()))
-                    } else {
-                        None
-                    }
-                }
+                // Empty references are left out
+                DataType::DullReference => match input.value() {
+                    ValueType::DullReference(x) if x.is_empty() => Some(JsonValue::Null),
+                    ValueType::DullReference(x) => Some(JsonValue::String(x.clone())),
+                    _ => None,
+                },
                 DataType::VerticalAnchor => {
                     if let ValueType::VerticalAnchor(x, i) = input.value() {
                         let mut obj = JsonValue::new_object();
-                        obj.insert(x.as_ref(), *i);
+                        obj[x.as_ref()] = JsonValue::from(*i);
                         Some(obj)
                     } else {
                         None
@@ -515,7 +1056,45 @@ impl Window {
                         None
                     }
                 }
-            }
+                DataType::Range => match input.value() {
+                    ValueType::Range(min, max) if min == max => Some(JsonValue::from(*min)),
+                    ValueType::Range(min, max) => Some(json::array![*min, *max]),
+                    _ => None,
+                },
+                // `none` is the default and is left out
+                DataType::TemperatureModifier => match input.value() {
+                    ValueType::TemperatureModifier(TemperatureModifier::None) => {
+                        Some(JsonValue::Null)
+                    }
+                    ValueType::TemperatureModifier(x) => Some(JsonValue::String(x.as_ref().into())),
+                    _ => None,
+                },
+                DataType::GrassColorModifier => match input.value() {
+                    ValueType::GrassColorModifier(GrassColorModifier::None) => {
+                        Some(JsonValue::Null)
+                    }
+                    ValueType::GrassColorModifier(x) => Some(JsonValue::String(x.as_ref().into())),
+                    _ => None,
+                },
+                DataType::Precipitation => match input.value() {
+                    ValueType::Precipitation(x) => Some(JsonValue::String(x.as_ref().into())),
+                    _ => None,
+                },
+                DataType::Color => match input.value() {
+                    ValueType::Color(Some(color)) => Some(JsonValue::from(u32::from_be_bytes([
+                        0,
+                        color.r(),
+                        color.g(),
+                        color.b(),
+                    ]))),
+                    ValueType::Color(None) => Some(JsonValue::Null),
+                    _ => None,
+                },
+                // Handled by `serialize_inner`
+                DataType::Object | DataType::Tuple | DataType::List(_) | DataType::Map(_) => None,
+                // Only on raw JSON nodes, written by `serialize_fields`
+                DataType::RawJson => None,
+            })
         }
     }
 }
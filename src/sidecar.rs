@@ -0,0 +1,73 @@
+//! Editor-only copy of every graph kept in `.datapack_creator/` next to the datapack, so
+//! layout and nodes that aren't connected to the output survive closing the app.
+
+use std::fs::{self, DirBuilder};
+use std::path::{Path, PathBuf};
+
+use egui_node_graph::NodeId;
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::app::EditorStateType;
+use crate::window::Window;
+
+pub const SIDECAR_DIR: &str = ".datapack_creator";
+
+#[derive(Serialize, Deserialize)]
+struct Sidecar {
+    /// `json_hash` of the file this graph was saved together with
+    hash: String,
+    root_node: NodeId,
+    state: EditorStateType,
+}
+
+/// Hex MD5 of a datapack file's content.
+pub fn json_hash(s: &str) -> String {
+    format!("{:x}", md5::compute(s))
+}
+
+impl Window {
+    pub fn sidecar_path(&self, project_path: &Path) -> PathBuf {
+        project_path.join(SIDECAR_DIR).join(self.relative_path())
+    }
+
+    /// Saves the whole editor state. Does nothing for windows that have never been saved as JSON.
+    pub fn save_sidecar(&self, project_path: &Path) -> Result<(), std::io::Error> {
+        let hash = match &self.json_hash {
+            Some(hash) => hash.clone(),
+            None => return Ok(()),
+        };
+        let path = self.sidecar_path(project_path);
+        if let Some(dir) = path.parent() {
+            DirBuilder::new().recursive(true).create(dir)?;
+        }
+        let sidecar = Sidecar {
+            hash,
+            root_node: self.root_node,
+            state: self.state.clone(),
+        };
+        fs::write(path, serde_json::to_string(&sidecar)?)
+    }
+
+    /// Replaces the graph with the saved editor state if it was saved together with `json`.
+    /// Returns `false` if the JSON has to be deserialized instead.
+    pub fn load_sidecar(&mut self, project_path: &Path, json: &str) -> bool {
+        let path = self.sidecar_path(project_path);
+        let s = match fs::read_to_string(&path) {
+            Ok(s) => s,
+            Err(_) => return false,
+        };
+        match serde_json::from_str::<Sidecar>(&s) {
+            Ok(sidecar) if sidecar.hash == json_hash(json) => {
+                self.root_node = sidecar.root_node;
+                self.state = sidecar.state;
+                true
+            }
+            Ok(_) => false,
+            Err(e) => {
+                warn!("Ignoring {}: {}", path.display(), e);
+                false
+            }
+        }
+    }
+}
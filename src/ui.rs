@@ -1,8 +1,10 @@
-use std::path::PathBuf;
+use std::path::Path;
 
-use eframe::egui::{Button, Response, Ui};
+use eframe::egui::{Button, Ui};
 use strum::IntoEnumIterator;
 
+use crate::app::Identifier;
+use crate::version::McVersion;
 use crate::window::{Window, WindowType};
 
 pub trait ComboBoxEnum: IntoEnumIterator + AsRef<str> + PartialEq + Clone {
@@ -19,12 +21,13 @@ pub struct NewWindowPrompt {
     pub window_type: WindowType,
 }
 impl NewWindowPrompt {
-    pub fn make_window(&self, project_path: &PathBuf) -> Window {
+    pub fn make_window(&self, project_path: &Path, version: McVersion) -> Window {
         Window::new(
             self.name.clone(),
             self.namespace.clone(),
             self.window_type,
             project_path,
+            version,
         )
     }
     //TODO: regex match potential namespace + path
@@ -48,6 +51,22 @@ impl NewWindowPrompt {
     }
 }
 
+pub struct RenamePrompt {
+    pub from: Identifier,
+    /// new `namespace:path`
+    pub to: String,
+    pub error: Option<String>,
+}
+impl RenamePrompt {
+    pub fn new(from: Identifier) -> Self {
+        Self {
+            to: from.to_string(),
+            from,
+            error: None,
+        }
+    }
+}
+
 pub struct OpenProjectPrompt {
     pub show: bool,
     pub path: String,
@@ -70,13 +89,7 @@ impl OpenProjectPrompt {
                 }
             }
         });
-        if ui
-            .add_enabled(!self.path.is_empty(), Button::new("load"))
+        ui.add_enabled(!self.path.is_empty(), Button::new("load"))
             .clicked()
-        {
-            true
-        } else {
-            false
-        }
     }
 }
@@ -0,0 +1,190 @@
+//! Finding and rewriting the inputs that refer to a file.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use egui_node_graph::{InputId, NodeId};
+
+use crate::app::Identifier;
+use crate::errors::AppError;
+use crate::nodes::data_types::ValueType;
+use crate::window::Window;
+
+/// An input of `window` holding a reference.
+pub struct Usage {
+    pub window: Identifier,
+    pub node_id: NodeId,
+    pub input_id: InputId,
+    /// `node label: input name`
+    pub description: String,
+}
+
+/// Whether a reference string of `value` points to `target`. Dull references are ids of other
+/// registries (carvers, features, sounds...), a file with the same `namespace:path` isn't them.
+fn refers_to(value: &ValueType, target: &Identifier) -> bool {
+    match value {
+        ValueType::Reference(window_type, name) if *window_type == target.window_type() => {
+            Identifier::from_string(name.clone(), *window_type).as_ref() == Some(target)
+        }
+        _ => false,
+    }
+}
+
+/// Whether JSON `text` has a string naming `target`, what raw JSON and files that couldn't be
+/// read are searched for since their references have no type.
+fn mentions(text: &str, target: &Identifier) -> bool {
+    text.contains(&format!("\"{}\"", target))
+        || (target.namespace() == "minecraft" && text.contains(&format!("\"{}\"", target.path())))
+}
+
+impl Window {
+    pub fn usages_of(&self, target: &Identifier) -> Vec<(NodeId, InputId)> {
+        let graph = &self.state.graph;
+        graph
+            .inputs
+            .iter()
+            .filter(|(_, input)| refers_to(&input.value, target))
+            .map(|(input_id, input)| (input.node, input_id))
+            .collect()
+    }
+
+    /// Points every reference to `from` at `to` instead, returns how many were changed.
+    pub fn rename_references(&mut self, from: &Identifier, to: &Identifier) -> usize {
+        let mut count = 0;
+        for input in self.state.graph.inputs.values_mut() {
+            if refers_to(&input.value, from) {
+                if let ValueType::Reference(_, name) = &mut input.value {
+                    *name = to.to_string();
+                    count += 1;
+                }
+            }
+        }
+        if count > 0 {
+            self.dirty = true;
+        }
+        count
+    }
+
+    /// Nodes whose raw JSON or unknown fields name `target`, the rename can't change them.
+    fn raw_mentions_of(&self, target: &Identifier) -> Vec<NodeId> {
+        let graph = &self.state.graph;
+        graph
+            .nodes
+            .iter()
+            .filter(|(_, node)| {
+                node.user_data
+                    .unknown_fields
+                    .iter()
+                    .any(|field| mentions(&field.value, target))
+                    || node.input_ids().any(|in_id| {
+                        matches!(graph.get_input(in_id).value(),
+                            ValueType::RawJson(text) if mentions(text, target))
+                    })
+            })
+            .map(|(node_id, _)| node_id)
+            .collect()
+    }
+
+    /// Whether the window refers to `target`, for a file that couldn't be read completely what is
+    /// on disk is searched.
+    fn may_refer_to(&self, target: &Identifier) -> bool {
+        !self.usages_of(target).is_empty()
+            || !self.raw_mentions_of(target).is_empty()
+            || (self.load_failed
+                && fs::read_to_string(&self.filepath).is_ok_and(|text| mentions(&text, target)))
+    }
+}
+
+/// Moves the file of `from` to `to` and points every reference to it at the new identifier.
+/// Files whose references changed are saved right away, so the pack on disk never refers to a
+/// file that was moved, and the change can be undone in each of them. Files that couldn't be
+/// read completely can't be rewritten, nothing is renamed while one of them refers to `from`.
+///
+/// Returns how many references were changed, along with the problems of saving and the raw JSON
+/// still naming `from`.
+pub fn rename(
+    file_structure: &mut [HashMap<Identifier, Window>],
+    project_path: &Path,
+    from: &Identifier,
+    to: &Identifier,
+) -> Result<(usize, Vec<AppError>), AppError> {
+    if from == to {
+        return Ok((0, Vec::new()));
+    }
+    let map = &mut file_structure[from.window_type() as usize];
+    if map.contains_key(to) {
+        return Err(AppError::WrongData(format!("{} already exists", to).into()));
+    }
+    if let Some(broken) = file_structure
+        .iter()
+        .flat_map(|map| map.values())
+        .find(|window| window.load_failed && window.may_refer_to(from))
+    {
+        return Err(AppError::WrongData(
+            format!(
+                "{} couldn't be read completely and may refer to {}, fix it first",
+                broken.identifier(),
+                from
+            )
+            .into(),
+        ));
+    }
+    let map = &mut file_structure[from.window_type() as usize];
+    let mut window = map
+        .remove(from)
+        .ok_or_else(|| AppError::WrongData(format!("{} doesn't exist", from).into()))?;
+    let moved = window.move_to(
+        project_path,
+        to.namespace().to_string(),
+        to.path().to_string(),
+    );
+    if let Err(e) = moved {
+        map.insert(from.clone(), window);
+        return Err(AppError::FileWrite(e.to_string()));
+    }
+    map.insert(to.clone(), window);
+
+    let mut count = 0;
+    let mut errors = Vec::new();
+    for window in file_structure.iter_mut().flat_map(|map| map.values_mut()) {
+        if !window.usages_of(from).is_empty() {
+            count += window.record_edit(|window| window.rename_references(from, to));
+            errors.extend(window.save(Some(project_path)));
+        }
+        for node_id in window.raw_mentions_of(from) {
+            errors.push(
+                AppError::WrongData(format!("raw JSON still refers to {}", from).into())
+                    .with_window(window.identifier())
+                    .with_node(node_id),
+            );
+        }
+    }
+    Ok((count, errors))
+}
+
+/// Every input in the project referring to `target`, sorted by window.
+pub fn find_usages(
+    file_structure: &[HashMap<Identifier, Window>],
+    target: &Identifier,
+) -> Vec<Usage> {
+    let mut usages = Vec::new();
+    for (id, window) in file_structure.iter().flat_map(|map| map.iter()) {
+        let graph = &window.state.graph;
+        for (node_id, input_id) in window.usages_of(target) {
+            let input_name = graph[node_id]
+                .inputs
+                .iter()
+                .find(|(_, id)| *id == input_id)
+                .map_or("", |(name, _)| name.as_str());
+            usages.push(Usage {
+                window: id.clone(),
+                node_id,
+                input_id,
+                description: format!("{}: {}", graph[node_id].label, input_name),
+            });
+        }
+    }
+    usages.sort_by(|a, b| a.window.partial_cmp(&b.window).unwrap());
+    usages
+}
@@ -0,0 +1,191 @@
+//! Files shipped with the game, in the `minecraft` namespace.
+
+use crate::window::WindowType;
+
+pub const DENSITY_FUNCTIONS: &[&str] = &[
+    "end/base_3d_noise",
+    "end/sloped_cheese",
+    "nether/base_3d_noise",
+    "overworld/base_3d_noise",
+    "overworld/caves/entrances",
+    "overworld/caves/noodle",
+    "overworld/caves/pillars",
+    "overworld/caves/spaghetti_2d",
+    "overworld/caves/spaghetti_2d_thickness_modulator",
+    "overworld/caves/spaghetti_roughness_function",
+    "overworld/continents",
+    "overworld/depth",
+    "overworld/erosion",
+    "overworld/factor",
+    "overworld/jaggedness",
+    "overworld/offset",
+    "overworld/ridges",
+    "overworld/ridges_folded",
+    "overworld/sloped_cheese",
+    "overworld_amplified/depth",
+    "overworld_amplified/factor",
+    "overworld_amplified/jaggedness",
+    "overworld_amplified/offset",
+    "overworld_amplified/sloped_cheese",
+    "overworld_large_biomes/continents",
+    "overworld_large_biomes/depth",
+    "overworld_large_biomes/erosion",
+    "overworld_large_biomes/factor",
+    "overworld_large_biomes/jaggedness",
+    "overworld_large_biomes/offset",
+    "overworld_large_biomes/sloped_cheese",
+    "shift_x",
+    "shift_z",
+    "y",
+    "zero",
+];
+
+pub const NOISES: &[&str] = &[
+    "aquifer_barrier",
+    "aquifer_fluid_level_floodedness",
+    "aquifer_fluid_level_spread",
+    "aquifer_lava",
+    "badlands_pillar",
+    "badlands_pillar_roof",
+    "badlands_surface",
+    "calcite",
+    "cave_cheese",
+    "cave_entrance",
+    "cave_layer",
+    "clay_bands_offset",
+    "continentalness",
+    "continentalness_large",
+    "erosion",
+    "erosion_large",
+    "gravel",
+    "gravel_layer",
+    "ice",
+    "iceberg_pillar",
+    "iceberg_pillar_roof",
+    "iceberg_surface",
+    "jagged",
+    "nether_state_selector",
+    "nether_wart",
+    "netherrack",
+    "noodle",
+    "noodle_ridge_a",
+    "noodle_ridge_b",
+    "noodle_thickness",
+    "offset",
+    "ore_gap",
+    "ore_vein_a",
+    "ore_vein_b",
+    "ore_veininess",
+    "packed_ice",
+    "patch",
+    "pillar",
+    "pillar_rareness",
+    "pillar_thickness",
+    "powder_snow",
+    "ridge",
+    "soul_sand_layer",
+    "spaghetti_2d",
+    "spaghetti_2d_elevation",
+    "spaghetti_2d_modulator",
+    "spaghetti_2d_thickness",
+    "spaghetti_3d_1",
+    "spaghetti_3d_2",
+    "spaghetti_3d_rarity",
+    "spaghetti_3d_thickness",
+    "spaghetti_roughness",
+    "spaghetti_roughness_modulator",
+    "surface",
+    "surface_secondary",
+    "surface_swamp",
+    "temperature",
+    "temperature_large",
+    "vegetation",
+    "vegetation_large",
+];
+
+pub const NOISE_SETTINGS: &[&str] = &[
+    "amplified",
+    "caves",
+    "end",
+    "floating_islands",
+    "large_biomes",
+    "nether",
+    "overworld",
+];
+
+pub const BIOMES: &[&str] = &[
+    "badlands",
+    "bamboo_jungle",
+    "basalt_deltas",
+    "beach",
+    "birch_forest",
+    "cherry_grove",
+    "cold_ocean",
+    "crimson_forest",
+    "dark_forest",
+    "deep_cold_ocean",
+    "deep_dark",
+    "deep_frozen_ocean",
+    "deep_lukewarm_ocean",
+    "deep_ocean",
+    "desert",
+    "dripstone_caves",
+    "end_barrens",
+    "end_highlands",
+    "end_midlands",
+    "eroded_badlands",
+    "flower_forest",
+    "forest",
+    "frozen_ocean",
+    "frozen_peaks",
+    "frozen_river",
+    "grove",
+    "ice_spikes",
+    "jagged_peaks",
+    "jungle",
+    "lukewarm_ocean",
+    "lush_caves",
+    "mangrove_swamp",
+    "meadow",
+    "mushroom_fields",
+    "nether_wastes",
+    "ocean",
+    "old_growth_birch_forest",
+    "old_growth_pine_taiga",
+    "old_growth_spruce_taiga",
+    "plains",
+    "river",
+    "savanna",
+    "savanna_plateau",
+    "small_end_islands",
+    "snowy_beach",
+    "snowy_plains",
+    "snowy_slopes",
+    "snowy_taiga",
+    "soul_sand_valley",
+    "sparse_jungle",
+    "stony_peaks",
+    "stony_shore",
+    "sunflower_plains",
+    "swamp",
+    "taiga",
+    "the_end",
+    "the_void",
+    "warm_ocean",
+    "warped_forest",
+    "windswept_forest",
+    "windswept_gravelly_hills",
+    "windswept_hills",
+    "windswept_savanna",
+    "wooded_badlands",
+];
+
+/// Paths of the vanilla files of a type, sorted.
+pub fn paths(window_type: WindowType) -> &'static [&'static str] {
+    match window_type {
+        WindowType::DensityFunction => DENSITY_FUNCTIONS,
+        WindowType::Noise => NOISES,
+        WindowType::NoiseSettings => NOISE_SETTINGS,
+        WindowType::Biome => BIOMES,
+    }
+}
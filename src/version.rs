@@ -0,0 +1,176 @@
+//! Game versions a pack can be made for, and what changes between them.
+
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+use strum::{AsRefStr, EnumIter, IntoEnumIterator};
+
+use crate::nodes::inner_data_types::density_function::DensityFunctionType;
+use crate::nodes::inner_data_types::InnerDataType;
+use crate::nodes::node_types::NodeTemplate;
+use crate::ui::ComboBoxEnum;
+use crate::window::WindowType;
+
+/// Folders of `data/<namespace>/` every version reads.
+const FOLDERS: &[&str] = &[
+    "banner_pattern",
+    "chat_type",
+    "damage_type",
+    "dimension",
+    "dimension_type",
+    "tags",
+    "trim_material",
+    "trim_pattern",
+    "worldgen",
+];
+/// Folders renamed from plural to singular in 1.21.
+const RENAMED_FOLDERS: &[(&str, &str)] = &[
+    ("advancements", "advancement"),
+    ("functions", "function"),
+    ("item_modifiers", "item_modifier"),
+    ("loot_tables", "loot_table"),
+    ("predicates", "predicate"),
+    ("recipes", "recipe"),
+    ("structures", "structure"),
+];
+/// Folders added in 1.21.
+const FOLDERS_1_21: &[&str] = &[
+    "enchantment",
+    "enchantment_provider",
+    "jukebox_song",
+    "painting_variant",
+    "wolf_variant",
+];
+/// Node types that only some versions have, with the `pack_format`s that have them. Surface
+/// rules and conditions are the same from 1.19 on.
+const VERSIONED_TEMPLATES: &[(NodeTemplate, Range<u32>)] = &[(
+    NodeTemplate::DensityFunction(DensityFunctionType::FindTopSurface),
+    57..u32::MAX,
+)];
+/// Fields that only some versions read, with the `pack_format`s that have them. Nodes are built
+/// without the fields their version doesn't read. `old_blended_noise` got all of its fields,
+/// `smear_scale_multiplier` included, in 1.19 and they didn't change since.
+const VERSIONED_FIELDS: &[(NodeTemplate, &str, Range<u32>)] = &[
+    // 1.19.4 replaced the kind of precipitation with whether there is any
+    (
+        NodeTemplate::Output(WindowType::Biome),
+        "precipitation",
+        0..12,
+    ),
+    (
+        NodeTemplate::Output(WindowType::Biome),
+        "has_precipitation",
+        12..u32::MAX,
+    ),
+    // 1.21.2 routes the height of the surface instead of the density without jaggedness
+    (
+        NodeTemplate::Output(WindowType::NoiseSettings),
+        "initial_density_without_jaggedness",
+        0..57,
+    ),
+    (
+        NodeTemplate::Output(WindowType::NoiseSettings),
+        "preliminary_surface_level",
+        57..u32::MAX,
+    ),
+];
+
+#[derive(
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    EnumIter,
+    AsRefStr,
+    Serialize,
+    Deserialize,
+)]
+pub enum McVersion {
+    #[strum(serialize = "1.19")]
+    V1_19,
+    #[strum(serialize = "1.19.4")]
+    V1_19_4,
+    #[default]
+    #[strum(serialize = "1.20")]
+    V1_20,
+    #[strum(serialize = "1.21")]
+    V1_21,
+    #[strum(serialize = "1.21.2")]
+    V1_21_2,
+}
+
+impl ComboBoxEnum for McVersion {}
+
+impl McVersion {
+    /// `pack_format` written for this version, the one of its first release.
+    pub fn pack_format(self) -> u32 {
+        match self {
+            McVersion::V1_19 => 10,
+            McVersion::V1_19_4 => 12,
+            McVersion::V1_20 => 15,
+            McVersion::V1_21 => 48,
+            McVersion::V1_21_2 => 57,
+        }
+    }
+
+    /// Version a `pack_format` belongs to, older and newer ones get the closest one known.
+    pub fn from_pack_format(pack_format: u32) -> Self {
+        match pack_format {
+            ..=11 => McVersion::V1_19,
+            12..=14 => McVersion::V1_19_4,
+            15..=47 => McVersion::V1_20,
+            48..=56 => McVersion::V1_21,
+            _ => McVersion::V1_21_2,
+        }
+    }
+
+    /// Every version whose `pack_format` is in `min..=max`.
+    pub fn in_range(min: u32, max: u32) -> impl Iterator<Item = Self> {
+        let (min, max) = (Self::from_pack_format(min), Self::from_pack_format(max));
+        Self::iter().filter(move |version| (min..=max).contains(version))
+    }
+
+    /// Folders of `data/<namespace>/` the game reads.
+    pub fn folders(self) -> impl Iterator<Item = &'static str> {
+        let renamed = RENAMED_FOLDERS
+            .iter()
+            .map(move |(plural, singular)| match self {
+                McVersion::V1_19 | McVersion::V1_19_4 | McVersion::V1_20 => *plural,
+                McVersion::V1_21 | McVersion::V1_21_2 => *singular,
+            });
+        let added = match self {
+            McVersion::V1_19 | McVersion::V1_19_4 | McVersion::V1_20 => &[],
+            McVersion::V1_21 | McVersion::V1_21_2 => FOLDERS_1_21,
+        };
+        FOLDERS
+            .iter()
+            .copied()
+            .chain(renamed)
+            .chain(added.iter().copied())
+    }
+
+    /// Whether the node can be made in a pack for this version.
+    pub fn supports(self, template: NodeTemplate) -> bool {
+        VERSIONED_TEMPLATES
+            .iter()
+            .filter(|(versioned, _)| *versioned == template)
+            .all(|(_, formats)| formats.contains(&self.pack_format()))
+    }
+
+    /// Types of a type switch the version has, what its combo box lists.
+    pub fn types<T: InnerDataType>(self) -> impl Iterator<Item = T> {
+        T::iter().filter(move |typ| self.supports(typ.to_NodeTemplate()))
+    }
+
+    /// Whether the version reads the input `field` of `template` nodes.
+    pub fn has_field(self, template: NodeTemplate, field: &str) -> bool {
+        VERSIONED_FIELDS
+            .iter()
+            .filter(|(versioned, name, _)| *versioned == template && *name == field)
+            .all(|(_, _, formats)| formats.contains(&self.pack_format()))
+    }
+}
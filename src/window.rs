@@ -1,21 +1,24 @@
-use eframe::epaint::Pos2;
+use eframe::epaint::{Pos2, Vec2};
 use egui_node_graph::NodeId;
 use enum_ordinalize::Ordinalize;
+use log::warn;
+use serde::{Deserialize, Serialize};
 
 use std::fmt::Display;
-use std::fs::{DirBuilder, File};
+use std::fs::{self, DirBuilder, File, OpenOptions};
 use std::hash::Hash;
 use std::io::{Seek, SeekFrom, Write};
-use std::path::{Path, PathBuf};
-use strum::{AsRefStr, EnumCount, EnumIter};
+use std::path::{Component, Path, PathBuf};
+use strum::{AsRefStr, EnumCount, EnumIter, IntoEnumIterator};
 
-use crate::app::EditorStateType;
+use crate::app::{EditorStateType, Identifier};
 use crate::errors::AppError;
-use crate::nodes::{
-    add_node, inner_data_types::density_function::DensityFunctionType, node_types::NodeTemplate,
-    GraphState,
-};
+use crate::history::History;
+use crate::nodes::{add_node, node_types::NodeTemplate, GraphState};
+use crate::pack::data_root;
+use crate::sidecar::json_hash;
 use crate::ui::ComboBoxEnum;
+use crate::version::McVersion;
 
 // #[derive(Clone)]
 pub struct Window {
@@ -29,6 +32,13 @@ pub struct Window {
     pub user_state: GraphState,
     pub dirty: bool,
     pub root_node: NodeId,
+    /// `sidecar::json_hash` of the file as last read or written, `None` if there is no file yet
+    pub json_hash: Option<String>,
+    pub history: History,
+    /// The file couldn't be read completely, it isn't overwritten until the graph is edited
+    pub load_failed: bool,
+    /// Nodes were arranged with estimated sizes, they are arranged again once they were drawn
+    pub arrange_when_drawn: bool,
 }
 
 impl PartialEq for Window {
@@ -65,27 +75,29 @@ impl PartialOrd for Window {
 }
 
 #[derive(
-    PartialEq, PartialOrd, Copy, Clone, EnumCount, EnumIter, AsRefStr, Hash, Eq, Ordinalize, Debug,
+    PartialEq,
+    PartialOrd,
+    Copy,
+    Clone,
+    EnumCount,
+    EnumIter,
+    AsRefStr,
+    Hash,
+    Eq,
+    Ordinalize,
+    Debug,
+    Serialize,
+    Deserialize,
 )]
 pub enum WindowType {
     #[strum(serialize = "Density Function")]
     DensityFunction,
     Noise,
+    #[strum(serialize = "Noise Settings")]
+    NoiseSettings,
     Biome,
 }
 
-impl WindowType {
-    pub fn get_node_template(&self) -> NodeTemplate {
-        match self {
-            WindowType::DensityFunction => {
-                NodeTemplate::DensityFunction(DensityFunctionType::Constant)
-            }
-            WindowType::Noise => NodeTemplate::Noise,
-            WindowType::Biome => todo!(),
-        }
-    }
-}
-
 impl ComboBoxEnum for WindowType {}
 
 impl Display for Window {
@@ -105,13 +117,17 @@ impl Window {
         filename: String,
         namespace: String,
         window_type: WindowType,
-        project_path: &PathBuf,
+        project_path: &Path,
+        version: McVersion,
     ) -> Self {
         let mut state = EditorStateType::default();
-        let mut user_state = GraphState::default();
+        let mut user_state = GraphState {
+            version,
+            ..Default::default()
+        };
 
         let root_node = Self::add_default_node(&mut state, &mut user_state, window_type);
-        let mut filepath = project_path.clone();
+        let mut filepath = data_root(project_path);
         filepath.push(&namespace);
         filepath.push(Self::path_from(window_type));
         filepath.push(&filename);
@@ -126,48 +142,46 @@ impl Window {
             filepath,
             namespace,
             root_node,
+            json_hash: None,
+            history: History::default(),
+            load_failed: false,
+            arrange_when_drawn: false,
         }
     }
-    pub fn from_file(file: File, path: PathBuf, project_path: &PathBuf) -> Result<Self, AppError> {
-        match path.strip_prefix(project_path) {
-            Ok(filepath) => {
-                let mut components = filepath.components();
-                let namespace = components.next();
-                let filename = components.as_path().file_stem();
-                let window_type_dir = components.as_path().parent();
-                if let (Some(namespace), Some(filename), Some(window_type_dir)) =
-                    (namespace, filename, window_type_dir)
-                {
-                    if let (Some(window_type), Some(name), Some(namespace)) = (
-                        Self::window_type_from(&window_type_dir.to_path_buf()),
-                        filename.to_str(),
-                        namespace.as_os_str().to_str(),
-                    ) {
-                        let mut state = EditorStateType::default();
-                        let mut user_state = GraphState::default();
-                        let root_node =
-                            Self::add_default_node(&mut state, &mut user_state, window_type);
-
-                        Ok(Self {
-                            window_type,
-                            name: name.to_string(),
-                            filepath: filepath.to_path_buf(),
-                            namespace: namespace.to_string(),
-                            file: Some(file),
-                            state,
-                            user_state,
-                            dirty: false,
-                            root_node,
-                        })
-                    } else {
-                        Err(AppError::FileStructure(path.into()))
-                    }
-                } else {
-                    Err(AppError::FileStructure(path.into()))
-                }
-            }
-            Err(x) => Err(AppError::FileRead(x.to_string())),
-        }
+    pub fn from_file(
+        file: File,
+        path: PathBuf,
+        project_path: &Path,
+        version: McVersion,
+    ) -> Result<Self, AppError> {
+        let relative_path = path
+            .strip_prefix(data_root(project_path))
+            .map_err(|e| AppError::FileRead(e.to_string()))?;
+        let (window_type, namespace, name) = match Self::locate(relative_path, &[]) {
+            FileLocation::Window(window_type, namespace, name) => (window_type, namespace, name),
+            _ => return Err(AppError::FileStructure(path.into())),
+        };
+        let mut state = EditorStateType::default();
+        let mut user_state = GraphState {
+            version,
+            ..Default::default()
+        };
+        let root_node = Self::add_default_node(&mut state, &mut user_state, window_type);
+        Ok(Self {
+            window_type,
+            name,
+            filepath: path,
+            namespace,
+            file: Some(file),
+            state,
+            user_state,
+            dirty: false,
+            root_node,
+            json_hash: None,
+            history: History::default(),
+            load_failed: false,
+            arrange_when_drawn: false,
+        })
     }
 
     fn add_default_node(
@@ -192,26 +206,168 @@ impl Window {
             dir.pop();
             DirBuilder::new().recursive(true).create(dir)?;
 
-            self.file = Some(File::create(&self.filepath).unwrap());
-            self.file.as_mut().unwrap().write_all(s.as_bytes())?;
+            let mut file = File::create(&self.filepath)?;
+            file.write_all(s.as_bytes())?;
+            self.file = Some(file);
         }
         Ok(())
     }
 
+    /// Writes the file, and the editor state if the project is known. Returns what couldn't be
+    /// written.
+    pub fn save(&mut self, project_path: Option<&Path>) -> Vec<AppError> {
+        let mut errors = Vec::new();
+        let id = self.identifier();
+        match self.serialize_to_string() {
+            Ok(s) => match self.save_to_file(s.clone()) {
+                Ok(()) => {
+                    self.json_hash = Some(json_hash(&s));
+                    self.load_failed = false;
+                }
+                Err(e) => errors.push(
+                    AppError::FileWrite(e.to_string())
+                        .with_window(id.clone())
+                        .with_file(self.filepath.clone()),
+                ),
+            },
+            Err(e) => errors.push(e),
+        }
+        // Unfinished graphs are kept even if they couldn't be written as JSON
+        if let Some(project_path) = project_path {
+            if let Err(e) = self.save_sidecar(project_path) {
+                errors.push(
+                    AppError::FileWrite(e.to_string())
+                        .with_window(id)
+                        .with_file(self.sidecar_path(project_path)),
+                );
+            }
+        }
+        errors
+    }
+
+    /// Renames the window, its file and sidecar are moved along if they exist.
+    pub fn move_to(
+        &mut self,
+        project_path: &Path,
+        namespace: String,
+        name: String,
+    ) -> Result<(), std::io::Error> {
+        let old_path = data_root(project_path).join(self.relative_path());
+        let old_sidecar = self.sidecar_path(project_path);
+        let old_name = std::mem::replace(&mut self.name, name);
+        let old_namespace = std::mem::replace(&mut self.namespace, namespace);
+        let new_path = data_root(project_path).join(self.relative_path());
+        if self.file.is_some() {
+            let moved = new_path
+                .parent()
+                .map_or(Ok(()), |dir| DirBuilder::new().recursive(true).create(dir))
+                .and_then(|_| fs::rename(&old_path, &new_path));
+            if let Err(e) = moved {
+                self.name = old_name;
+                self.namespace = old_namespace;
+                return Err(e);
+            }
+            // Without a file handle the next save creates the file again
+            self.file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(&new_path)
+                .ok();
+        }
+        self.filepath = new_path;
+        if old_sidecar.exists() {
+            let new_sidecar = self.sidecar_path(project_path);
+            let moved = new_sidecar
+                .parent()
+                .map_or(Ok(()), |dir| DirBuilder::new().recursive(true).create(dir))
+                .and_then(|_| fs::rename(old_sidecar, new_sidecar));
+            if let Err(e) = moved {
+                warn!("failed to move editor state of {}: {}", self, e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Selects the node and scrolls the editor to it.
+    pub fn focus_node(&mut self, node_id: NodeId) {
+        if let Some(pos) = self.state.node_positions.get(node_id) {
+            self.state.pan_zoom.pan = Vec2::new(300., 200.) - pos.to_vec2();
+            self.state.selected_nodes = vec![node_id];
+        }
+    }
+
+    pub fn identifier(&self) -> Identifier {
+        Identifier::new(self.namespace.clone(), self.name.clone(), self.window_type)
+    }
+
+    /// Path of the file from the folder of the namespaces, see `pack::data_root`.
+    pub fn relative_path(&self) -> PathBuf {
+        let mut path = PathBuf::from(&self.namespace);
+        path.push(Self::path_from(self.window_type));
+        path.push(&self.name);
+        path.set_extension("json");
+        path
+    }
+
     fn path_from(window_type: WindowType) -> String {
         match window_type {
             WindowType::DensityFunction => "worldgen/density_function",
             WindowType::Noise => "worldgen/noise",
+            WindowType::NoiseSettings => "worldgen/noise_settings",
             WindowType::Biome => "worldgen/biome",
         }
         .to_string()
     }
-    /// returns `WindowType` from the exact part of path that is distinct for it.
-    fn window_type_from(path: &PathBuf) -> Option<WindowType> {
-        match path {
-            x if x == Path::new("worldgen/density_function") => Some(WindowType::DensityFunction),
-            x if x == Path::new("worldgen/noise") => Some(WindowType::Noise),
-            _ => None,
+    /// What the file at `relative_path` from the folder of the namespaces is, `known_folders`
+    /// are the ones the game reads besides those of windows.
+    pub fn locate(relative_path: &Path, known_folders: &[&str]) -> FileLocation {
+        let parts: Option<Vec<&str>> = relative_path
+            .components()
+            .map(|component| match component {
+                Component::Normal(part) => part.to_str(),
+                _ => None,
+            })
+            .collect();
+        let (namespace, folders, file) = match parts.as_deref() {
+            Some([namespace, folders @ .., file]) if !folders.is_empty() => {
+                (*namespace, folders, *file)
+            }
+            _ => return FileLocation::Stray,
+        };
+        for window_type in WindowType::iter() {
+            let type_folders = Self::path_from(window_type);
+            let type_folders: Vec<&str> = type_folders.split('/').collect();
+            match (
+                folders.strip_prefix(type_folders.as_slice()),
+                file.strip_suffix(".json"),
+            ) {
+                (Some(subfolders), Some(file_stem)) => {
+                    let mut name = subfolders.join("/");
+                    if !name.is_empty() {
+                        name.push('/');
+                    }
+                    name.push_str(file_stem);
+                    return FileLocation::Window(window_type, namespace.to_string(), name);
+                }
+                (Some(_), None) => return FileLocation::Other,
+                _ => {}
+            }
+        }
+        if known_folders.contains(&folders[0]) {
+            FileLocation::Other
+        } else {
+            FileLocation::Stray
         }
     }
 }
+
+/// Where a file of the pack is.
+#[derive(PartialEq, Eq, Debug)]
+pub enum FileLocation {
+    /// A file the editor opens, with its namespace and path
+    Window(WindowType, String, String),
+    /// Part of the pack, but not something the editor knows how to edit
+    Other,
+    /// Not in a folder the game reads
+    Stray,
+}
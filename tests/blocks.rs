@@ -0,0 +1,36 @@
+use datapack_creato_rs::nodes::blocks::{BlockState, BLOCKS};
+
+#[test]
+fn block_states_keep_their_properties() {
+    let json =
+        json::parse(r#"{"Name": "minecraft:grass_block", "Properties": {"snowy": "false"}}"#)
+            .unwrap();
+    let state = BlockState::from_json(&json).unwrap();
+    assert_eq!(state, BlockState::new("minecraft:grass_block"));
+    assert_eq!(state.to_json(), json);
+
+    // Blocks of mods are kept as they are
+    let json = json::parse(r#"{"Name": "mod:block", "Properties": {"a": "b"}}"#).unwrap();
+    let state = BlockState::from_json(&json).unwrap();
+    assert!(state.block().is_none());
+    assert_eq!(state.to_json(), json);
+
+    let stone = BlockState::from_json(&json::parse(r#""stone""#).unwrap()).unwrap();
+    assert_eq!(stone.to_json(), json::object! { "Name": "minecraft:stone" });
+}
+
+#[test]
+fn default_states_are_valid() {
+    for id in [
+        "minecraft:water",
+        "minecraft:oak_stairs",
+        "minecraft:deepslate",
+    ] {
+        let block = BLOCKS.get(id).unwrap_or_else(|| panic!("{} not found", id));
+        assert!(!block.properties.is_empty());
+        assert!(BlockState::new(id).invalid_properties().is_empty());
+    }
+    let mut state = BlockState::new("minecraft:water");
+    state.properties.insert("level".into(), "16".into());
+    assert_eq!(state.invalid_properties(), ["level"]);
+}
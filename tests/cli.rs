@@ -0,0 +1,78 @@
+//! Runs the command line on `tests/corpus` and checks the exit statuses scripts rely on.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use walkdir::WalkDir;
+
+fn corpus() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus")
+}
+
+fn run(args: &[&str], pack: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_datapack_creato_rs"))
+        .args(args)
+        .arg(pack)
+        .output()
+        .unwrap()
+}
+
+/// Copy of the corpus in a directory of its own, for commands that write files.
+fn corpus_copy(name: &str) -> PathBuf {
+    let copy = std::env::temp_dir().join(format!("{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&copy);
+    for entry in WalkDir::new(corpus()).into_iter().filter_map(|e| e.ok()) {
+        let to = copy.join(entry.path().strip_prefix(corpus()).unwrap());
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&to).unwrap();
+        } else {
+            fs::copy(entry.path(), &to).unwrap();
+        }
+    }
+    copy
+}
+
+#[test]
+fn validate_succeeds_on_the_corpus() {
+    let output = run(&["validate"], &corpus());
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn check_roundtrip_succeeds_on_the_corpus() {
+    let output = run(&["check-roundtrip"], &corpus());
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+}
+
+#[test]
+fn fmt_check_succeeds_after_fmt() {
+    let pack = corpus_copy("fmt_check");
+    // The corpus is indented with 2 spaces, the editor saves with 4
+    assert_eq!(run(&["fmt", "--check"], &pack).status.code(), Some(1));
+    assert!(run(&["fmt"], &pack).status.success());
+    let output = run(&["fmt", "--check"], &pack);
+    fs::remove_dir_all(&pack).unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stdout)
+    );
+}
+
+#[test]
+fn usage_errors_exit_with_2() {
+    assert_eq!(run(&["fmt", "--fix"], &corpus()).status.code(), Some(2));
+    assert_eq!(
+        run(&["validate", "other_pack"], &corpus()).status.code(),
+        Some(2)
+    );
+}
@@ -0,0 +1,108 @@
+use std::fs::File;
+use std::path::Path;
+
+use datapack_creato_rs::nodes::data_types::{ComplexDataType, DataType};
+use datapack_creato_rs::version::McVersion;
+use datapack_creato_rs::window::{Window, WindowType};
+use eframe::epaint::Pos2;
+use egui_node_graph::NodeId;
+
+fn open_end() -> Window {
+    let project_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus");
+    let path = project_path.join("data/minecraft/worldgen/noise_settings/end.json");
+    let json = json::parse(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    let mut window = Window::from_file(
+        File::open(&path).unwrap(),
+        path.clone(),
+        &project_path,
+        McVersion::V1_20,
+    )
+    .unwrap();
+    window.deserialize(&json);
+    window
+}
+
+fn empty_window(window_type: WindowType) -> Window {
+    Window::new(
+        "pasted".to_string(),
+        "test".to_string(),
+        window_type,
+        Path::new("/nonexistent"),
+        McVersion::V1_20,
+    )
+}
+
+fn output_type(window: &Window, node_id: NodeId) -> DataType {
+    let graph = &window.state.graph;
+    let output_id = graph[node_id].output_ids().next().unwrap();
+    graph.get_output(output_id).typ
+}
+
+#[test]
+fn copied_nodes_paste_into_another_window() {
+    let mut window = open_end();
+    // The density function that the output node's first field is made of
+    let copied = window.state.node_order[1];
+    window.state.selected_nodes = vec![window.root_node, copied];
+    let text = window.copy_selection().unwrap().unwrap();
+    let json = json::parse(&text).unwrap();
+    assert_eq!(json, window.serialize_node(copied).unwrap());
+
+    let mut other = empty_window(WindowType::DensityFunction);
+    let errors = other.paste(&text, Pos2::new(100., 100.));
+    assert!(
+        errors.is_empty(),
+        "{:?}",
+        errors.iter().map(|e| e.to_string()).collect::<Vec<_>>()
+    );
+    let pasted = other.state.selected_nodes[0];
+    assert_eq!(other.serialize_node(pasted).unwrap(), json);
+    assert_eq!(
+        output_type(&other, pasted),
+        DataType::Single(ComplexDataType::DensityFunction)
+    );
+}
+
+#[test]
+fn pasted_json_becomes_the_node_it_reads_as() {
+    let mut window = empty_window(WindowType::NoiseSettings);
+    let text = r#"[
+        {"firstOctave": -7, "amplitudes": [1, 1]},
+        {"type": "minecraft:condition", "if_true": {"type": "minecraft:above_preliminary_surface"},
+            "then_run": {"type": "minecraft:block", "result_state": {"Name": "minecraft:stone"}}}
+    ]"#;
+    let errors = window.paste(text, Pos2::ZERO);
+    assert!(
+        errors.is_empty(),
+        "{:?}",
+        errors.iter().map(|e| e.to_string()).collect::<Vec<_>>()
+    );
+    let types: Vec<DataType> = window
+        .state
+        .selected_nodes
+        .iter()
+        .map(|id| output_type(&window, *id))
+        .collect();
+    assert!(types.contains(&DataType::Single(ComplexDataType::Noise)));
+    assert!(types.contains(&DataType::Single(ComplexDataType::SurfaceRule)));
+
+    assert!(!window.paste("[1, 2]", Pos2::ZERO).is_empty());
+    assert!(!window.paste("not json", Pos2::ZERO).is_empty());
+}
+
+#[test]
+fn duplicates_are_written_like_the_original() {
+    let mut window = open_end();
+    let copied = window.state.node_order[1];
+    let nodes = window.state.graph.nodes.len();
+    window.state.selected_nodes = vec![copied];
+    let errors = window.duplicate_selection();
+    assert!(errors.is_empty());
+    assert!(window.state.graph.nodes.len() > nodes + 1);
+    let duplicate = window.state.selected_nodes[0];
+    assert_ne!(duplicate, copied);
+    assert_eq!(
+        window.serialize_node(duplicate).unwrap(),
+        window.serialize_node(copied).unwrap()
+    );
+}
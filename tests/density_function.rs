@@ -0,0 +1,241 @@
+//! Density functions computed at block positions, compared to values worked out by hand or
+//! with the game's noise code.
+
+use std::fs::File;
+use std::path::Path;
+
+use datapack_creato_rs::app::Identifier;
+use datapack_creato_rs::evaluator::density_function::DensityFunction;
+use datapack_creato_rs::version::McVersion;
+use datapack_creato_rs::window::{Window, WindowType};
+
+/// Window of the newest version, which has every density function.
+fn density_function(text: &str) -> Window {
+    let mut window = Window::new(
+        "test".to_string(),
+        "test".to_string(),
+        WindowType::DensityFunction,
+        Path::new("/nonexistent"),
+        McVersion::V1_21_2,
+    );
+    let errors = window.deserialize(&json::parse(text).unwrap());
+    assert!(
+        errors.is_empty(),
+        "{:?}",
+        errors.iter().map(|e| e.to_string()).collect::<Vec<_>>()
+    );
+    window
+}
+
+fn offset_noise() -> Window {
+    let project_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus");
+    let path = project_path.join("data/minecraft/worldgen/noise/offset.json");
+    let json = json::parse(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    let mut window = Window::from_file(
+        File::open(&path).unwrap(),
+        path.clone(),
+        &project_path,
+        McVersion::V1_21_2,
+    )
+    .unwrap();
+    window.deserialize(&json);
+    window
+}
+
+/// Compiles `text` for seed 0, `minecraft:offset` is the only noise it can use.
+fn compile(text: &str) -> DensityFunction {
+    let noise = offset_noise();
+    let resolve = |id: &Identifier| (*id == noise.identifier()).then_some(&noise);
+    density_function(text)
+        .compile_density_function(None, 0, &resolve)
+        .unwrap()
+}
+
+fn assert_values(function: &DensityFunction, expected: &[((i32, i32, i32), f64)]) {
+    for ((x, y, z), value) in expected {
+        let actual = function.compute(*x, *y, *z);
+        assert!(
+            (actual - value).abs() < 1e-9,
+            "{} instead of {} at {} {} {}",
+            actual,
+            value,
+            x,
+            y,
+            z
+        );
+    }
+}
+
+/// `y / 100` between y -100 and 200.
+const GRADIENT: &str = r#"{
+    "type": "minecraft:y_clamped_gradient",
+    "from_y": -100,
+    "to_y": 200,
+    "from_value": -1.0,
+    "to_value": 2.0
+}"#;
+
+#[test]
+fn clamp_keeps_values_between_min_and_max() {
+    let function = compile(&format!(
+        r#"{{"type": "minecraft:clamp", "input": {}, "min": -0.5, "max": 0.25}}"#,
+        GRADIENT
+    ));
+    assert_values(
+        &function,
+        &[
+            ((0, -100, 0), -0.5),
+            ((0, -20, 0), -0.2),
+            ((0, 10, 0), 0.1),
+            ((0, 150, 0), 0.25),
+        ],
+    );
+
+    let noise = offset_noise();
+    let resolve = |id: &Identifier| (*id == noise.identifier()).then_some(&noise);
+    let window =
+        density_function(r#"{"type": "minecraft:clamp", "input": 0.0, "min": 1.0, "max": -1.0}"#);
+    assert!(window.compile_density_function(None, 0, &resolve).is_err());
+}
+
+#[test]
+fn spline_is_interpolated_and_extended_past_its_points() {
+    let function = compile(&format!(
+        r#"{{
+            "type": "minecraft:spline",
+            "spline": {{
+                "coordinate": {},
+                "points": [
+                    {{"location": -1.0, "derivative": 0.0, "value": 0.0}},
+                    {{"location": 1.0, "derivative": 0.5, "value": 1.0}}
+                ]
+            }}
+        }}"#,
+        GRADIENT
+    ));
+    assert_values(
+        &function,
+        &[
+            // below the first point its value, its derivative is 0
+            ((0, -100, 0), 0.),
+            ((0, -50, 0), 0.109375),
+            ((0, 0, 0), 0.375),
+            ((0, 100, 0), 1.),
+            // past the last point along its derivative
+            ((0, 150, 0), 1.25),
+        ],
+    );
+}
+
+#[test]
+fn squeeze_clamps_its_argument() {
+    for (argument, value) in [
+        (0.5, 0.24479166666666666),
+        (-0.5, -0.24479166666666666),
+        (2., 0.4583333333333333),
+        (-3., -0.4583333333333333),
+    ] {
+        let function = compile(&format!(
+            r#"{{"type": "minecraft:squeeze", "argument": {}}}"#,
+            argument
+        ));
+        assert_values(&function, &[((0, 0, 0), value)]);
+    }
+}
+
+// The values below come from a reimplementation of the game's noise classes in Java, seed 0
+
+#[test]
+fn shifts_sample_the_offset_noise() {
+    let shift = |typ: &str| {
+        compile(&format!(
+            r#"{{"type": "minecraft:{}", "argument": "minecraft:offset"}}"#,
+            typ
+        ))
+    };
+    assert_values(
+        &shift("shift_a"),
+        &[
+            ((0, 0, 0), -2.1743453665579744),
+            ((100, 64, 200), -0.704120668977371),
+            ((-300, -20, 77), -2.1646597439677437),
+        ],
+    );
+    assert_values(
+        &shift("shift_b"),
+        &[
+            ((0, 0, 0), -2.1743453665579744),
+            ((100, 64, 200), -0.7521789112506344),
+            ((-300, -20, 77), 0.6958232370764806),
+        ],
+    );
+    assert_values(
+        &shift("shift"),
+        &[
+            ((0, 0, 0), -2.1743453665579744),
+            ((100, 64, 200), 0.2679885921631667),
+            ((-300, -20, 77), -1.8070373200459229),
+        ],
+    );
+}
+
+#[test]
+fn weird_scaled_sampler_scales_by_rarity() {
+    let sampler = |input: f64, mapper: &str| {
+        compile(&format!(
+            r#"{{
+                "type": "minecraft:weird_scaled_sampler",
+                "input": {},
+                "noise": "minecraft:offset",
+                "rarity_value_mapper": "{}"
+            }}"#,
+            input, mapper
+        ))
+    };
+    // rarity 1.5
+    assert_values(
+        &sampler(0.3, "type_1"),
+        &[
+            ((0, 0, 0), 0.8153795124592405),
+            ((100, 64, 200), 0.33029707386173757),
+            ((-300, -20, 77), 0.6436699232555454),
+        ],
+    );
+    // rarity 0.5
+    assert_values(
+        &sampler(-0.8, "type_2"),
+        &[
+            ((0, 0, 0), 0.2717931708197468),
+            ((100, 64, 200), 0.09128085887414622),
+            ((-300, -20, 77), 0.3047989742915306),
+        ],
+    );
+}
+
+#[test]
+fn find_top_surface_steps_down_from_the_upper_bound() {
+    let find_top_surface = |lower_bound: i32, cell_height: i32| {
+        format!(
+            r#"{{
+                "type": "minecraft:find_top_surface",
+                "density": {{"type": "minecraft:mul", "argument1": -1.0, "argument2": {}}},
+                "upper_bound": 100.0,
+                "lower_bound": {},
+                "cell_height": {}
+            }}"#,
+            GRADIENT, lower_bound, cell_height
+        )
+    };
+    // the density is positive below y 0, the cells start at 96
+    assert_values(
+        &compile(&find_top_surface(-64, 8)),
+        &[((0, 0, 0), -8.), ((30, 150, -12), -8.)],
+    );
+    // nothing above the lower bound is solid
+    assert_values(&compile(&find_top_surface(40, 8)), &[((0, 0, 0), 40.)]);
+
+    let noise = offset_noise();
+    let resolve = |id: &Identifier| (*id == noise.identifier()).then_some(&noise);
+    let window = density_function(&find_top_surface(-64, 0));
+    assert!(window.compile_density_function(None, 0, &resolve).is_err());
+}
@@ -0,0 +1,45 @@
+//! Problems found while loading stay listed when the project is saved, until their file is
+//! read again or saved without problems.
+
+use datapack_creato_rs::app::Identifier;
+use datapack_creato_rs::diagnostics::Diagnostics;
+use datapack_creato_rs::errors::AppError;
+use datapack_creato_rs::window::WindowType;
+
+#[test]
+fn saving_replaces_only_the_save_errors() {
+    let id = Identifier::new(
+        "minecraft".to_string(),
+        "plains".to_string(),
+        WindowType::Biome,
+    );
+    let mut diagnostics = Diagnostics::default();
+    diagnostics.push(AppError::WrongData("unknown carver".into()).with_window(id.clone()));
+    diagnostics.set_save_errors(vec![
+        AppError::FileWrite("permission denied".to_string()).with_window(id.clone())
+    ]);
+    assert_eq!(diagnostics.len(), 2);
+    diagnostics.set_save_errors(Vec::new());
+    assert_eq!(diagnostics.len(), 1);
+}
+
+#[test]
+fn load_errors_of_a_fixed_window_are_dropped() {
+    let id =
+        |name: &str| Identifier::new("minecraft".to_string(), name.to_string(), WindowType::Biome);
+    let mut diagnostics = Diagnostics::default();
+    diagnostics.extend([
+        AppError::WrongData("unknown carver".into()).with_window(id("plains")),
+        AppError::WrongData("missing effects".into()).with_window(id("plains")),
+        AppError::WrongData("unknown feature".into()).with_window(id("desert")),
+        AppError::FileRead("not a json file".to_string()),
+    ]);
+    diagnostics.set_save_errors(vec![
+        AppError::FileWrite("permission denied".to_string()).with_window(id("desert"))
+    ]);
+    diagnostics.clear_window(&id("plains"));
+    assert_eq!(diagnostics.len(), 3);
+    diagnostics.clear_window(&id("desert"));
+    // the problems of the last save stay until the next one
+    assert_eq!(diagnostics.len(), 2);
+}
@@ -0,0 +1,113 @@
+use std::fs::File;
+use std::path::Path;
+
+use datapack_creato_rs::json_view::{node_spans, pretty_with_spans};
+use datapack_creato_rs::nodes::add_node;
+use datapack_creato_rs::nodes::node_types::NodeTemplate;
+use datapack_creato_rs::serializer::canonical;
+use datapack_creato_rs::version::McVersion;
+use datapack_creato_rs::window::Window;
+use eframe::epaint::Pos2;
+use walkdir::WalkDir;
+
+fn corpus() -> std::path::PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus")
+}
+
+fn open_end() -> Window {
+    let project_path = corpus();
+    let path = project_path.join("data/minecraft/worldgen/noise_settings/end.json");
+    let json = json::parse(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    let mut window = Window::from_file(
+        File::open(&path).unwrap(),
+        path.clone(),
+        &project_path,
+        McVersion::V1_20,
+    )
+    .unwrap();
+    window.deserialize(&json);
+    window
+}
+
+#[test]
+fn text_is_the_pretty_json_with_every_value_in_it() {
+    for entry in WalkDir::new(corpus()).into_iter().filter_map(|e| e.ok()) {
+        if entry.path().extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let json = canonical(json::parse(&std::fs::read_to_string(entry.path()).unwrap()).unwrap());
+        let (text, spans) = pretty_with_spans(&json);
+        assert_eq!(text, json.pretty(4), "{}", entry.path().display());
+        for span in &spans {
+            assert_eq!(&json::parse(&text[span.range.clone()]).unwrap(), span.value);
+        }
+    }
+}
+
+#[test]
+fn selected_nodes_are_found_in_the_text() {
+    let window = open_end();
+    let json = canonical(window.serialize().unwrap());
+    let (text, spans) = pretty_with_spans(&json);
+    for node_id in window.state.node_order.iter().skip(1) {
+        let ranges = node_spans(&window, *node_id, &spans);
+        assert!(!ranges.is_empty());
+        let value = canonical(window.serialize_node(*node_id).unwrap());
+        for range in ranges {
+            assert_eq!(json::parse(&text[range]).unwrap(), value);
+        }
+    }
+}
+
+#[test]
+fn edited_text_is_read_into_the_graph() {
+    let mut window = open_end();
+    let unconnected = add_node(
+        &mut window.state,
+        &mut window.user_state,
+        NodeTemplate::Noise,
+        Pos2::ZERO,
+    );
+    let mut json = window.serialize().unwrap();
+    json["sea_level"] = 12.into();
+    json["noise_router"]["barrier"] = 0.5.into();
+
+    let errors = window.read_json(&json);
+    assert!(
+        errors.is_empty(),
+        "{:?}",
+        errors.iter().map(|e| e.to_string()).collect::<Vec<_>>()
+    );
+    assert_eq!(canonical(window.serialize().unwrap()), canonical(json));
+    assert!(window.state.graph.nodes.contains_key(unconnected));
+}
+
+#[test]
+fn nodes_stay_where_they_were_after_an_edit() {
+    let mut window = open_end();
+    let positions = |window: &Window| {
+        let mut positions: Vec<(i32, i32)> = window
+            .state
+            .node_order
+            .iter()
+            .map(|id| window.state.node_positions[*id])
+            .map(|pos| (pos.x as i32, pos.y as i32))
+            .collect();
+        positions.sort_unstable();
+        positions
+    };
+    let before = positions(&window);
+    let mut json = window.serialize().unwrap();
+    json["sea_level"] = 12.into();
+    assert!(window.read_json(&json).is_empty());
+    assert_eq!(positions(&window), before);
+
+    json["noise_router"]["barrier"] = json::object! {"type": "minecraft:abs", "argument": 0.5};
+    assert!(window.read_json(&json).is_empty());
+    let after = positions(&window);
+    assert_eq!(after.len(), before.len() + 1);
+    let root_x = window.state.node_positions[window.root_node].x;
+    let added: Vec<_> = after.iter().filter(|pos| !before.contains(pos)).collect();
+    assert!(!added.is_empty());
+    assert!(added.iter().all(|(x, _)| (*x as f32) < root_x));
+}
@@ -0,0 +1,52 @@
+use std::fs::File;
+use std::path::Path;
+
+use datapack_creato_rs::version::McVersion;
+use datapack_creato_rs::window::Window;
+
+fn open_end() -> Window {
+    let project_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus");
+    let path = project_path.join("data/minecraft/worldgen/noise_settings/end.json");
+    let json = json::parse(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    let mut window = Window::from_file(
+        File::open(&path).unwrap(),
+        path.clone(),
+        &project_path,
+        McVersion::V1_20,
+    )
+    .unwrap();
+    window.deserialize(&json);
+    window
+}
+
+#[test]
+fn nodes_are_left_of_what_they_feed() {
+    let window = open_end();
+    let graph = &window.state.graph;
+    let positions = &window.state.node_positions;
+    assert!(graph.nodes.len() > 10);
+    for (input_id, output_id) in graph.connections.iter() {
+        let parent = graph.get_input(input_id).node;
+        let child = graph.get_output(*output_id).node;
+        assert!(positions[child].x < positions[parent].x);
+    }
+    let mut seen = Vec::new();
+    for (_, pos) in positions.iter() {
+        assert!(!seen.contains(pos), "two nodes at {:?}", pos);
+        seen.push(*pos);
+    }
+}
+
+#[test]
+fn arranging_the_selection_leaves_other_nodes() {
+    let mut window = open_end();
+    let before = window.state.node_positions.clone();
+    let selected: Vec<_> = window.state.node_order[1..4].to_vec();
+    window.state.selected_nodes = selected.clone();
+    window.arrange_selection();
+    for (id, pos) in before.iter() {
+        if !selected.contains(&id) {
+            assert_eq!(window.state.node_positions[id], *pos);
+        }
+    }
+}
@@ -0,0 +1,41 @@
+//! Entries are added to and removed from list inputs without panicking on missing lists.
+
+use std::path::Path;
+
+use datapack_creato_rs::nodes::add_node;
+use datapack_creato_rs::nodes::data_types::{
+    decrease_node_list_length, increase_node_list_length, list_entries,
+};
+use datapack_creato_rs::nodes::node_types::NodeTemplate;
+use datapack_creato_rs::version::McVersion;
+use datapack_creato_rs::window::{Window, WindowType};
+use eframe::epaint::Pos2;
+
+#[test]
+fn list_length_changes_only_for_existing_lists() {
+    let mut window = Window::new(
+        "test".to_string(),
+        "test".to_string(),
+        WindowType::DensityFunction,
+        Path::new("/nonexistent"),
+        McVersion::V1_20,
+    );
+    let node_id = add_node(
+        &mut window.state,
+        &mut window.user_state,
+        NodeTemplate::Spline,
+        Pos2::ZERO,
+    );
+    let graph = &mut window.state.graph;
+    assert_eq!(list_entries(graph, node_id, "points"), Some((1, 1)));
+    assert!(increase_node_list_length(graph, node_id, "points").is_some());
+    assert_eq!(list_entries(graph, node_id, "points"), Some((1, 2)));
+    assert!(increase_node_list_length(graph, node_id, "no such list").is_none());
+
+    assert!(decrease_node_list_length(graph, node_id, "points").is_some());
+    assert!(decrease_node_list_length(graph, node_id, "points").is_some());
+    assert!(increase_node_list_length(graph, node_id, "points").is_none());
+
+    graph.remove_node(node_id);
+    assert!(increase_node_list_length(graph, node_id, "points").is_none());
+}
@@ -0,0 +1,74 @@
+//! `pack.mcmeta` is written back with what the editor doesn't show, and tells where the
+//! namespaces are.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use datapack_creato_rs::cli::json_diff;
+use datapack_creato_rs::pack::{data_root, FormatRange, PackSettings, PACK_MCMETA};
+
+#[test]
+fn pack_mcmeta_keeps_unknown_fields() {
+    let expected = json::parse(
+        r#"{
+            "pack": {
+                "pack_format": 15,
+                "description": {"text": "Terrain", "color": "green"},
+                "supported_formats": [15, 18]
+            },
+            "filter": {"block": [{"namespace": "minecraft", "path": "worldgen/biome/.*"}]},
+            "overlays": {"entries": [{"formats": 18, "directory": "overlay_18"}]},
+            "features": {"enabled": ["minecraft:trade_rebalance"]}
+        }"#,
+    )
+    .unwrap();
+    let settings = PackSettings::from_json(expected.clone()).unwrap();
+    assert_eq!(
+        settings.supported_formats,
+        Some(FormatRange { min: 15, max: 18 })
+    );
+    assert_eq!(settings.overlays[0].directory, "overlay_18");
+    assert_eq!(
+        json_diff(&expected, &settings.to_json()),
+        Vec::<String>::new()
+    );
+}
+
+/// Empty project folder of its own for `name`.
+fn project(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&path);
+    fs::create_dir_all(&path).unwrap();
+    path
+}
+
+fn create_dirs(project_path: &Path, dirs: &[&str]) {
+    for dir in dirs {
+        fs::create_dir_all(project_path.join(dir)).unwrap();
+    }
+}
+
+#[test]
+fn namespaces_are_in_data_of_a_pack() {
+    let pack = project("data_root_pack");
+    fs::write(pack.join(PACK_MCMETA), "{}").unwrap();
+    assert_eq!(data_root(&pack), pack.join("data"));
+    // even if there are old layout folders next to it
+    create_dirs(&pack, &["data/worldgen", "minecraft/worldgen"]);
+    assert_eq!(data_root(&pack), pack.join("data"));
+    fs::remove_dir_all(&pack).unwrap();
+}
+
+#[test]
+fn namespaces_of_the_old_layout_are_in_the_project_folder() {
+    let old = project("data_root_old");
+    create_dirs(&old, &["data/worldgen/biome", "minecraft/worldgen"]);
+    assert_eq!(data_root(&old), old);
+    fs::remove_dir_all(&old).unwrap();
+
+    let new = project("data_root_new");
+    assert_eq!(data_root(&new), new.join("data"));
+    create_dirs(&new, &["data/minecraft/worldgen"]);
+    assert_eq!(data_root(&new), new.join("data"));
+    fs::remove_dir_all(&new).unwrap();
+}
@@ -0,0 +1,176 @@
+//! Opens every file of `tests/corpus`, a datapack of worldgen files based on vanilla 1.20, and
+//! checks that saving it again gives back the same JSON.
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use json::JsonValue;
+use walkdir::WalkDir;
+
+use datapack_creato_rs::cli::json_diff;
+use datapack_creato_rs::nodes::node_types::NodeTemplate;
+use datapack_creato_rs::pack::PackSettings;
+use datapack_creato_rs::window::Window;
+
+fn corpus() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus")
+}
+
+fn json_files(project_path: &Path) -> Vec<PathBuf> {
+    let files: Vec<PathBuf> = WalkDir::new(project_path)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "json"))
+        .map(|e| e.into_path())
+        .collect();
+    assert!(!files.is_empty(), "no files in {}", project_path.display());
+    files
+}
+
+/// Opens `path` like the app does, vanilla files must be read without any error.
+fn open(path: &Path, project_path: &Path, json: &JsonValue) -> Result<Window, String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let version = PackSettings::load(project_path)
+        .map_err(|e| e.to_string())?
+        .version();
+    let mut window = Window::from_file(file, path.to_path_buf(), project_path, version)
+        .map_err(|e| e.to_string())?;
+    let errors: Vec<String> = window
+        .deserialize(json)
+        .iter()
+        .map(|e| e.to_string())
+        .collect();
+    if !errors.is_empty() {
+        return Err(errors.join(", "));
+    }
+    // Types the editor doesn't know are kept as raw JSON without an error
+    let raw = window
+        .state
+        .graph
+        .nodes
+        .values()
+        .filter(|node| matches!(node.user_data.template, NodeTemplate::RawJson(_)))
+        .count();
+    if raw > 0 {
+        return Err(format!("{} nodes kept as raw JSON", raw));
+    }
+    Ok(window)
+}
+
+/// Runs `check` on every file, fails with the problems it returned for each of them.
+fn check_corpus(what: &str, check: impl Fn(&Path, &Path) -> Result<Vec<String>, String>) {
+    let project_path = corpus();
+    let files = json_files(&project_path);
+    let mut failures = Vec::new();
+    for path in &files {
+        let name = path.strip_prefix(&project_path).unwrap_or(path).display();
+        match check(path, &project_path) {
+            Ok(diffs) if diffs.is_empty() => {}
+            Ok(diffs) => failures.push(format!("{}:\n    {}", name, diffs.join("\n    "))),
+            Err(e) => failures.push(format!("{}: {}", name, e)),
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "{} of {} files {}:\n{}",
+        failures.len(),
+        files.len(),
+        what,
+        failures.join("\n")
+    );
+}
+
+#[test]
+fn vanilla_files_are_saved_unchanged() {
+    check_corpus("changed when saved", |path, project_path| {
+        let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let expected = json::parse(&text).map_err(|e| e.to_string())?;
+        let window = open(path, project_path, &expected)?;
+        let actual = window.serialize().map_err(|e| e.to_string())?;
+        Ok(json_diff(&expected, &actual))
+    });
+}
+
+#[test]
+fn formatting_is_stable() {
+    check_corpus("changed when formatted twice", |path, project_path| {
+        let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let json = json::parse(&text).map_err(|e| e.to_string())?;
+        let formatted = open(path, project_path, &json)?
+            .serialize_to_string()
+            .map_err(|e| e.to_string())?;
+        let json = json::parse(&formatted).map_err(|e| e.to_string())?;
+        let again = open(path, project_path, &json)?
+            .serialize_to_string()
+            .map_err(|e| e.to_string())?;
+        if formatted == again {
+            return Ok(Vec::new());
+        }
+        let line = formatted
+            .lines()
+            .zip(again.lines())
+            .position(|(a, b)| a != b)
+            .unwrap_or_else(|| formatted.lines().count().min(again.lines().count()));
+        Ok(vec![format!(
+            "differs from line {}, {} lines instead of {}",
+            line + 1,
+            again.lines().count(),
+            formatted.lines().count()
+        )])
+    });
+}
+
+#[test]
+fn vertical_anchors_and_surface_types_are_read() {
+    let project_path = corpus();
+    let path = project_path.join("data/minecraft/worldgen/noise_settings/nether.json");
+    let json = json::parse(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    let version = PackSettings::load(&project_path).unwrap().version();
+    let mut window = Window::from_file(
+        File::open(&path).unwrap(),
+        path.clone(),
+        &project_path,
+        version,
+    )
+    .unwrap();
+    let errors: Vec<String> = window
+        .deserialize(&json)
+        .iter()
+        .map(|e| e.to_string())
+        .collect();
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(
+        json_diff(&json, &window.serialize().unwrap()),
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+fn single_ids_are_read_as_lists_and_written_back() {
+    let project_path = corpus();
+    let path = project_path.join("data/minecraft/worldgen/biome/soul_sand_valley.json");
+    let mut json = json::parse(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert!(json["carvers"]["air"].is_string());
+    json["features"][0] = "minecraft:basalt_pillar".into();
+    let version = PackSettings::load(&project_path).unwrap().version();
+    let mut window = Window::from_file(
+        File::open(&path).unwrap(),
+        path.clone(),
+        &project_path,
+        version,
+    )
+    .unwrap();
+    let errors: Vec<String> = window
+        .deserialize(&json)
+        .iter()
+        .map(|e| e.to_string())
+        .collect();
+    assert!(errors.is_empty(), "{:?}", errors);
+    let root = &window.state.graph[window.root_node];
+    assert!(root.user_data.unknown_fields.is_empty());
+    assert_eq!(
+        json_diff(&json, &window.serialize().unwrap()),
+        Vec::<String>::new()
+    );
+}
@@ -0,0 +1,114 @@
+use std::fs::File;
+use std::path::Path;
+
+use datapack_creato_rs::evaluator::surface_rule::Column;
+use datapack_creato_rs::nodes::blocks::BlockState;
+use datapack_creato_rs::version::McVersion;
+use datapack_creato_rs::window::Window;
+
+/// Grass on top of the ground if there is no water above, dirt below it for the surface depth.
+const RULE: &str = r#"{
+    "type": "minecraft:sequence",
+    "sequence": [
+        {
+            "type": "minecraft:condition",
+            "if_true": {
+                "type": "minecraft:stone_depth",
+                "offset": 0,
+                "add_surface_depth": false,
+                "secondary_depth_range": 0,
+                "surface_type": "floor"
+            },
+            "then_run": {
+                "type": "minecraft:condition",
+                "if_true": {
+                    "type": "minecraft:water",
+                    "offset": -1,
+                    "surface_depth_multiplier": 0,
+                    "add_stone_depth": false
+                },
+                "then_run": {
+                    "type": "minecraft:block",
+                    "result_state": {"Name": "minecraft:grass_block", "Properties": {"snowy": "false"}}
+                }
+            }
+        },
+        {
+            "type": "minecraft:condition",
+            "if_true": {
+                "type": "minecraft:stone_depth",
+                "offset": 0,
+                "add_surface_depth": true,
+                "secondary_depth_range": 0,
+                "surface_type": "floor"
+            },
+            "then_run": {"type": "minecraft:block", "result_state": {"Name": "minecraft:dirt"}}
+        }
+    ]
+}"#;
+
+fn column(water_level: i32) -> Column {
+    Column {
+        x: 0,
+        z: 0,
+        min_y: 0,
+        height: 128,
+        surface_y: 70,
+        water_level,
+        biome: "minecraft:plains".to_string(),
+        surface_depth: 3,
+        surface_secondary: 0.,
+        steep: false,
+        cold: false,
+        default_block: BlockState::new("minecraft:stone"),
+        default_fluid: BlockState::new("minecraft:water"),
+    }
+}
+
+#[test]
+fn surface_rule_places_blocks_in_a_column() {
+    let project_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus");
+    let path = project_path.join("data/minecraft/worldgen/noise_settings/end.json");
+    let mut json = json::parse(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    json["surface_rule"] = json::parse(RULE).unwrap();
+    let mut window = Window::from_file(
+        File::open(&path).unwrap(),
+        path.clone(),
+        &project_path,
+        McVersion::V1_20,
+    )
+    .unwrap();
+    let errors = window.deserialize(&json);
+    assert!(errors.is_empty(), "{:?}", errors.iter().map(|e| e.to_string()).collect::<Vec<_>>());
+    let rule = window.compile_surface_rule(None, 0, &|_| None).unwrap();
+
+    let names = |water_level| -> Vec<String> {
+        column(water_level).blocks(&rule)[65..=72]
+            .iter()
+            .map(|block| {
+                block
+                    .state
+                    .name
+                    .trim_start_matches("minecraft:")
+                    .to_string()
+            })
+            .collect()
+    };
+    assert_eq!(
+        names(63),
+        [
+            "stone",
+            "stone",
+            "dirt",
+            "dirt",
+            "dirt",
+            "grass_block",
+            "air",
+            "air"
+        ]
+    );
+    assert_eq!(
+        names(80),
+        ["stone", "stone", "dirt", "dirt", "dirt", "dirt", "water", "water"]
+    );
+}
@@ -0,0 +1,135 @@
+//! References to a file are found and renamed, ids of other registries with the same name aren't.
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+use datapack_creato_rs::app::{App, Identifier};
+use datapack_creato_rs::pack::PackSettings;
+use datapack_creato_rs::usages::rename;
+use datapack_creato_rs::window::{Window, WindowType};
+use strum::EnumCount;
+use walkdir::WalkDir;
+
+fn corpus() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus")
+}
+
+fn open(relative_path: &str) -> Window {
+    let project_path = corpus();
+    let path = project_path.join(relative_path);
+    let json = json::parse(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    let version = PackSettings::load(&project_path).unwrap().version();
+    let mut window =
+        Window::from_file(File::open(&path).unwrap(), path, &project_path, version).unwrap();
+    assert!(window.deserialize(&json).is_empty());
+    window
+}
+
+/// Copy of the corpus in a directory of its own, opened like the editor does.
+fn project(name: &str) -> (PathBuf, [HashMap<Identifier, Window>; WindowType::COUNT]) {
+    let copy = std::env::temp_dir().join(format!("{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&copy);
+    for entry in WalkDir::new(corpus()).into_iter().filter_map(|e| e.ok()) {
+        let to = copy.join(entry.path().strip_prefix(corpus()).unwrap());
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&to).unwrap();
+        } else {
+            fs::copy(entry.path(), &to).unwrap();
+        }
+    }
+    (copy, Default::default())
+}
+
+fn load(project_path: &Path, file_structure: &mut [HashMap<Identifier, Window>]) -> Vec<String> {
+    let pack = PackSettings::load(project_path).unwrap();
+    App::load_from_fs(&project_path.to_path_buf(), &pack, file_structure)
+        .iter()
+        .map(|e| e.to_string())
+        .collect()
+}
+
+fn id(path: &str, window_type: WindowType) -> Identifier {
+    Identifier::new("minecraft".to_string(), path.to_string(), window_type)
+}
+
+#[test]
+fn references_of_the_same_type_are_renamed() {
+    let mut window = open("data/minecraft/worldgen/noise_settings/nether.json");
+    let shift_x = id("shift_x", WindowType::DensityFunction);
+    assert_eq!(window.usages_of(&shift_x).len(), 2);
+    let renamed = id("shift/x", WindowType::DensityFunction);
+    assert_eq!(window.rename_references(&shift_x, &renamed), 2);
+    assert!(window.usages_of(&shift_x).is_empty());
+    let json = window.serialize().unwrap();
+    assert_eq!(
+        json["noise_router"]["temperature"]["shift_x"],
+        "minecraft:shift/x"
+    );
+}
+
+#[test]
+fn dull_references_are_not_renamed() {
+    let mut window = open("data/minecraft/worldgen/biome/soul_sand_valley.json");
+    let expected = window.serialize().unwrap();
+    assert_eq!(expected["carvers"]["air"], "minecraft:nether_cave");
+    let nether_cave = id("nether_cave", WindowType::DensityFunction);
+    assert!(window.usages_of(&nether_cave).is_empty());
+    assert_eq!(
+        window.rename_references(&nether_cave, &id("caves", WindowType::DensityFunction)),
+        0
+    );
+    assert_eq!(window.serialize().unwrap(), expected);
+}
+
+#[test]
+fn files_referring_to_a_renamed_file_are_saved() {
+    let (project_path, mut file_structure) = project("rename");
+    assert_eq!(
+        load(&project_path, &mut file_structure),
+        Vec::<String>::new()
+    );
+    let shift_x = id("shift_x", WindowType::DensityFunction);
+    let renamed = id("shift/x", WindowType::DensityFunction);
+    let (count, errors) = rename(&mut file_structure, &project_path, &shift_x, &renamed).unwrap();
+    assert_eq!(count, 4);
+    assert!(errors.is_empty());
+    let worldgen = project_path.join("data/minecraft/worldgen");
+    assert!(!worldgen.join("density_function/shift_x.json").exists());
+    assert!(worldgen.join("density_function/shift/x.json").exists());
+    let nether = fs::read_to_string(worldgen.join("noise_settings/nether.json")).unwrap();
+    assert!(nether.contains("\"minecraft:shift/x\""));
+    assert!(!nether.contains("\"minecraft:shift_x\""));
+
+    let nether = id("nether", WindowType::NoiseSettings);
+    let window = file_structure[WindowType::NoiseSettings as usize]
+        .get_mut(&nether)
+        .unwrap();
+    // what is worked out from the graph, like the preview, sees both changes
+    let generation = window.history.generation();
+    assert!(generation > 0);
+    window.undo();
+    assert!(window.history.generation() > generation);
+    assert_eq!(window.usages_of(&shift_x).len(), 2);
+    fs::remove_dir_all(&project_path).unwrap();
+}
+
+#[test]
+fn nothing_is_renamed_while_a_broken_file_refers_to_it() {
+    let (project_path, mut file_structure) = project("rename_broken");
+    let broken = project_path.join("data/minecraft/worldgen/density_function/broken.json");
+    fs::write(
+        &broken,
+        "{\"type\": \"minecraft:shift_a\", \"argument\": \"minecraft:shift_x\"",
+    )
+    .unwrap();
+    assert_eq!(load(&project_path, &mut file_structure).len(), 1);
+    let shift_x = id("shift_x", WindowType::DensityFunction);
+    let renamed = id("shift/x", WindowType::DensityFunction);
+    assert!(rename(&mut file_structure, &project_path, &shift_x, &renamed).is_err());
+    assert!(project_path
+        .join("data/minecraft/worldgen/density_function/shift_x.json")
+        .exists());
+    assert!(file_structure[WindowType::DensityFunction as usize].contains_key(&shift_x));
+    fs::remove_dir_all(&project_path).unwrap();
+}
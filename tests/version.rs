@@ -0,0 +1,250 @@
+//! What changes between the versions a pack can be made for.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use datapack_creato_rs::app::{App, Identifier};
+use datapack_creato_rs::nodes::add_node;
+use datapack_creato_rs::nodes::data_types::ComplexDataType;
+use datapack_creato_rs::nodes::inner_data_types::density_function::DensityFunctionType;
+use datapack_creato_rs::nodes::inner_data_types::surface_rule::SurfaceRuleType;
+use datapack_creato_rs::nodes::inner_data_types::surface_rule_condition::SurfaceRuleConditionType;
+use datapack_creato_rs::nodes::node_types::{AllNodeTemplates, NodeTemplate};
+use datapack_creato_rs::version::McVersion;
+use datapack_creato_rs::window::{Window, WindowType};
+use eframe::epaint::Pos2;
+use egui_node_graph::NodeTemplateIter;
+use json::JsonValue;
+use strum::{EnumCount, IntoEnumIterator};
+
+/// Names of the inputs of a `template` node built for `version`.
+fn inputs(version: McVersion, template: NodeTemplate) -> Vec<String> {
+    let mut window = Window::new(
+        "test".to_string(),
+        "test".to_string(),
+        WindowType::Biome,
+        Path::new("/nonexistent"),
+        version,
+    );
+    let node_id = add_node(
+        &mut window.state,
+        &mut window.user_state,
+        template,
+        Pos2::ZERO,
+    );
+    window.state.graph[node_id]
+        .inputs
+        .iter()
+        .map(|(name, _)| name.clone())
+        .collect()
+}
+
+#[test]
+fn precipitation_flag_starts_with_pack_format_12() {
+    let biome = NodeTemplate::Output(WindowType::Biome);
+    for (pack_format, flag) in [(10, false), (12, true), (14, true), (48, true)] {
+        let version = McVersion::from_pack_format(pack_format);
+        assert_eq!(version.has_field(biome, "has_precipitation"), flag);
+        assert_eq!(version.has_field(biome, "precipitation"), !flag);
+    }
+}
+
+#[test]
+fn biomes_are_built_with_the_fields_of_the_version() {
+    let biome = NodeTemplate::Output(WindowType::Biome);
+    let old = inputs(McVersion::V1_19, biome);
+    let new = inputs(McVersion::V1_21, biome);
+    assert!(old.contains(&"precipitation".to_string()));
+    assert!(!old.contains(&"has_precipitation".to_string()));
+    assert!(new.contains(&"has_precipitation".to_string()));
+    assert!(!new.contains(&"precipitation".to_string()));
+    let without = |inputs: &[String], field: &str| -> Vec<String> {
+        inputs
+            .iter()
+            .filter(|name| *name != field)
+            .cloned()
+            .collect()
+    };
+    assert_eq!(
+        without(&old, "precipitation"),
+        without(&new, "has_precipitation")
+    );
+}
+
+#[test]
+fn density_functions_have_the_same_fields_where_they_are_read() {
+    for typ in DensityFunctionType::iter() {
+        let template = NodeTemplate::DensityFunction(typ);
+        let first = McVersion::iter()
+            .find(|version| version.supports(template))
+            .unwrap();
+        for version in McVersion::iter().filter(|version| version.supports(template)) {
+            assert_eq!(
+                inputs(first, template),
+                inputs(version, template),
+                "{:?}",
+                typ
+            );
+        }
+    }
+    let find_top_surface = NodeTemplate::DensityFunction(DensityFunctionType::FindTopSurface);
+    assert!(!McVersion::V1_21.supports(find_top_surface));
+    assert!(McVersion::V1_21_2.supports(find_top_surface));
+    assert_eq!(McVersion::from_pack_format(57), McVersion::V1_21_2);
+}
+
+#[test]
+fn node_finder_and_type_switch_offer_what_the_version_has() {
+    for version in McVersion::iter() {
+        assert!(AllNodeTemplates(version)
+            .all_kinds()
+            .into_iter()
+            .all(|template| version.supports(template)));
+        assert!(SurfaceRuleType::iter().eq(version.types::<SurfaceRuleType>()));
+        assert!(SurfaceRuleConditionType::iter().eq(version.types::<SurfaceRuleConditionType>()));
+    }
+    // The finder adds a constant density function, its type switch lists the others
+    let constant = NodeTemplate::DensityFunction(DensityFunctionType::Constant);
+    assert!(AllNodeTemplates(McVersion::V1_21)
+        .all_kinds()
+        .contains(&constant));
+    assert!(!McVersion::V1_21
+        .types::<DensityFunctionType>()
+        .any(|typ| typ == DensityFunctionType::FindTopSurface));
+    assert!(McVersion::V1_21_2
+        .types::<DensityFunctionType>()
+        .any(|typ| typ == DensityFunctionType::FindTopSurface));
+}
+
+fn deserialize(version: McVersion, window_type: WindowType, json: &JsonValue) -> Window {
+    let mut window = Window::new(
+        "test".to_string(),
+        "test".to_string(),
+        window_type,
+        Path::new("/nonexistent"),
+        version,
+    );
+    let errors = window.deserialize(json);
+    assert!(
+        errors.is_empty(),
+        "{:?}",
+        errors.iter().map(|e| e.to_string()).collect::<Vec<_>>()
+    );
+    window
+}
+
+fn nether_path() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/corpus/data/minecraft/worldgen/noise_settings/nether.json")
+}
+
+#[test]
+fn noise_router_is_serialized_with_the_fields_of_the_version() {
+    let json = json::parse(&std::fs::read_to_string(nether_path()).unwrap()).unwrap();
+    let new_router = inputs(
+        McVersion::V1_21_2,
+        NodeTemplate::Output(WindowType::NoiseSettings),
+    );
+    let old_router = inputs(
+        McVersion::V1_21,
+        NodeTemplate::Output(WindowType::NoiseSettings),
+    );
+    assert!(new_router.contains(&"preliminary_surface_level".to_string()));
+    assert!(!new_router.contains(&"initial_density_without_jaggedness".to_string()));
+    assert!(old_router.contains(&"initial_density_without_jaggedness".to_string()));
+    assert!(!old_router.contains(&"preliminary_surface_level".to_string()));
+
+    let old = deserialize(McVersion::V1_21, WindowType::NoiseSettings, &json)
+        .serialize()
+        .unwrap();
+    assert_eq!(old, json);
+    let mut new = json.clone();
+    new["noise_router"].remove("initial_density_without_jaggedness");
+    new["noise_router"]["preliminary_surface_level"] = 0.into();
+    let serialized = deserialize(McVersion::V1_21_2, WindowType::NoiseSettings, &new)
+        .serialize()
+        .unwrap();
+    assert_eq!(serialized, new);
+    // the fields after the removed one are still inside the noise router
+    assert!(serialized["vein_gap"].is_null());
+    assert!(!serialized["noise_router"]["vein_gap"].is_null());
+}
+
+#[test]
+fn density_functions_a_version_lacks_are_kept_as_raw_json() {
+    let json = json::parse(
+        r#"{
+            "type": "minecraft:find_top_surface",
+            "density": "minecraft:overworld/depth",
+            "upper_bound": 320.0,
+            "lower_bound": -64,
+            "cell_height": 8
+        }"#,
+    )
+    .unwrap();
+    let root_type = |window: &Window| {
+        let graph = &window.state.graph;
+        let input = graph[window.root_node].inputs[0].1;
+        graph
+            .connection(input)
+            .map(|output| graph[graph.get_output(output).node].user_data.template)
+    };
+    let new = deserialize(McVersion::V1_21_2, WindowType::DensityFunction, &json);
+    assert_eq!(
+        root_type(&new),
+        Some(NodeTemplate::DensityFunction(
+            DensityFunctionType::FindTopSurface
+        ))
+    );
+    assert_eq!(new.serialize().unwrap(), json);
+    let old = deserialize(McVersion::V1_21, WindowType::DensityFunction, &json);
+    assert_eq!(
+        root_type(&old),
+        Some(NodeTemplate::RawJson(ComplexDataType::DensityFunction))
+    );
+    assert_eq!(old.serialize().unwrap(), json);
+}
+
+#[test]
+fn changing_the_version_rebuilds_every_window_in_one_step() {
+    let json = json::parse(&std::fs::read_to_string(nether_path()).unwrap()).unwrap();
+    let window = deserialize(McVersion::V1_21, WindowType::NoiseSettings, &json);
+    let id = window.identifier();
+    let mut map: [HashMap<Identifier, Window>; WindowType::COUNT] = Default::default();
+    map[WindowType::NoiseSettings as usize].insert(id.clone(), window);
+
+    assert!(App::change_version(&mut map, McVersion::V1_21_2).is_empty());
+    let window = map[WindowType::NoiseSettings as usize]
+        .get_mut(&id)
+        .unwrap();
+    assert_eq!(window.user_state.version, McVersion::V1_21_2);
+    let router = window.serialize().unwrap()["noise_router"].clone();
+    assert!(router.has_key("preliminary_surface_level"));
+    // the field 1.21.2 doesn't read is kept as it was
+    assert_eq!(
+        router["initial_density_without_jaggedness"],
+        json["noise_router"]["initial_density_without_jaggedness"]
+    );
+    window.undo();
+    assert_eq!(window.serialize().unwrap(), json);
+    assert!(!window.history.can_undo());
+
+    // a graph that can't be written as JSON can't be rebuilt, it is reported
+    let mut unfinished = deserialize(McVersion::V1_21, WindowType::NoiseSettings, &json);
+    let root = unfinished.root_node;
+    let surface_rule = unfinished.state.graph[root]
+        .inputs
+        .iter()
+        .find(|(name, _)| name == "surface_rule")
+        .unwrap()
+        .1;
+    unfinished.state.graph.remove_connection(surface_rule);
+    let nodes = unfinished.state.graph.nodes.len();
+    map[WindowType::NoiseSettings as usize].insert(id.clone(), unfinished);
+    let errors = App::change_version(&mut map, McVersion::V1_21_2);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].context().unwrap().window.as_ref(), Some(&id));
+    let unfinished = &map[WindowType::NoiseSettings as usize][&id];
+    assert_eq!(unfinished.state.graph.nodes.len(), nodes);
+    assert_eq!(unfinished.user_state.version, McVersion::V1_21_2);
+}